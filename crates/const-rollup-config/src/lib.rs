@@ -0,0 +1,58 @@
+//! Rollup constants that must be identical in the rollup node and in the zk guest.
+//!
+//! Both the `rollup` crate and the risc0 guests depend on this crate, so the values
+//! below are the single source of truth for them. They are fixed at build time:
+//! set `SOV_CELESTIA_BATCH_NAMESPACE` and `SOV_CELESTIA_PROOF_NAMESPACE` when building
+//! the node (the guest is built in the same cargo invocation and inherits them) to
+//! point the rollup at different Celestia namespaces.
+#![no_std]
+
+/// Length of a Celestia v0 namespace id.
+pub const NAMESPACE_LEN: usize = 10;
+
+/// The namespace used by the rollup to store its batches on Celestia.
+///
+/// Defaults to `sov-test-b`. Batches used to share the `sov-test-p` proof namespace,
+/// which is now rejected, so rollups started with it must be started over.
+pub const ROLLUP_BATCH_NAMESPACE_RAW: [u8; NAMESPACE_LEN] =
+    namespace_from_str(match option_env!("SOV_CELESTIA_BATCH_NAMESPACE") {
+        Some(namespace) => namespace,
+        None => "sov-test-b",
+    });
+
+/// The namespace used by the rollup to store its aggregated zk proofs on Celestia.
+pub const ROLLUP_PROOF_NAMESPACE_RAW: [u8; NAMESPACE_LEN] =
+    namespace_from_str(match option_env!("SOV_CELESTIA_PROOF_NAMESPACE") {
+        Some(namespace) => namespace,
+        None => "sov-test-p",
+    });
+
+// The batch and proof namespaces must not collide, otherwise proofs would be read as batches.
+const _: () = {
+    let mut i = 0;
+    while i < NAMESPACE_LEN && ROLLUP_BATCH_NAMESPACE_RAW[i] == ROLLUP_PROOF_NAMESPACE_RAW[i] {
+        i += 1;
+    }
+    assert!(
+        i < NAMESPACE_LEN,
+        "The rollup batch and proof namespaces must be different"
+    );
+};
+
+/// Converts a namespace string into its raw bytes, failing the build if it is
+/// not exactly [`NAMESPACE_LEN`] bytes long.
+const fn namespace_from_str(namespace: &str) -> [u8; NAMESPACE_LEN] {
+    let bytes = namespace.as_bytes();
+    assert!(
+        bytes.len() == NAMESPACE_LEN,
+        "Celestia namespaces must be exactly 10 bytes long"
+    );
+
+    let mut raw = [0u8; NAMESPACE_LEN];
+    let mut i = 0;
+    while i < NAMESPACE_LEN {
+        raw[i] = bytes[i];
+        i += 1;
+    }
+    raw
+}
@@ -1,56 +1,209 @@
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+/// A guest embedded in this crate.
+struct Guest {
+    /// The package name of the guest crate.
+    package: &'static str,
+    /// The directory of the guest crate, relative to this crate.
+    dir: &'static str,
+    /// The prefix of the generated `<PREFIX>_ELF` and `<PREFIX>_ID` constants.
+    prefix: &'static str,
+}
+
+impl Guest {
+    /// The environment variable pointing at a prebuilt ELF for this guest.
+    fn elf_path_var(&self) -> String {
+        format!("{}_ELF_PATH", self.prefix)
+    }
+
+    fn constants(&self, elf: &[u8], image_id: &[u32; 8]) -> String {
+        format!(
+            r#"
+            pub const {prefix}_ELF: &[u8] = &{elf:?};
+            pub const {prefix}_ID: [u32; 8] = {image_id:?};
+            "#,
+            prefix = self.prefix,
+        )
+    }
+}
+
+const GUESTS: [Guest; 2] = [
+    Guest {
+        package: "sov-demo-prover-guest-celestia",
+        dir: "guest-celestia",
+        prefix: "ROLLUP",
+    },
+    Guest {
+        package: "guest-mock-starter",
+        dir: "guest-mock",
+        prefix: "MOCK_DA",
+    },
+];
 
 fn main() {
     println!("cargo::rerun-if-env-changed=SKIP_GUEST_BUILD");
     println!("cargo::rerun-if-env-changed=OUT_DIR");
+    for guest in &GUESTS {
+        println!("cargo::rerun-if-env-changed={}", guest.elf_path_var());
+    }
 
-    if let Ok(rollup_elf_path) = std::env::var("ROLLUP_ELF_PATH") {
-        println!("Using prebuilt rollup ELF bytes at {rollup_elf_path}");
+    let out_dir = std::env::var_os("OUT_DIR").unwrap();
+    let out_dir = std::path::Path::new(&out_dir);
+    let methods_path = out_dir.join("methods.rs");
 
-        let out_dir = std::env::var_os("OUT_DIR").unwrap();
-        let out_dir = std::path::Path::new(&out_dir);
-        let methods_path = out_dir.join("methods.rs");
+    let (prebuilt, to_build): (Vec<_>, Vec<_>) = GUESTS
+        .iter()
+        .map(|guest| (guest, std::env::var_os(guest.elf_path_var())))
+        .partition(|(_, elf_path)| elf_path.is_some());
 
-        let rollup_elf_bytes = std::fs::read(rollup_elf_path).unwrap();
+    let mut methods = String::new();
 
-        let elf = format!(
-            r#"
-            pub const ROLLUP_ELF: &[u8] = &{rollup_elf_bytes:?};
-            "#
-        );
-
-        std::fs::write(methods_path, elf).expect("Failed to write rollup elf to methods.rs");
+    if to_build.is_empty() {
+        println!("All guests are prebuilt, skipping guest build");
     } else if std::env::var("SKIP_GUEST_BUILD").is_ok() {
         println!("Skipping guest build for CI run");
-        let out_dir = std::env::var_os("OUT_DIR").unwrap();
-        let out_dir = std::path::Path::new(&out_dir);
-        let methods_path = out_dir.join("methods.rs");
+        for (guest, _) in &to_build {
+            methods.push_str(&guest.constants(&[], &[0; 8]));
+        }
+    } else {
+        // Only the guests without a prebuilt ELF are built: `embed_methods` would build
+        // every guest listed in the package metadata.
+        for (guest, _) in &to_build {
+            for entry in build_guest(guest, out_dir) {
+                methods.push_str(&guest.constants(&entry.elf, &entry.image_id));
+            }
+        }
+    }
 
-        let elf = r#"
-            pub const ROLLUP_ELF: &[u8] = &[];
-            pub const MOCK_DA_ELF: &[u8] = &[];
-        "#;
+    for (guest, elf_path) in &prebuilt {
+        let elf_path = PathBuf::from(elf_path.as_ref().unwrap());
+        println!(
+            "Using prebuilt {} ELF bytes at {}",
+            guest.package,
+            elf_path.display()
+        );
+        println!("cargo::rerun-if-changed={}", elf_path.display());
 
-        std::fs::write(methods_path, elf).expect("Failed to write mock rollup elf");
-    } else {
-        let guest_pkg_to_options = get_guest_options();
-        risc0_build::embed_methods_with_options(guest_pkg_to_options);
+        let elf = std::fs::read(&elf_path).unwrap();
+        let image_id = compute_image_id(&elf);
+        verify_prebuilt_elf(&elf_path, &elf, &image_id);
+
+        methods.push_str(&guest.constants(&elf, &image_id));
     }
+
+    std::fs::write(methods_path, methods).expect("Failed to write guest elfs to methods.rs");
 }
 
-fn get_guest_options() -> HashMap<&'static str, risc0_build::GuestOptions> {
-    let mut guest_pkg_to_options = HashMap::new();
+/// Builds the guest crate with the risc0 toolchain and returns its binaries.
+fn build_guest(guest: &Guest, out_dir: &Path) -> Vec<risc0_build::GuestListEntry> {
+    let manifest_dir = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let guest_dir = manifest_dir.join(guest.dir);
+    println!("cargo::rerun-if-changed={}", guest_dir.display());
+
+    let metadata = cargo_metadata::MetadataCommand::new()
+        .manifest_path(guest_dir.join("Cargo.toml"))
+        .no_deps()
+        .exec()
+        .unwrap_or_else(|e| panic!("Failed to read the metadata of {}: {e}", guest.package));
+    let package = metadata
+        .packages
+        .iter()
+        .find(|package| package.name == guest.package)
+        .unwrap_or_else(|| panic!("No package {} in {}", guest.package, guest_dir.display()));
+
+    risc0_build::build_package(package, out_dir.join("riscv-guest"), guest_options())
+        .unwrap_or_else(|e| panic!("Failed to build the {} guest: {e}", guest.package))
+}
+
+fn guest_options() -> risc0_build::GuestOptions {
     let mut features = vec![];
 
     if cfg!(feature = "bench") {
         features.push("bench".to_string());
     }
-    guest_pkg_to_options.insert(
-        "sov-demo-prover-guest-mock",
-        risc0_build::GuestOptions {
-            features,
-            ..Default::default()
-        },
+    risc0_build::GuestOptions {
+        features,
+        ..Default::default()
+    }
+}
+
+/// Computes the risc0 image ID of a guest ELF, which is the code commitment of the guest.
+fn compute_image_id(elf: &[u8]) -> [u32; 8] {
+    let program =
+        risc0_binfmt::Program::load_elf(elf, risc0_zkvm_platform::memory::GUEST_MAX_MEM as u32)
+            .expect("Failed to load the guest ELF");
+    let image = risc0_binfmt::MemoryImage::new(&program, risc0_zkvm_platform::PAGE_SIZE as u32)
+        .expect("Failed to create the memory image of the guest ELF");
+
+    image
+        .compute_id()
+        .as_words()
+        .try_into()
+        .expect("An image ID is 8 words long")
+}
+
+/// Checks a prebuilt ELF against the files next to it:
+/// - `<elf>.sha256`: the hex SHA-256 of the ELF, as written by `sha256sum`.
+/// - `<elf>.image_id`: the image ID as a JSON array of 8 words, as in `inner_code_commitment`.
+///
+/// At least one of them must exist, so that only reproducible artifacts are embedded.
+fn verify_prebuilt_elf(elf_path: &Path, elf: &[u8], image_id: &[u32; 8]) {
+    let sha256_path = PathBuf::from(format!("{}.sha256", elf_path.display()));
+    let image_id_path = PathBuf::from(format!("{}.image_id", elf_path.display()));
+    let mut verified = false;
+
+    if sha256_path.exists() {
+        println!("cargo::rerun-if-changed={}", sha256_path.display());
+        let expected = std::fs::read_to_string(&sha256_path).unwrap();
+        let expected = expected.split_whitespace().next().unwrap_or_default();
+        let actual: String = Sha256::digest(elf)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        assert!(
+            expected.eq_ignore_ascii_case(&actual),
+            "SHA-256 of {} is {actual}, but {} expects {expected}",
+            elf_path.display(),
+            sha256_path.display(),
+        );
+        verified = true;
+    }
+
+    if image_id_path.exists() {
+        println!("cargo::rerun-if-changed={}", image_id_path.display());
+        let expected: Vec<u32> = std::fs::read_to_string(&image_id_path)
+            .unwrap()
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(|word| {
+                word.trim().parse().unwrap_or_else(|_| {
+                    panic!(
+                        "Invalid image ID word {word:?} in {}",
+                        image_id_path.display()
+                    )
+                })
+            })
+            .collect();
+
+        assert!(
+            expected == image_id,
+            "Image ID of {} is {image_id:?}, but {} expects {expected:?}",
+            elf_path.display(),
+            image_id_path.display(),
+        );
+        verified = true;
+    }
+
+    assert!(
+        verified,
+        "Cannot verify the prebuilt ELF {}: add a {} or {} file next to it",
+        elf_path.display(),
+        sha256_path.display(),
+        image_id_path.display(),
     );
-    guest_pkg_to_options
 }
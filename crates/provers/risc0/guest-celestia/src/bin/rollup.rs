@@ -2,6 +2,7 @@
 
 #![no_main]
 
+use const_rollup_config::{ROLLUP_BATCH_NAMESPACE_RAW, ROLLUP_PROOF_NAMESPACE_RAW};
 use sov_celestia_adapter::types::Namespace;
 use sov_celestia_adapter::verifier::CelestiaVerifier;
 use sov_kernels::basic::BasicKernel;
@@ -14,9 +15,9 @@ use sov_state::ZkStorage;
 use stf_starter::runtime::Runtime;
 use stf_starter::StfVerifier;
 
-/// The namespace for the rollup on Celestia. Must be kept in sync with the "rollup/src/lib.rs"
-const ROLLUP_BATCH_NAMESPACE: Namespace = Namespace::const_v0(*b"sov-test-p");
-const ROLLUP_PROOF_NAMESPACE: Namespace = Namespace::const_v0(*b"sov-test-p");
+/// The namespaces of the rollup on Celestia, shared with the rollup node through `const-rollup-config`.
+const ROLLUP_BATCH_NAMESPACE: Namespace = Namespace::const_v0(ROLLUP_BATCH_NAMESPACE_RAW);
+const ROLLUP_PROOF_NAMESPACE: Namespace = Namespace::const_v0(ROLLUP_PROOF_NAMESPACE_RAW);
 
 risc0_zkvm::guest::entry!(main);
 
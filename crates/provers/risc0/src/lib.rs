@@ -1 +1,6 @@
+//! The risc0 guests of the rollup.
+//!
+//! For each guest, this crate exposes its ELF (`ROLLUP_ELF`, `MOCK_DA_ELF`) and its
+//! image ID (`ROLLUP_ID`, `MOCK_DA_ID`). The image ID is the code commitment that ties
+//! proofs to the guest code and must match `inner_code_commitment` in the chain state genesis.
 include!(concat!(env!("OUT_DIR"), "/methods.rs"));
@@ -1,89 +1,269 @@
 //! This binary runs the rollup full node.
 
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
 use anyhow::Context;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::rpc_params;
+use serde::de::DeserializeOwned;
 #[cfg(feature = "celestia_da")]
 use sov_celestia_adapter::CelestiaConfig;
 use sov_kernels::basic::BasicKernelGenesisConfig;
 use sov_kernels::basic::BasicKernelGenesisPaths;
-#[cfg(feature = "mock_da")]
-use sov_mock_da::MockDaConfig;
 use sov_modules_rollup_blueprint::{Rollup, RollupBlueprint};
 #[cfg(feature = "celestia_da")]
 use sov_rollup_starter::celestia_rollup::CelestiaRollup;
+use sov_rollup_starter::code_commitment::{inner_code_commitment, write_chain_state_commitment};
+use sov_rollup_starter::config::{resolve_prover_config, DaLayer, ProverMode, StarterConfig};
+use sov_rollup_starter::config_loader::{read_redacted_rollup_config, read_rollup_config};
+use sov_rollup_starter::genesis_export::export_rollup_genesis;
+use sov_rollup_starter::genesis_generator::generate_genesis;
+use sov_rollup_starter::genesis_layout::{convert_genesis, GenesisLayout};
+use sov_rollup_starter::genesis_root::{check_node_genesis, compute_genesis_root, GenesisRoot};
+use sov_rollup_starter::health::ReadinessReport;
+use sov_rollup_starter::home::{reset_data, NodeLocation, RollupHome};
+use sov_rollup_starter::metrics::{parse_bind_address, DEFAULT_METRICS_BIND_ADDRESS};
+#[cfg(feature = "mock_da")]
+use sov_rollup_starter::mock_da::StarterMockDaConfig;
 #[cfg(feature = "mock_da")]
 use sov_rollup_starter::mock_rollup::MockRollup;
+use sov_rollup_starter::shutdown::{wait_for_signal, Shutdown, DEFAULT_SHUTDOWN_TIMEOUT};
+use sov_rollup_starter::telemetry::{otlp_layer, shutdown_otlp, LogFormat};
+use sov_stf_runner::RollupConfig;
 use sov_stf_runner::RollupProverConfig;
-use sov_stf_runner::{from_toml_path, RollupConfig};
 use stf_starter::genesis_config::GenesisPaths;
+use stf_starter::genesis_validation::{validate_genesis, GenesisError};
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::prelude::*;
-use tracing_subscriber::{fmt, EnvFilter};
-
-#[cfg(all(feature = "mock_da", feature = "celestia_da"))]
-compile_error!("Both mock_da and celestia_da are enabled, but only one should be.");
+use tracing_subscriber::{fmt, EnvFilter, Layer, Registry};
 
 #[cfg(all(not(feature = "mock_da"), not(feature = "celestia_da")))]
-compile_error!("Neither mock_da and celestia_da are enabled, but only one should be.");
-
-// config and genesis for mock da
-#[cfg(all(feature = "mock_da", not(feature = "celestia_da")))]
-const DEFAULT_CONFIG_PATH: &str = "../../rollup_config.toml";
-#[cfg(all(feature = "mock_da", not(feature = "celestia_da")))]
-const DEFAULT_GENESIS_PATH: &str = "../../test-data/genesis/mock/";
-#[cfg(all(feature = "mock_da", not(feature = "celestia_da")))]
-const DEFAULT_KERNEL_GENESIS_PATH: &str = "../../test-data/genesis/mock/chain_state.json";
-
-// config and genesis for local docker celestia
-#[cfg(all(feature = "celestia_da", not(feature = "mock_da")))]
-const DEFAULT_CONFIG_PATH: &str = "../../celestia_rollup_config.toml";
-#[cfg(all(feature = "celestia_da", not(feature = "mock_da")))]
-const DEFAULT_GENESIS_PATH: &str = "../../test-data/genesis/celestia/";
-#[cfg(all(feature = "celestia_da", not(feature = "mock_da")))]
-const DEFAULT_KERNEL_GENESIS_PATH: &str = "../../test-data/genesis/celestia/chain_state.json";
+compile_error!("Neither mock_da nor celestia_da are enabled, but at least one should be.");
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// The path to the rollup config.
-    #[arg(long, default_value = DEFAULT_CONFIG_PATH)]
-    rollup_config_path: String,
+    /// Runs a command instead of the node.
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The home directory of the node, written by `rollup init`. Defaults to `$ROLLUP_HOME`, or to
+    /// `~/.sov-rollup-starter`.
+    #[arg(long, global = true)]
+    home: Option<String>,
+
+    #[command(flatten)]
+    node: NodeArgs,
+}
+
+/// The arguments of the node, given to `rollup run` or to the binary without a command.
+#[derive(clap::Args, Debug)]
+struct NodeArgs {
+    /// The DA layer to run the rollup on. Defaults to the `da_layer` key of the rollup config,
+    /// or to `mock` if the key is not set.
+    #[arg(long, value_enum)]
+    da: Option<DaLayer>,
 
-    /// The path to the genesis config.
-    #[arg(long, default_value = DEFAULT_GENESIS_PATH)]
-    genesis_paths: String,
-    /// The path to the kernel genesis config.
-    #[arg(long, default_value = DEFAULT_KERNEL_GENESIS_PATH)]
-    kernel_genesis_paths: String,
+    /// The path to the rollup config. Defaults to the `config.toml` of the home directory.
+    #[arg(long)]
+    rollup_config_path: Option<String>,
+
+    /// The path to the genesis config: a directory with one file per module, or a single `.json` or `.toml`
+    /// genesis file. Defaults to the `genesis` directory of the home directory.
+    #[arg(long)]
+    genesis_paths: Option<String>,
+    /// The path to the kernel genesis config. Defaults to the `chain_state.json` of the genesis directory. Not
+    /// allowed with a single genesis file, which holds the kernel genesis.
+    #[arg(long)]
+    kernel_genesis_paths: Option<String>,
+
+    /// The prover mode. Overrides the `SOV_PROVER_MODE` environment variable and the `[prover]` section
+    /// of the rollup config.
+    #[arg(long, value_enum)]
+    prover_mode: Option<ProverMode>,
 
     /// The optional path to the log file.
     #[arg(long, default_value = None)]
     log_dir: Option<String>,
 
-    /// The optional path to the log file.
-    #[arg(long, default_value_t = 9845)]
-    metrics: u64,
+    /// The format of the logs, on stdout and in the log file.
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// The OTLP collector to export the traces to over gRPC, e.g. `http://127.0.0.1:4317`. The traces are not
+    /// exported without it.
+    #[arg(long)]
+    otlp_endpoint: Option<String>,
+
+    /// The address to serve the Prometheus metrics on: `HOST:PORT`, or a port on `127.0.0.1`. Defaults to the
+    /// `[metrics]` section of the rollup config, or to `127.0.0.1:9845`.
+    #[arg(long)]
+    metrics: Option<String>,
+
+    /// The seconds the node has to stop after SIGTERM or SIGINT. Defaults to the `[shutdown]` section of the
+    /// rollup config, or to 30 seconds.
+    #[arg(long)]
+    shutdown_timeout: Option<u64>,
 }
 
-fn init_logging(log_dir: Option<String>) -> Option<WorkerGuard> {
-    let stdout_layer = fmt::layer().with_writer(std::io::stdout);
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Writes the default rollup config and genesis of a DA layer into the home directory.
+    Init {
+        /// The DA layer of the config and the genesis.
+        #[arg(long, value_enum, default_value_t = DaLayer::Mock)]
+        da: DaLayer,
+        /// Overwrites the config and the genesis of an initialized home directory. The data and the keys are kept.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Runs the node, like the binary without a command.
+    Run(NodeArgs),
+    /// Removes the data of a stopped node, keeping its config, genesis and keys.
+    Reset {
+        /// The path to the rollup config of the node. Defaults to the `config.toml` of the home directory.
+        #[arg(long)]
+        rollup_config_path: Option<String>,
+        /// Removes the data. Without it, only prints what would be removed.
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Prints the status of a running node, queried from its RPC and REST servers.
+    Status {
+        /// The path to the rollup config of the node, to find its servers. Defaults to the `config.toml` of the
+        /// home directory.
+        #[arg(long)]
+        rollup_config_path: Option<String>,
+    },
+    /// Genesis utilities.
+    #[command(subcommand)]
+    Genesis(GenesisCommand),
+    /// Exports the state of a stopped node at a given height to new genesis files.
+    ExportGenesis {
+        /// The rollup height (slot number) to export.
+        #[arg(long)]
+        height: u64,
+        /// The directory to write the genesis files to.
+        #[arg(long)]
+        output: String,
+        /// The DA layer of the node. Defaults to the `da_layer` key of the rollup config, or to `mock`.
+        #[arg(long, value_enum)]
+        da: Option<DaLayer>,
+        /// The path to the rollup config of the node. Defaults to the `config.toml` of the home directory.
+        #[arg(long)]
+        rollup_config_path: Option<String>,
+        /// The genesis the node was started with. Defaults to the `genesis` directory of the home directory.
+        #[arg(long)]
+        genesis_paths: Option<String>,
+        /// The kernel genesis the node was started with. Defaults to the `chain_state.json` of the genesis
+        /// directory.
+        #[arg(long)]
+        kernel_genesis_paths: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum GenesisCommand {
+    /// Writes the image ID of the guest into the `inner_code_commitment` of a chain state genesis file.
+    WriteCodeCommitment {
+        /// The DA layer of the guest.
+        #[arg(long, value_enum, default_value_t = DaLayer::Mock)]
+        da: DaLayer,
+        /// The chain state genesis file to update. Defaults to the `chain_state.json` of the home directory.
+        #[arg(long)]
+        kernel_genesis_paths: Option<String>,
+    },
+    /// Converts a genesis directory to a single genesis file, or a single genesis file to a directory.
+    Convert {
+        /// The genesis directory, or the `.json` or `.toml` genesis file, to convert.
+        #[arg(long)]
+        from: String,
+        /// The genesis file (`.json` or `.toml`) to write if `from` is a directory, or else the directory to write.
+        #[arg(long)]
+        to: String,
+    },
+    /// Writes the genesis files described by a manifest of keys, balances and roles.
+    Generate {
+        /// The DA layer of the genesis.
+        #[arg(long, value_enum, default_value_t = DaLayer::Mock)]
+        da: DaLayer,
+        /// The genesis manifest, e.g. `../../test-data/genesis/manifest.toml`.
+        #[arg(long)]
+        manifest: String,
+        /// The directory to write the genesis files to.
+        #[arg(long)]
+        output: String,
+    },
+    /// Runs the genesis into a temporary storage and prints the resulting state root and the hash of the inputs.
+    Root {
+        /// The DA layer of the genesis. Defaults to the `da_layer` key of the rollup config, or to `mock`.
+        #[arg(long, value_enum)]
+        da: Option<DaLayer>,
+        /// The path to the rollup config. Defaults to the `config.toml` of the home directory.
+        #[arg(long)]
+        rollup_config_path: Option<String>,
+        /// The path to the genesis directory or single genesis file. Defaults to the `genesis` directory of the
+        /// home directory.
+        #[arg(long)]
+        genesis_paths: Option<String>,
+        /// The path to the kernel genesis config. Defaults to the `chain_state.json` of the genesis directory.
+        #[arg(long)]
+        kernel_genesis_paths: Option<String>,
+    },
+    /// Parses the genesis files and checks them against each other and the rollup config.
+    Validate {
+        /// The DA layer of the genesis. Defaults to the `da_layer` key of the rollup config, or to `mock`.
+        #[arg(long, value_enum)]
+        da: Option<DaLayer>,
+        /// The path to the rollup config. Defaults to the `config.toml` of the home directory.
+        #[arg(long)]
+        rollup_config_path: Option<String>,
+        /// The path to the genesis directory or single genesis file. Defaults to the `genesis` directory of the
+        /// home directory.
+        #[arg(long)]
+        genesis_paths: Option<String>,
+        /// The path to the kernel genesis config. Defaults to the `chain_state.json` of the genesis directory.
+        #[arg(long)]
+        kernel_genesis_paths: Option<String>,
+    },
+}
+
+fn init_logging(
+    log_format: LogFormat,
+    log_dir: Option<String>,
+    otlp_endpoint: Option<&str>,
+) -> anyhow::Result<Option<WorkerGuard>> {
     let filter_layer =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("debug,hyper=info,risc0_zkvm=warn,sov_prover_storage_manager=info,jmt=info,sov_celestia_adapter=info,jsonrpsee_server=info"));
 
-    let subscriber = tracing_subscriber::registry()
-        .with(stdout_layer)
-        .with(filter_layer);
-
-    if let Some(path) = log_dir {
+    let mut layers = vec![fmt_layer(log_format, std::io::stdout)];
+    let guard = log_dir.map(|path| {
         let file_appender = tracing_appender::rolling::daily(path, "rollup.log");
         let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
-        subscriber
-            .with(fmt::layer().with_writer(non_blocking))
-            .init();
-        Some(guard)
-    } else {
-        subscriber.init();
-        None
+        layers.push(fmt_layer(log_format, non_blocking));
+        guard
+    });
+    if let Some(endpoint) = otlp_endpoint {
+        layers.push(otlp_layer(endpoint)?.boxed());
+    }
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(filter_layer)
+        .init();
+    Ok(guard)
+}
+
+fn fmt_layer<W>(log_format: LogFormat, writer: W) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'writer> fmt::MakeWriter<'writer> + Send + Sync + 'static,
+{
+    match log_format {
+        LogFormat::Text => fmt::layer().with_writer(writer).boxed(),
+        LogFormat::Json => fmt::layer().json().with_writer(writer).boxed(),
     }
 }
 
@@ -101,112 +281,663 @@ fn setup_panic_hook() {
     }));
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<()> {
     let args = Args::parse();
+    let (command, mut node_args) = match args.command {
+        Some(Command::Run(node_args)) => (None, node_args),
+        command => (command, args.node),
+    };
 
-    let guard = init_logging(args.log_dir);
+    // The runtime is built by hand, so that closing it can be bounded by the shutdown timeout
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the tokio runtime")?;
+
+    let otlp_endpoint = node_args.otlp_endpoint.take();
+    let guard = {
+        // The OTLP exporter runs on the runtime
+        let _runtime = runtime.enter();
+        init_logging(
+            node_args.log_format,
+            node_args.log_dir.take(),
+            otlp_endpoint.as_deref(),
+        )?
+    };
     setup_panic_hook();
+    let home = RollupHome::resolve(args.home.as_deref())?;
 
-    let metrics_port = args.metrics;
-    let address = format!("127.0.0.1:{}", metrics_port);
-    prometheus_exporter::start(address.parse().unwrap())
-        .expect("Could not start prometheus server");
+    if let Some(command) = command {
+        if let Command::Status { rollup_config_path } = command {
+            let rollup_config_path = config_path(&home, rollup_config_path.as_deref())?;
+            return runtime.block_on(print_status(&rollup_config_path));
+        }
+        let _runtime = runtime.enter();
+        return run_command(command, &home);
+    }
 
-    let rollup_config_path = args.rollup_config_path.as_str();
+    let result = runtime.block_on(run_node(node_args, &home));
+    if otlp_endpoint.is_some() {
+        // Export the last spans while the runtime still runs the exporter
+        shutdown_otlp();
+    }
+    let close_timeout = match &result {
+        Ok(deadline) => deadline.saturating_duration_since(Instant::now()),
+        Err(_) => DEFAULT_SHUTDOWN_TIMEOUT,
+    };
+    // The databases were flushed by `run_node`, dropping the remaining tasks stops the RPC and REST servers
+    runtime.shutdown_timeout(close_timeout);
+    if result.is_ok() {
+        tracing::info!("The node has stopped");
+    }
+    drop(guard);
+    result.map(drop)
+}
+
+/// Runs the node until it stops or receives a shutdown signal, and returns the deadline to close it by.
+async fn run_node(args: NodeArgs, home: &RollupHome) -> anyhow::Result<Instant> {
+    let NodeInputs {
+        da_layer,
+        rollup_config_path,
+        genesis,
+        chain_state_path,
+    } = node_inputs(
+        home,
+        args.da,
+        args.rollup_config_path.as_deref(),
+        args.genesis_paths.as_deref(),
+        args.kernel_genesis_paths.as_deref(),
+    )?;
+    tracing::info!(%da_layer, "Selected DA layer");
 
-    let genesis_paths = args.genesis_paths.as_str();
-    let kernel_genesis_paths = args.kernel_genesis_paths.as_str();
+    tracing::info!(
+        "Effective rollup config from {}:\n{}",
+        rollup_config_path.display(),
+        toml::to_string_pretty(&read_redacted_rollup_config(&rollup_config_path)?)?
+    );
+    let starter_config = StarterConfig::from_toml_path(&rollup_config_path)?;
 
-    let prover_config = parse_prover_config()?;
-    tracing::info!(?prover_config, "Running demo rollup with prover config");
+    let metrics_address = parse_bind_address(
+        args.metrics
+            .as_deref()
+            .or(starter_config.metrics.bind_address.as_deref())
+            .unwrap_or(DEFAULT_METRICS_BIND_ADDRESS),
+    )?;
+    prometheus_exporter::start(metrics_address)
+        .with_context(|| format!("Failed to start the metrics server on {metrics_address}"))?;
+    tracing::info!(%metrics_address, "Serving the Prometheus metrics");
 
-    let rollup = new_rollup(
-        &GenesisPaths::from_dir(genesis_paths),
+    let shutdown_timeout = args
+        .shutdown_timeout
+        .or(starter_config.shutdown.timeout_secs)
+        .map_or(DEFAULT_SHUTDOWN_TIMEOUT, Duration::from_secs);
+
+    let (prover_config, prover_mode_source) =
+        resolve_prover_config(args.prover_mode, &starter_config)?;
+    tracing::info!(
+        ?prover_config,
+        source = %prover_mode_source,
+        "Running demo rollup with prover config"
+    );
+
+    let (rollup, shutdown) = new_rollup(
+        da_layer,
+        &starter_config,
+        &genesis.genesis_paths(),
         &BasicKernelGenesisPaths {
-            chain_state: kernel_genesis_paths.into(),
+            chain_state: chain_state_path,
         },
-        rollup_config_path,
+        &rollup_config_path,
         prover_config,
     )
     .await?;
-    rollup.run().await?;
-    drop(guard);
-    Ok(())
+
+    let runner_shutdown = shutdown.clone();
+    let mut node = tokio::spawn(async move { runner_shutdown.run_runner(rollup.run()).await });
+    let signal = tokio::select! {
+        result = &mut node => {
+            result.context("The rollup task panicked")??;
+            shutdown.close()?;
+            return Ok(Instant::now() + shutdown_timeout);
+        }
+        signal = wait_for_signal() => signal?,
+    };
+
+    let deadline = Instant::now() + shutdown_timeout;
+    tracing::info!(
+        signal,
+        timeout = ?shutdown_timeout,
+        "Received a shutdown signal, stopping the node"
+    );
+    shutdown.stop_runner(&mut node, shutdown_timeout).await?;
+    Ok(deadline)
 }
 
-fn parse_prover_config() -> anyhow::Result<Option<RollupProverConfig>> {
-    if let Some(value) = option_env!("SOV_PROVER_MODE") {
-        let config = std::str::FromStr::from_str(value).map_err(|error| {
-            tracing::error!(value, ?error, "Unknown `SOV_PROVER_MODE` value; aborting");
-            error
-        })?;
-        Ok(Some(config))
+fn run_command(command: Command, home: &RollupHome) -> anyhow::Result<()> {
+    match command {
+        Command::Init { da, force } => {
+            home.init(da, force)?;
+            println!(
+                "Initialized {} for the {da} DA layer, start the node with `rollup run --home {}`",
+                home.root().display(),
+                home.root().display()
+            );
+            Ok(())
+        }
+        Command::Reset {
+            rollup_config_path,
+            yes,
+        } => {
+            let rollup_config_path = config_path(home, rollup_config_path.as_deref())?;
+            let location = NodeLocation::read(&rollup_config_path)?;
+            match reset_data(home, &location, yes)? {
+                None => println!("There is no data at {}", location.storage_path.display()),
+                Some(storage) if yes => println!("Removed {}", storage.display()),
+                Some(storage) => {
+                    println!("Would remove {}", storage.display());
+                    anyhow::bail!("Pass --yes to remove the data");
+                }
+            }
+            Ok(())
+        }
+        Command::Run(_) | Command::Status { .. } => {
+            unreachable!("The node and the status are run on the runtime by `main`")
+        }
+        Command::Genesis(GenesisCommand::WriteCodeCommitment {
+            da,
+            kernel_genesis_paths,
+        }) => {
+            let chain_state_path = kernel_genesis_paths.map_or_else(
+                || home.genesis_dir().join("chain_state.json"),
+                PathBuf::from,
+            );
+            let image_id = inner_code_commitment(da);
+            write_chain_state_commitment(&chain_state_path, &image_id)?;
+            println!(
+                "Wrote inner code commitment {image_id:?} to {}",
+                chain_state_path.display()
+            );
+            Ok(())
+        }
+        Command::Genesis(GenesisCommand::Convert { from, to }) => {
+            convert_genesis(&from, &to)?;
+            println!("Converted the genesis {from} to {to}");
+            Ok(())
+        }
+        Command::Genesis(GenesisCommand::Generate {
+            da,
+            manifest,
+            output,
+        }) => {
+            let output = Path::new(&output);
+            generate_genesis_files(da, Path::new(&manifest), output)?;
+            let errors = validate_genesis_files(
+                da,
+                &GenesisPaths::from_dir(output),
+                &output.join("chain_state.json"),
+                None,
+            )?;
+            for error in &errors {
+                eprintln!("{error}");
+            }
+            if !errors.is_empty() {
+                anyhow::bail!("The generated genesis has {} error(s)", errors.len());
+            }
+            println!("Wrote the genesis files to {}", output.display());
+            Ok(())
+        }
+        Command::ExportGenesis {
+            height,
+            output,
+            da,
+            rollup_config_path,
+            genesis_paths,
+            kernel_genesis_paths,
+        } => {
+            let inputs = node_inputs(
+                home,
+                da,
+                rollup_config_path.as_deref(),
+                genesis_paths.as_deref(),
+                kernel_genesis_paths.as_deref(),
+            )?;
+            export_genesis_files(
+                inputs.da_layer,
+                &inputs.rollup_config_path,
+                height,
+                &inputs.genesis.genesis_paths(),
+                &inputs.chain_state_path,
+                Path::new(&output),
+            )?;
+            println!("Exported the state at height {height} to {output}");
+            Ok(())
+        }
+        Command::Genesis(GenesisCommand::Root {
+            da,
+            rollup_config_path,
+            genesis_paths,
+            kernel_genesis_paths,
+        }) => {
+            let inputs = node_inputs(
+                home,
+                da,
+                rollup_config_path.as_deref(),
+                genesis_paths.as_deref(),
+                kernel_genesis_paths.as_deref(),
+            )?;
+            let genesis_root = genesis_root(
+                inputs.da_layer,
+                &inputs.rollup_config_path,
+                &inputs.genesis.genesis_paths(),
+                &inputs.chain_state_path,
+            )?;
+            println!("Genesis state root: {}", genesis_root.state_root);
+            println!("Genesis inputs hash: {}", genesis_root.inputs_hash);
+            Ok(())
+        }
+        Command::Genesis(GenesisCommand::Validate {
+            da,
+            rollup_config_path,
+            genesis_paths,
+            kernel_genesis_paths,
+        }) => {
+            let NodeInputs {
+                da_layer,
+                rollup_config_path,
+                genesis,
+                chain_state_path,
+            } = node_inputs(
+                home,
+                da,
+                rollup_config_path.as_deref(),
+                genesis_paths.as_deref(),
+                kernel_genesis_paths.as_deref(),
+            )?;
+            let genesis_height = StarterConfig::from_toml_path(&rollup_config_path)?
+                .runner
+                .genesis_height;
+            let errors = validate_genesis_files(
+                da_layer,
+                &genesis.genesis_paths(),
+                &chain_state_path,
+                genesis_height,
+            )?;
+            if errors.is_empty() {
+                println!("The genesis {} is valid", genesis.path().display());
+                return Ok(());
+            }
+            let error_count = errors.len();
+            for error in errors {
+                eprintln!("{}", genesis.locate(error));
+            }
+            anyhow::bail!("Found {error_count} error(s) in the genesis files")
+        }
+    }
+}
+
+/// Exports the state of the node running on `da_layer` with the config at `rollup_config_path`.
+fn export_genesis_files(
+    da_layer: DaLayer,
+    rollup_config_path: &Path,
+    height: u64,
+    genesis_paths: &GenesisPaths,
+    chain_state_path: &Path,
+    output_dir: &Path,
+) -> anyhow::Result<()> {
+    match da_layer {
+        #[cfg(feature = "mock_da")]
+        DaLayer::Mock => {
+            let rollup_config: RollupConfig<StarterMockDaConfig> =
+                read_rollup_config(rollup_config_path)
+                    .context("Failed to read rollup configuration")?;
+            export_rollup_genesis(
+                &MockRollup::default(),
+                &rollup_config,
+                height,
+                genesis_paths,
+                chain_state_path,
+                output_dir,
+            )
+        }
+        #[cfg(feature = "celestia_da")]
+        DaLayer::Celestia => {
+            let rollup_config: RollupConfig<CelestiaConfig> =
+                read_rollup_config(rollup_config_path)
+                    .context("Failed to read rollup configuration")?;
+            export_rollup_genesis(
+                &CelestiaRollup::default(),
+                &rollup_config,
+                height,
+                genesis_paths,
+                chain_state_path,
+                output_dir,
+            )
+        }
+        #[allow(unreachable_patterns)]
+        other => anyhow::bail!("This binary was built without support for the {other} DA layer"),
+    }
+}
+
+/// Computes the genesis state root of the rollup running on `da_layer`.
+fn genesis_root(
+    da_layer: DaLayer,
+    rollup_config_path: &Path,
+    genesis_paths: &GenesisPaths,
+    chain_state_path: &Path,
+) -> anyhow::Result<GenesisRoot> {
+    match da_layer {
+        #[cfg(feature = "mock_da")]
+        DaLayer::Mock => {
+            let rollup_config: RollupConfig<StarterMockDaConfig> =
+                read_rollup_config(rollup_config_path)
+                    .context("Failed to read rollup configuration")?;
+            compute_genesis_root(
+                &MockRollup::default(),
+                &rollup_config,
+                genesis_paths,
+                chain_state_path,
+            )
+        }
+        #[cfg(feature = "celestia_da")]
+        DaLayer::Celestia => {
+            let rollup_config: RollupConfig<CelestiaConfig> =
+                read_rollup_config(rollup_config_path)
+                    .context("Failed to read rollup configuration")?;
+            compute_genesis_root(
+                &CelestiaRollup::default(),
+                &rollup_config,
+                genesis_paths,
+                chain_state_path,
+            )
+        }
+        #[allow(unreachable_patterns)]
+        other => anyhow::bail!("This binary was built without support for the {other} DA layer"),
+    }
+}
+
+/// Generates the genesis files with the spec of the rollup running on `da_layer`.
+fn generate_genesis_files(
+    da_layer: DaLayer,
+    manifest_path: &Path,
+    output_dir: &Path,
+) -> anyhow::Result<()> {
+    match da_layer {
+        #[cfg(feature = "mock_da")]
+        DaLayer::Mock => generate_genesis::<<MockRollup as RollupBlueprint>::NativeSpec>(
+            manifest_path,
+            output_dir,
+            da_layer,
+        ),
+        #[cfg(feature = "celestia_da")]
+        DaLayer::Celestia => generate_genesis::<<CelestiaRollup as RollupBlueprint>::NativeSpec>(
+            manifest_path,
+            output_dir,
+            da_layer,
+        ),
+        #[allow(unreachable_patterns)]
+        other => anyhow::bail!("This binary was built without support for the {other} DA layer"),
+    }
+}
+
+/// Validates the genesis files with the spec of the rollup running on `da_layer`.
+fn validate_genesis_files(
+    da_layer: DaLayer,
+    genesis_paths: &GenesisPaths,
+    chain_state_path: &Path,
+    genesis_height: Option<u64>,
+) -> anyhow::Result<Vec<GenesisError>> {
+    match da_layer {
+        #[cfg(feature = "mock_da")]
+        DaLayer::Mock => Ok(validate_genesis::<
+            <MockRollup as RollupBlueprint>::NativeSpec,
+            <MockRollup as RollupBlueprint>::DaSpec,
+        >(genesis_paths, chain_state_path, genesis_height)),
+        #[cfg(feature = "celestia_da")]
+        DaLayer::Celestia => Ok(validate_genesis::<
+            <CelestiaRollup as RollupBlueprint>::NativeSpec,
+            <CelestiaRollup as RollupBlueprint>::DaSpec,
+        >(genesis_paths, chain_state_path, genesis_height)),
+        #[allow(unreachable_patterns)]
+        other => anyhow::bail!("This binary was built without support for the {other} DA layer"),
+    }
+}
+
+/// Picks the DA layer from the `--da` flag, falling back to the `da_layer` key of the rollup config.
+fn select_da_layer(da_flag: Option<DaLayer>, rollup_config_path: &Path) -> anyhow::Result<DaLayer> {
+    if let Some(da_layer) = da_flag {
+        return Ok(da_layer);
+    }
+
+    let da_layer = StarterConfig::from_toml_path(rollup_config_path)?.da_layer;
+    Ok(da_layer.unwrap_or(DaLayer::Mock))
+}
+
+/// The rollup config given by `--rollup-config-path`, or else the one of the home directory.
+fn config_path(home: &RollupHome, rollup_config_path: Option<&str>) -> anyhow::Result<PathBuf> {
+    match rollup_config_path {
+        Some(path) => Ok(path.into()),
+        None => home.require_config(),
+    }
+}
+
+/// The files a node runs from.
+struct NodeInputs {
+    da_layer: DaLayer,
+    rollup_config_path: PathBuf,
+    genesis: GenesisLayout,
+    chain_state_path: PathBuf,
+}
+
+/// Resolves the files of a node from the flags, falling back to the files of the home directory.
+fn node_inputs(
+    home: &RollupHome,
+    da_flag: Option<DaLayer>,
+    rollup_config_path: Option<&str>,
+    genesis_paths: Option<&str>,
+    kernel_genesis_paths: Option<&str>,
+) -> anyhow::Result<NodeInputs> {
+    let rollup_config_path = config_path(home, rollup_config_path)?;
+    let da_layer = select_da_layer(da_flag, &rollup_config_path)?;
+    let genesis =
+        GenesisLayout::open(genesis_paths.map_or_else(|| home.genesis_dir(), PathBuf::from))?;
+    let chain_state_path = genesis.chain_state_path(kernel_genesis_paths)?;
+    Ok(NodeInputs {
+        da_layer,
+        rollup_config_path,
+        genesis,
+        chain_state_path,
+    })
+}
+
+/// Prints the head slot and the readiness of the node with the rollup config at `rollup_config_path`.
+async fn print_status(rollup_config_path: &Path) -> anyhow::Result<()> {
+    let location = NodeLocation::read(rollup_config_path)?;
+    let rpc_url = format!("http://{}", location.rpc_address);
+    let client = HttpClientBuilder::default().build(&rpc_url)?;
+    let head: serde_json::Value = client
+        .request("ledger_getHead", rpc_params![])
+        .await
+        .with_context(|| format!("No node answers on {rpc_url}, is it running?"))?;
+
+    println!("Node: {rpc_url}");
+    if head.is_null() {
+        println!("Latest slot: none yet");
     } else {
-        Ok(None)
+        println!(
+            "Latest slot: {} ({})",
+            head["number"],
+            head["hash"].as_str().unwrap_or("unknown hash")
+        );
+    }
+
+    // `/readyz` answers with a report whether the node is ready or not
+    let readyz_url = format!("http://{}/readyz", location.rest_address);
+    let report: ReadinessReport = reqwest::get(&readyz_url)
+        .await
+        .with_context(|| format!("Failed to query {readyz_url}"))?
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse the answer of {readyz_url}"))?;
+    println!("Ready: {}", if report.ready { "yes" } else { "no" });
+    println!("Synced DA height: {}", report.synced_da_height);
+    match report.da_head_height {
+        Some(height) => println!("DA head height: {height}"),
+        None => println!("DA head height: unknown"),
+    }
+    for (name, check) in report.checks.iter().filter(|(_, check)| !check.ok) {
+        println!("Failing check {name}: {}", check.message);
+    }
+    Ok(())
+}
+
+/// A rollup running on one of the DA layers supported by this binary.
+enum StarterRollup {
+    #[cfg(feature = "mock_da")]
+    Mock(Rollup<MockRollup>),
+    #[cfg(feature = "celestia_da")]
+    Celestia(Rollup<CelestiaRollup>),
+}
+
+impl StarterRollup {
+    async fn run(self) -> anyhow::Result<()> {
+        match self {
+            #[cfg(feature = "mock_da")]
+            StarterRollup::Mock(rollup) => rollup.run().await,
+            #[cfg(feature = "celestia_da")]
+            StarterRollup::Celestia(rollup) => rollup.run().await,
+        }
     }
 }
 
-#[cfg(all(feature = "mock_da", not(feature = "celestia_da")))]
 async fn new_rollup(
+    da_layer: DaLayer,
+    starter_config: &StarterConfig,
     rt_genesis_paths: &GenesisPaths,
     kernel_genesis_paths: &BasicKernelGenesisPaths,
-    rollup_config_path: &str,
+    rollup_config_path: &Path,
     prover_config: Option<RollupProverConfig>,
-) -> Result<Rollup<MockRollup>, anyhow::Error> {
-    tracing::info!("Reading rollup config from {rollup_config_path:?}");
+) -> Result<(StarterRollup, Shutdown), anyhow::Error> {
+    match da_layer {
+        #[cfg(feature = "mock_da")]
+        DaLayer::Mock => new_mock_rollup(
+            starter_config,
+            rt_genesis_paths,
+            kernel_genesis_paths,
+            rollup_config_path,
+            prover_config,
+        )
+        .await
+        .map(|(rollup, shutdown)| (StarterRollup::Mock(rollup), shutdown)),
+        #[cfg(feature = "celestia_da")]
+        DaLayer::Celestia => new_celestia_rollup(
+            starter_config,
+            rt_genesis_paths,
+            kernel_genesis_paths,
+            rollup_config_path,
+            prover_config,
+        )
+        .await
+        .map(|(rollup, shutdown)| (StarterRollup::Celestia(rollup), shutdown)),
+        #[allow(unreachable_patterns)]
+        other => anyhow::bail!("This binary was built without support for the {other} DA layer"),
+    }
+}
 
-    let rollup_config: RollupConfig<MockDaConfig> =
-        from_toml_path(rollup_config_path).context("Failed to read rollup configuration")?;
+fn read_chain_state<T: DeserializeOwned>(
+    kernel_genesis_paths: &BasicKernelGenesisPaths,
+) -> anyhow::Result<T> {
+    Ok(serde_json::from_str(
+        &std::fs::read_to_string(&kernel_genesis_paths.chain_state)
+            .context("Failed to read chain state")?,
+    )?)
+}
+
+#[cfg(feature = "mock_da")]
+async fn new_mock_rollup(
+    starter_config: &StarterConfig,
+    rt_genesis_paths: &GenesisPaths,
+    kernel_genesis_paths: &BasicKernelGenesisPaths,
+    rollup_config_path: &Path,
+    prover_config: Option<RollupProverConfig>,
+) -> Result<(Rollup<MockRollup>, Shutdown), anyhow::Error> {
+    tracing::info!("Reading rollup config from {rollup_config_path:?}");
 
-    let mock_rollup = MockRollup {};
+    let rollup_config: RollupConfig<StarterMockDaConfig> =
+        read_rollup_config(rollup_config_path).context("Failed to read rollup configuration")?;
+    rollup_config
+        .da
+        .validate()
+        .and_then(|()| {
+            rollup_config
+                .da
+                .check_prover_config(prover_config.as_ref(), false)
+        })
+        .with_context(|| format!("Invalid `[da]` section in {}", rollup_config_path.display()))?;
 
+    let mock_rollup = MockRollup::default().with_health_config(starter_config.health.clone());
+    check_node_genesis(
+        &mock_rollup,
+        &rollup_config,
+        starter_config,
+        DaLayer::Mock,
+        rt_genesis_paths,
+        &kernel_genesis_paths.chain_state,
+    )?;
     let kernel_genesis = BasicKernelGenesisConfig {
-        chain_state: serde_json::from_str(
-            &std::fs::read_to_string(&kernel_genesis_paths.chain_state)
-                .context("Failed to read chain state")?,
-        )?,
+        chain_state: read_chain_state(kernel_genesis_paths)?,
     };
 
-    mock_rollup
+    let shutdown = mock_rollup.shutdown().clone();
+    let rollup = mock_rollup
         .create_new_rollup(
             rt_genesis_paths,
             kernel_genesis,
             rollup_config,
             prover_config,
         )
-        .await
+        .await?;
+    Ok((rollup, shutdown))
 }
 
-#[cfg(all(feature = "celestia_da", not(feature = "mock_da")))]
-async fn new_rollup(
+#[cfg(feature = "celestia_da")]
+async fn new_celestia_rollup(
+    starter_config: &StarterConfig,
     rt_genesis_paths: &GenesisPaths,
     kernel_genesis_paths: &BasicKernelGenesisPaths,
-    rollup_config_path: &str,
+    rollup_config_path: &Path,
     prover_config: Option<RollupProverConfig>,
-) -> Result<Rollup<CelestiaRollup>, anyhow::Error> {
+) -> Result<(Rollup<CelestiaRollup>, Shutdown), anyhow::Error> {
     tracing::info!(
         "Starting celestia rollup with config {}",
-        rollup_config_path
+        rollup_config_path.display()
     );
 
     let rollup_config: RollupConfig<CelestiaConfig> =
-        from_toml_path(rollup_config_path).context("Failed to read rollup configuration")?;
+        read_rollup_config(rollup_config_path).context("Failed to read rollup configuration")?;
+    starter_config.celestia.check_namespaces()?;
 
+    let celestia_rollup =
+        CelestiaRollup::default().with_health_config(starter_config.health.clone());
+    check_node_genesis(
+        &celestia_rollup,
+        &rollup_config,
+        starter_config,
+        DaLayer::Celestia,
+        rt_genesis_paths,
+        &kernel_genesis_paths.chain_state,
+    )?;
     let kernel_genesis = BasicKernelGenesisConfig {
-        chain_state: serde_json::from_str(
-            &std::fs::read_to_string(&kernel_genesis_paths.chain_state)
-                .context("Failed to read chain state")?,
-        )?,
+        chain_state: read_chain_state(kernel_genesis_paths)?,
     };
 
-    let mock_rollup = CelestiaRollup {};
-    mock_rollup
+    let shutdown = celestia_rollup.shutdown().clone();
+    let rollup = celestia_rollup
         .create_new_rollup(
             rt_genesis_paths,
             kernel_genesis,
             rollup_config,
             prover_config,
         )
-        .await
+        .await?;
+    Ok((rollup, shutdown))
 }
@@ -1,19 +1,38 @@
 //! This binary defines a cli wallet for interacting
 //! with the rollup.
+//!
+//! The DA layer of the rollup is selected with the `SOV_DA_LAYER` environment
+//! variable (`mock` or `celestia`) and defaults to `mock`.
 
 use sov_modules_api::cli::{FileNameArg, JsonStringArg};
 use sov_modules_rollup_blueprint::WalletBlueprint;
-#[cfg(all(feature = "celestia_da", not(feature = "mock_da")))]
-use sov_rollup_starter::celestia_rollup::CelestiaRollup as StarterRollup;
-#[cfg(all(feature = "mock_da", not(feature = "celestia_da")))]
-use sov_rollup_starter::mock_rollup::MockRollup as StarterRollup;
+#[cfg(feature = "celestia_da")]
+use sov_rollup_starter::celestia_rollup::CelestiaRollup;
+use sov_rollup_starter::config::DaLayer;
+#[cfg(feature = "mock_da")]
+use sov_rollup_starter::mock_rollup::MockRollup;
 use stf_starter::runtime::RuntimeSubcommand;
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
-    StarterRollup::run_wallet::<
-        RuntimeSubcommand<FileNameArg, _, _>,
-        RuntimeSubcommand<JsonStringArg, _, _>,
-    >()
-    .await
+    match DaLayer::from_env()?.unwrap_or(DaLayer::Mock) {
+        #[cfg(feature = "mock_da")]
+        DaLayer::Mock => {
+            MockRollup::run_wallet::<
+                RuntimeSubcommand<FileNameArg, _, _>,
+                RuntimeSubcommand<JsonStringArg, _, _>,
+            >()
+            .await
+        }
+        #[cfg(feature = "celestia_da")]
+        DaLayer::Celestia => {
+            CelestiaRollup::run_wallet::<
+                RuntimeSubcommand<FileNameArg, _, _>,
+                RuntimeSubcommand<JsonStringArg, _, _>,
+            >()
+            .await
+        }
+        #[allow(unreachable_patterns)]
+        other => anyhow::bail!("This wallet was built without support for the {other} DA layer"),
+    }
 }
@@ -2,6 +2,7 @@
 //! StarterRollup provides a minimal self-contained rollup implementation
 
 use async_trait::async_trait;
+use const_rollup_config::{ROLLUP_BATCH_NAMESPACE_RAW, ROLLUP_PROOF_NAMESPACE_RAW};
 use sov_celestia_adapter::types::Namespace;
 use sov_celestia_adapter::verifier::{CelestiaSpec, CelestiaVerifier, RollupParams};
 use sov_celestia_adapter::{CelestiaConfig, CelestiaService};
@@ -15,7 +16,6 @@ use sov_modules_rollup_blueprint::RollupBlueprint;
 use sov_modules_stf_blueprint::{RuntimeEndpoints, StfBlueprint};
 use sov_prover_storage_manager::ProverStorageManager;
 use sov_risc0_adapter::host::Risc0Host;
-use sov_rollup_interface::zk::aggregated_proof::CodeCommitment;
 use sov_rollup_interface::zk::{Zkvm, ZkvmGuest, ZkvmHost};
 use sov_sequencer::SequencerDb;
 use sov_state::config::Config as StorageConfig;
@@ -28,21 +28,46 @@ use stf_starter::authentication::ModAuth;
 use stf_starter::Runtime;
 use tokio::sync::watch;
 
-/// The rollup stores its data in the namespace b"sov-test" on Celestia
-/// You can change this constant to point your rollup at a different namespace
-const ROLLUP_BATCH_NAMESPACE: Namespace = Namespace::const_v0(*b"sov-test-p");
+use crate::code_commitment::aggregated_code_commitment;
+use crate::config::HealthSection;
+use crate::health::{add_health_routes, Readiness, DEFAULT_MAX_DA_LAG_BLOCKS};
+use crate::metrics::{count_requests, spawn_node_metrics};
+use crate::shutdown::{GracefulDaService, Shutdown};
+use crate::telemetry::TracedProverService;
 
-/// The rollup stores the zk proofs in the namespace b"sov-test-p" on Celestia.
-const ROLLUP_PROOF_NAMESPACE: Namespace = Namespace::const_v0(*b"sov-test-p");
+/// The rollup stores its data in this namespace on Celestia.
+/// The namespaces are shared with the zk guest through `const-rollup-config`, see that crate
+/// to point your rollup at different namespaces.
+pub const ROLLUP_BATCH_NAMESPACE: Namespace = Namespace::const_v0(ROLLUP_BATCH_NAMESPACE_RAW);
+
+/// The rollup stores the zk proofs in this namespace on Celestia.
+pub const ROLLUP_PROOF_NAMESPACE: Namespace = Namespace::const_v0(ROLLUP_PROOF_NAMESPACE_RAW);
 
 /// Rollup with [`CelestiaDaService`].
-pub struct CelestiaRollup {}
+#[derive(Default)]
+pub struct CelestiaRollup {
+    shutdown: Shutdown,
+    health: HealthSection,
+}
+
+impl CelestiaRollup {
+    /// Sets the `[health]` section of the rollup config, read by `/readyz`.
+    pub fn with_health_config(mut self, health: HealthSection) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// The handle to stop the rollup at a slot boundary.
+    pub fn shutdown(&self) -> &Shutdown {
+        &self.shutdown
+    }
+}
 
 /// This is the place, where all the rollup components come together, and
 /// they can be easily swapped with alternative implementations as needed.
 #[async_trait]
 impl RollupBlueprint for CelestiaRollup {
-    type DaService = CelestiaService;
+    type DaService = GracefulDaService<CelestiaService>;
     type DaSpec = CelestiaSpec;
     type DaConfig = CelestiaConfig;
     /// Inner Zkvm representing the rollup circuit
@@ -77,13 +102,15 @@ impl RollupBlueprint for CelestiaRollup {
     type ZkKernel =
         ConsensusStateTracker<BasicKernel<Self::ZkSpec, Self::DaSpec>, Self::ZkSpec, Self::DaSpec>;
 
-    type ProverService = ParallelProverService<
-        <<Self::NativeSpec as Spec>::Storage as Storage>::Root,
-        <<Self::NativeSpec as Spec>::Storage as Storage>::Witness,
-        Self::DaService,
-        Self::InnerZkvmHost,
-        Self::OuterZkvmHost,
-        StfBlueprint<Self::ZkSpec, Self::DaSpec, Self::ZkRuntime, Self::ZkKernel>,
+    type ProverService = TracedProverService<
+        ParallelProverService<
+            <<Self::NativeSpec as Spec>::Storage as Storage>::Root,
+            <<Self::NativeSpec as Spec>::Storage as Storage>::Witness,
+            Self::DaService,
+            Self::InnerZkvmHost,
+            Self::OuterZkvmHost,
+            StfBlueprint<Self::ZkSpec, Self::DaSpec, Self::ZkRuntime, Self::ZkKernel>,
+        >,
     >;
 
     fn create_outer_code_commitment(
@@ -101,24 +128,46 @@ impl RollupBlueprint for CelestiaRollup {
         rollup_config: &RollupConfig<Self::DaConfig>,
     ) -> anyhow::Result<RuntimeEndpoints> {
         let sequencer = rollup_config.da.own_celestia_address.clone();
-        sov_modules_rollup_blueprint::register_endpoints::<
+        let endpoints = sov_modules_rollup_blueprint::register_endpoints::<
             Self,
             ModAuth<Self::NativeSpec, Self::DaSpec>,
-        >(storage, ledger_db, sequencer_db, da_service, sequencer)
+        >(
+            storage.clone(),
+            ledger_db,
+            sequencer_db,
+            da_service,
+            sequencer,
+        )?;
+        self.shutdown
+            .close_databases_on_stop(ledger_db, sequencer_db, storage.clone());
+        let readiness = Readiness::<Self::NativeSpec, _>::new(
+            storage.clone(),
+            da_service.clone(),
+            rollup_config.storage.path.clone(),
+            rollup_config.runner.genesis_height,
+            self.health
+                .max_da_lag_blocks
+                .unwrap_or(DEFAULT_MAX_DA_LAG_BLOCKS),
+            self.shutdown.clone(),
+        );
+        let endpoints = add_health_routes(endpoints, readiness);
+        spawn_node_metrics::<Self::NativeSpec, _>(ledger_db.clone(), storage, da_service.clone());
+        count_requests(endpoints)
     }
 
     async fn create_da_service(
         &self,
         rollup_config: &RollupConfig<Self::DaConfig>,
     ) -> Self::DaService {
-        CelestiaService::new(
+        let da_service = CelestiaService::new(
             rollup_config.da.clone(),
             RollupParams {
                 rollup_batch_namespace: ROLLUP_BATCH_NAMESPACE,
                 rollup_proof_namespace: ROLLUP_PROOF_NAMESPACE,
             },
         )
-        .await
+        .await;
+        GracefulDaService::new(da_service, self.shutdown.clone())
     }
 
     async fn create_prover_service(
@@ -137,15 +186,15 @@ impl RollupBlueprint for CelestiaRollup {
             rollup_proof_namespace: ROLLUP_PROOF_NAMESPACE,
         };
 
-        ParallelProverService::new_with_default_workers(
+        TracedProverService::new(ParallelProverService::new_with_default_workers(
             inner_vm,
             outer_vm,
             zk_stf,
             da_verifier,
             prover_config,
             zk_storage,
-            CodeCommitment::default(),
-        )
+            aggregated_code_commitment(&risc0_starter::ROLLUP_ID),
+        ))
     }
 
     fn create_storage_manager(
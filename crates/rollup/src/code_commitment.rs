@@ -0,0 +1,131 @@
+//! Code commitments tie the rollup proofs to the guest code that is deployed.
+//!
+//! The inner code commitment is the risc0 image ID of the guest ELF. It is stored in the
+//! `inner_code_commitment` field of the chain state genesis, and the node checks it against
+//! the guest it was built with when it starts.
+
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use sov_rollup_interface::zk::aggregated_proof::CodeCommitment;
+
+use crate::config::DaLayer;
+
+/// A risc0 image ID.
+pub type ImageId = [u32; 8];
+
+const INNER_CODE_COMMITMENT_KEY: &str = "inner_code_commitment";
+
+/// An image ID of all zeros, used as a placeholder in development genesis files and
+/// as the image ID of guests that were not built (`SKIP_GUEST_BUILD`).
+const PLACEHOLDER_IMAGE_ID: ImageId = [0; 8];
+
+/// Returns the image ID of the guest for `da_layer`.
+pub fn inner_code_commitment(da_layer: DaLayer) -> ImageId {
+    match da_layer {
+        DaLayer::Mock => risc0_starter::MOCK_DA_ID,
+        DaLayer::Celestia => risc0_starter::ROLLUP_ID,
+    }
+}
+
+/// Encodes an image ID as the code commitment of the aggregated proofs.
+pub fn aggregated_code_commitment(image_id: &ImageId) -> CodeCommitment {
+    CodeCommitment(
+        image_id
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect(),
+    )
+}
+
+/// Checks that the chain state genesis at `chain_state_path` commits to the `expected` image ID.
+///
+/// A placeholder (all zeros) commitment in the genesis is an error, unless `allow_placeholder` is set
+/// for development setups, which then only get a warning. A guest that was not built can't be checked,
+/// so its placeholder image ID only logs a warning.
+pub fn check_chain_state_commitment(
+    chain_state_path: impl AsRef<Path>,
+    expected: &ImageId,
+    allow_placeholder: bool,
+) -> anyhow::Result<()> {
+    let chain_state_path = chain_state_path.as_ref();
+    let chain_state = read_chain_state(chain_state_path)?;
+    let genesis: ImageId = serde_json::from_value(
+        chain_state
+            .get(INNER_CODE_COMMITMENT_KEY)
+            .cloned()
+            .with_context(|| {
+                format!(
+                    "Missing `{INNER_CODE_COMMITMENT_KEY}` in {}",
+                    chain_state_path.display()
+                )
+            })?,
+    )
+    .with_context(|| {
+        format!(
+            "Invalid `{INNER_CODE_COMMITMENT_KEY}` in {}",
+            chain_state_path.display()
+        )
+    })?;
+
+    if genesis == *expected {
+        return Ok(());
+    }
+
+    if genesis == PLACEHOLDER_IMAGE_ID && *expected != PLACEHOLDER_IMAGE_ID {
+        if !allow_placeholder {
+            bail!(
+                "{} has a placeholder inner code commitment; write the image ID {expected:?} of the guest with `rollup genesis write-code-commitment`, or set `allow_placeholder_code_commitment` in the rollup config for development",
+                chain_state_path.display()
+            );
+        }
+        tracing::warn!(
+            path = %chain_state_path.display(),
+            ?expected,
+            "The chain state genesis has a placeholder inner code commitment; proofs are not tied to the deployed guest"
+        );
+        return Ok(());
+    }
+
+    if *expected == PLACEHOLDER_IMAGE_ID {
+        tracing::warn!(
+            ?genesis,
+            "The guest was not built, the inner code commitment of the genesis can't be checked"
+        );
+        return Ok(());
+    }
+
+    bail!(
+        "The inner code commitment {genesis:?} in {} does not match the image ID {expected:?} of the guest this node was built with",
+        chain_state_path.display()
+    )
+}
+
+/// Writes `image_id` as the inner code commitment of the chain state genesis at `chain_state_path`.
+pub fn write_chain_state_commitment(
+    chain_state_path: impl AsRef<Path>,
+    image_id: &ImageId,
+) -> anyhow::Result<()> {
+    let chain_state_path = chain_state_path.as_ref();
+    let mut chain_state = read_chain_state(chain_state_path)?;
+    let object = chain_state
+        .as_object_mut()
+        .with_context(|| format!("{} must contain a JSON object", chain_state_path.display()))?;
+    object.insert(
+        INNER_CODE_COMMITMENT_KEY.to_string(),
+        serde_json::to_value(image_id)?,
+    );
+
+    std::fs::write(
+        chain_state_path,
+        serde_json::to_string_pretty(&chain_state)? + "\n",
+    )
+    .with_context(|| format!("Failed to write {}", chain_state_path.display()))
+}
+
+fn read_chain_state(chain_state_path: &Path) -> anyhow::Result<serde_json::Value> {
+    let contents = std::fs::read_to_string(chain_state_path)
+        .with_context(|| format!("Failed to read {}", chain_state_path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {}", chain_state_path.display()))
+}
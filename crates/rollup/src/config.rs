@@ -0,0 +1,268 @@
+//! Rollup settings that are read from the rollup TOML file on top of the
+//! [`sov_stf_runner::RollupConfig`] sections.
+
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use const_rollup_config::{ROLLUP_BATCH_NAMESPACE_RAW, ROLLUP_PROOF_NAMESPACE_RAW};
+use serde::Deserialize;
+use sov_stf_runner::RollupProverConfig;
+
+use crate::config_loader::read_rollup_config;
+
+/// The DA layers supported by the rollup binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum DaLayer {
+    /// In-memory mock DA, for local development and tests.
+    Mock,
+    /// Celestia DA.
+    Celestia,
+}
+
+impl DaLayer {
+    /// Environment variable used to select the DA layer when there is no
+    /// command line flag for it (e.g. in the CLI wallet).
+    pub const ENV_VAR: &'static str = "SOV_DA_LAYER";
+
+    /// Reads the DA layer from [`Self::ENV_VAR`], if it is set.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        match std::env::var(Self::ENV_VAR) {
+            Ok(value) => {
+                let da_layer = <Self as clap::ValueEnum>::from_str(&value, true).map_err(|e| {
+                    anyhow::anyhow!("Invalid `{}` value {value:?}: {e}", Self::ENV_VAR)
+                })?;
+                Ok(Some(da_layer))
+            }
+            Err(std::env::VarError::NotPresent) => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read `{}`", Self::ENV_VAR)),
+        }
+    }
+}
+
+impl core::fmt::Display for DaLayer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DaLayer::Mock => write!(f, "mock"),
+            DaLayer::Celestia => write!(f, "celestia"),
+        }
+    }
+}
+
+/// The starter-specific keys of the rollup TOML file. Sections owned by
+/// [`sov_stf_runner::RollupConfig`] are ignored here, except for the few keys of
+/// [`RunnerSection`] that the starter checks.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StarterConfig {
+    /// The DA layer this config file is written for.
+    pub da_layer: Option<DaLayer>,
+    /// The `[prover]` section.
+    #[serde(default)]
+    pub prover: ProverSection,
+    /// The `[celestia]` section.
+    #[serde(default)]
+    pub celestia: CelestiaSection,
+    /// The keys of the `[runner]` section read by the starter.
+    #[serde(default)]
+    pub runner: RunnerSection,
+    /// The `[metrics]` section.
+    #[serde(default)]
+    pub metrics: MetricsSection,
+    /// The `[shutdown]` section.
+    #[serde(default)]
+    pub shutdown: ShutdownSection,
+    /// The `[health]` section.
+    #[serde(default)]
+    pub health: HealthSection,
+    /// The genesis state root printed by `rollup genesis root`. The node refuses to start from a genesis with
+    /// another root.
+    pub expected_genesis_root: Option<String>,
+    /// Accepts a chain state genesis with a placeholder (all zeros) inner code commitment, with a warning.
+    /// For development only: the proofs of such a rollup are not tied to the deployed guest.
+    #[serde(default)]
+    pub allow_placeholder_code_commitment: bool,
+}
+
+impl StarterConfig {
+    /// Reads the starter-specific keys from the rollup TOML file at `path`, with the values of the environment
+    /// applied (see [`crate::config_loader`]).
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        read_rollup_config(path)
+    }
+}
+
+/// The `[celestia]` section of the rollup TOML file.
+///
+/// The namespaces are compiled into both the node and the zk guest (see `const-rollup-config`),
+/// so they can't be changed here. Setting them pins the namespaces the operator expects,
+/// and the node refuses to start if it was built with different ones.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CelestiaSection {
+    /// The namespace of the rollup batches.
+    pub batch_namespace: Option<String>,
+    /// The namespace of the aggregated zk proofs.
+    pub proof_namespace: Option<String>,
+}
+
+impl CelestiaSection {
+    /// Checks the configured namespaces against the ones the node and the guest were built with.
+    pub fn check_namespaces(&self) -> anyhow::Result<()> {
+        if let (Some(batch), Some(proof)) = (&self.batch_namespace, &self.proof_namespace) {
+            if batch == proof {
+                bail!("The batch and proof namespaces must be different, both are {batch:?}");
+            }
+        }
+        check_namespace(
+            "batch",
+            self.batch_namespace.as_deref(),
+            &ROLLUP_BATCH_NAMESPACE_RAW,
+        )?;
+        check_namespace(
+            "proof",
+            self.proof_namespace.as_deref(),
+            &ROLLUP_PROOF_NAMESPACE_RAW,
+        )
+    }
+}
+
+fn check_namespace(kind: &str, configured: Option<&str>, built: &[u8]) -> anyhow::Result<()> {
+    match configured {
+        Some(configured) if configured.as_bytes() != built => bail!(
+            "The configured {kind} namespace {configured:?} differs from the {kind} namespace {:?} \
+             the rollup was built with; rebuild with `SOV_CELESTIA_{}_NAMESPACE={configured}`",
+            String::from_utf8_lossy(built),
+            kind.to_uppercase(),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// The keys of the `[runner]` section of the rollup TOML file read by the starter.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RunnerSection {
+    /// The DA height of the rollup genesis, which the `genesis_da_height` of the chain state must match.
+    pub genesis_height: Option<u64>,
+}
+
+/// The `[metrics]` section of the rollup TOML file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MetricsSection {
+    /// The address to serve the Prometheus metrics on: `HOST:PORT`, or a port on `127.0.0.1`.
+    /// Overridden by the `--metrics` flag.
+    pub bind_address: Option<String>,
+}
+
+/// The `[shutdown]` section of the rollup TOML file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ShutdownSection {
+    /// The seconds the node has to stop after SIGTERM or SIGINT: to finish the current slot, then to close the
+    /// servers and the databases. Overridden by the `--shutdown-timeout` flag.
+    pub timeout_secs: Option<u64>,
+}
+
+/// The `[health]` section of the rollup TOML file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HealthSection {
+    /// How many DA blocks the node can be behind the DA head and still be ready on `/readyz`. Defaults to 10.
+    pub max_da_lag_blocks: Option<u64>,
+}
+
+/// The `[prover]` section of the rollup TOML file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProverSection {
+    /// The prover mode. Proving is disabled when unset.
+    pub mode: Option<ProverMode>,
+}
+
+/// The prover modes of the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ProverMode {
+    /// The slots are not proven.
+    Skip,
+    /// The proofs are simulated, without running the zkVM.
+    Simulate,
+    /// The guest is executed in the zkVM, without generating the proofs.
+    Execute,
+    /// The proofs are generated.
+    Prove,
+}
+
+impl From<ProverMode> for RollupProverConfig {
+    fn from(mode: ProverMode) -> Self {
+        match mode {
+            ProverMode::Skip => RollupProverConfig::Skip,
+            ProverMode::Simulate => RollupProverConfig::Simulate,
+            ProverMode::Execute => RollupProverConfig::Execute,
+            ProverMode::Prove => RollupProverConfig::Prove,
+        }
+    }
+}
+
+/// Where the effective prover mode was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProverModeSource {
+    /// The `--prover-mode` command line flag.
+    CliFlag,
+    /// The `SOV_PROVER_MODE` environment variable.
+    EnvVar,
+    /// The `[prover]` section of the rollup config.
+    ConfigFile,
+    /// No prover mode was set, proving is disabled.
+    Unset,
+}
+
+impl core::fmt::Display for ProverModeSource {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProverModeSource::CliFlag => write!(f, "--prover-mode flag"),
+            ProverModeSource::EnvVar => write!(f, "{} environment variable", PROVER_MODE_ENV_VAR),
+            ProverModeSource::ConfigFile => write!(f, "[prover] config section"),
+            ProverModeSource::Unset => write!(f, "default"),
+        }
+    }
+}
+
+/// Environment variable that overrides the prover mode of the rollup config.
+pub const PROVER_MODE_ENV_VAR: &str = "SOV_PROVER_MODE";
+
+/// Resolves the prover mode, in order of precedence, from the command line flag,
+/// the [`PROVER_MODE_ENV_VAR`] environment variable read at run time, and the
+/// `[prover]` section of the rollup config.
+pub fn resolve_prover_config(
+    cli_flag: Option<ProverMode>,
+    config: &StarterConfig,
+) -> anyhow::Result<(Option<RollupProverConfig>, ProverModeSource)> {
+    let env_var = match std::env::var(PROVER_MODE_ENV_VAR) {
+        Ok(value) => Some(value),
+        Err(std::env::VarError::NotPresent) => None,
+        Err(e) => return Err(e).with_context(|| format!("Failed to read `{PROVER_MODE_ENV_VAR}`")),
+    };
+
+    let (mode, source) = resolve_prover_mode(cli_flag, env_var.as_deref(), config)?;
+    Ok((mode.map(RollupProverConfig::from), source))
+}
+
+/// Resolves the prover mode like [`resolve_prover_config`], with the value of the environment variable given.
+pub fn resolve_prover_mode(
+    cli_flag: Option<ProverMode>,
+    env_var: Option<&str>,
+    config: &StarterConfig,
+) -> anyhow::Result<(Option<ProverMode>, ProverModeSource)> {
+    if let Some(mode) = cli_flag {
+        return Ok((Some(mode), ProverModeSource::CliFlag));
+    }
+    if let Some(value) = env_var {
+        let mode = <ProverMode as clap::ValueEnum>::from_str(value, true).map_err(|e| {
+            anyhow::anyhow!(
+                "Unknown prover mode {value:?} from {}: {e}",
+                ProverModeSource::EnvVar
+            )
+        })?;
+        return Ok((Some(mode), ProverModeSource::EnvVar));
+    }
+    match config.prover.mode {
+        Some(mode) => Ok((Some(mode), ProverModeSource::ConfigFile)),
+        None => Ok((None, ProverModeSource::Unset)),
+    }
+}
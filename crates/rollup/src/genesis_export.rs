@@ -0,0 +1,48 @@
+//! Exports the state of a rollup to new genesis files, for `rollup export-genesis`. See
+//! [`stf_starter::genesis_export`] for what is exported.
+
+use std::path::Path;
+
+use anyhow::Context;
+use sov_modules_api::{ApiStateAccessor, Spec};
+use sov_modules_rollup_blueprint::RollupBlueprint;
+use sov_rollup_interface::storage::HierarchicalStorageManager;
+use sov_stf_runner::RollupConfig;
+use stf_starter::genesis_config::GenesisPaths;
+use stf_starter::genesis_export::export_genesis;
+
+/// Exports the state of the rollup at `height` to `output_dir`, starting from the genesis it was created
+/// with at `source`. The kernel genesis at `chain_state_path` is copied as is: update its `genesis_da_height`
+/// before starting a new chain.
+///
+/// The node must be stopped, since the storage can only be opened by one process.
+pub fn export_rollup_genesis<R>(
+    rollup: &R,
+    rollup_config: &RollupConfig<R::DaConfig>,
+    height: u64,
+    source: &GenesisPaths,
+    chain_state_path: &Path,
+    output_dir: &Path,
+) -> anyhow::Result<()>
+where
+    R: RollupBlueprint,
+    R::StorageManager:
+        HierarchicalStorageManager<R::DaSpec, NativeStorage = <R::NativeSpec as Spec>::Storage>,
+{
+    let storage_manager = rollup
+        .create_storage_manager(rollup_config)
+        .context("Failed to open the rollup storage")?;
+    let storage = storage_manager
+        .create_finalized_storage()
+        .context("Failed to read the finalized rollup storage")?;
+
+    let mut state = ApiStateAccessor::<R::NativeSpec>::new(&storage);
+    state.set_archival_version(height);
+    let exported = export_genesis::<R::NativeSpec, R::DaSpec>(source, &mut state)
+        .with_context(|| format!("Failed to export the state at height {height}"))?;
+
+    exported.write_to_dir(source, output_dir)?;
+    std::fs::copy(chain_state_path, output_dir.join("chain_state.json"))
+        .with_context(|| format!("Failed to copy {}", chain_state_path.display()))?;
+    Ok(())
+}
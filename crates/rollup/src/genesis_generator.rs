@@ -0,0 +1,242 @@
+//! Generates a consistent set of genesis files from a manifest, for `rollup genesis generate`.
+//!
+//! The manifest is a TOML file listing the keys of the network, in the `PrivateKeyAndAddress` format of
+//! `test-data/keys`, with their gas token balances and roles. See `test-data/genesis/manifest.toml`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sov_cli::wallet_state::PrivateKeyAndAddress;
+use sov_modules_api::{CryptoSpec, PublicKey, Spec};
+
+use crate::code_commitment::{inner_code_commitment, ImageId};
+use crate::config::DaLayer;
+
+/// A role of a key in the genesis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// The preferred sequencer. Exactly one key must have this role.
+    Sequencer,
+    /// An initial prover, bonded with the `prover_incentives.bond` of the manifest.
+    Prover,
+    /// An authorized minter of the gas token.
+    Minter,
+    /// A circuit breaker admin.
+    Admin,
+}
+
+/// A key of the manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestKey {
+    /// The key file, relative to the manifest.
+    pub path: PathBuf,
+    /// The initial gas token balance of the key.
+    #[serde(default)]
+    pub balance: u64,
+    /// The roles of the key.
+    #[serde(default)]
+    pub roles: Vec<Role>,
+}
+
+/// The `[sequencer]` section of the manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SequencerSection {
+    /// The DA address the sequencer posts batches from.
+    pub da_address: String,
+    /// The minimum bond of the sequencers.
+    pub minimum_bond: u64,
+}
+
+/// The `[prover_incentives]` section of the manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProverIncentivesSection {
+    /// The minimum bond of the provers.
+    pub minimum_bond: u64,
+    /// The penalty of a prover submitting an invalid proof.
+    pub proving_penalty: u64,
+    /// The bond of each initial prover. Defaults to `minimum_bond`.
+    pub bond: Option<u64>,
+}
+
+/// A genesis manifest.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisManifest {
+    /// The name of the gas token.
+    pub gas_token_name: String,
+    /// The DA height of the rollup genesis, which must match the `runner.genesis_height` of the rollup config.
+    #[serde(default)]
+    pub genesis_da_height: u64,
+    /// An EVM genesis file to copy, relative to the manifest. Defaults to the mock EVM genesis, without its
+    /// prefunded development account.
+    pub evm: Option<PathBuf>,
+    /// The sequencer settings.
+    pub sequencer: SequencerSection,
+    /// The prover incentives settings.
+    pub prover_incentives: ProverIncentivesSection,
+    /// The keys of the network.
+    pub keys: Vec<ManifestKey>,
+}
+
+impl GenesisManifest {
+    /// Reads a manifest from a TOML file.
+    pub fn from_toml_path(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read genesis manifest {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse genesis manifest {}", path.display()))
+    }
+}
+
+const DEFAULT_EVM_GENESIS: &str = include_str!("../../../test-data/genesis/mock/evm.json");
+
+/// Writes the module genesis files and the kernel `chain_state.json` described by the manifest at
+/// `manifest_path` to `output_dir`, for a rollup running on `da_layer`.
+pub fn generate_genesis<S: Spec>(
+    manifest_path: &Path,
+    output_dir: &Path,
+    da_layer: DaLayer,
+) -> anyhow::Result<()> {
+    let manifest = GenesisManifest::from_toml_path(manifest_path)?;
+    let manifest_dir = manifest_path.parent().unwrap_or(Path::new("."));
+
+    let mut accounts = Vec::new();
+    let mut balances = Vec::new();
+    let mut minters = Vec::new();
+    let mut admins = Vec::new();
+    let mut provers = Vec::new();
+    let mut sequencers = Vec::new();
+    let prover_bond = manifest
+        .prover_incentives
+        .bond
+        .unwrap_or(manifest.prover_incentives.minimum_bond);
+
+    for key in &manifest.keys {
+        let key_path = manifest_dir.join(&key.path);
+        let key_and_address = read_key::<S>(&key_path)?;
+        let address = key_and_address.address.to_string();
+        let credential_id = key_and_address
+            .private_key
+            .pub_key()
+            .credential_id::<<S::CryptoSpec as CryptoSpec>::Hasher>();
+
+        accounts.push(json!({ "credential_id": credential_id, "address": address }));
+        balances.push(json!([address, key.balance]));
+        for role in &key.roles {
+            match role {
+                Role::Sequencer => sequencers.push(address.clone()),
+                Role::Prover => provers.push(json!([address, prover_bond])),
+                Role::Minter => minters.push(address.clone()),
+                Role::Admin => admins.push(address.clone()),
+            }
+        }
+    }
+
+    let seq_rollup_address = match sequencers.as_slice() {
+        [sequencer] => sequencer.clone(),
+        _ => bail!(
+            "Exactly one key must have the `sequencer` role, found {}",
+            sequencers.len()
+        ),
+    };
+
+    let evm = match &manifest.evm {
+        Some(path) => {
+            let path = manifest_dir.join(path);
+            serde_json::from_str(
+                &std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?,
+            )
+            .with_context(|| format!("Failed to parse {}", path.display()))?
+        }
+        None => {
+            let mut evm: serde_json::Value = serde_json::from_str(DEFAULT_EVM_GENESIS)?;
+            // The mock genesis funds a well-known development key, which must not hold funds on a real network
+            evm["data"] = json!([]);
+            evm
+        }
+    };
+
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+    let write = |file: &str, value: serde_json::Value| write_json(&output_dir.join(file), &value);
+
+    write("accounts.json", json!({ "accounts": accounts }))?;
+    write(
+        "bank.json",
+        json!({
+            "gas_token_config": {
+                "token_name": manifest.gas_token_name,
+                "address_and_balances": balances,
+                "authorized_minters": minters,
+                "salt": 0,
+            },
+            "tokens": [],
+        }),
+    )?;
+    write(
+        "ibc.json",
+        json!({ "clients": [], "connections": [], "channels": [] }),
+    )?;
+    write("ibc_transfer.json", json!({ "escrows": [] }))?;
+    write(
+        "sequencer_registry.json",
+        json!({
+            "seq_rollup_address": seq_rollup_address,
+            "seq_da_address": manifest.sequencer.da_address,
+            "minimum_bond": manifest.sequencer.minimum_bond,
+            "is_preferred_sequencer": true,
+        }),
+    )?;
+    write(
+        "prover_incentives.json",
+        json!({
+            "proving_penalty": manifest.prover_incentives.proving_penalty,
+            "minimum_bond": manifest.prover_incentives.minimum_bond,
+            "initial_provers": provers,
+        }),
+    )?;
+    write("evm.json", evm)?;
+    write("circuit_breaker.json", json!({ "admins": admins }))?;
+    write(
+        "chain_state.json",
+        chain_state(&inner_code_commitment(da_layer), manifest.genesis_da_height),
+    )?;
+
+    Ok(())
+}
+
+fn read_key<S: Spec>(path: &Path) -> anyhow::Result<PrivateKeyAndAddress<S>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read key file {}", path.display()))?;
+    let key_and_address: PrivateKeyAndAddress<S> = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse key file {}", path.display()))?;
+    if !key_and_address.is_matching_to_default() {
+        bail!(
+            "The address of key file {} does not match its private key",
+            path.display()
+        );
+    }
+    Ok(key_and_address)
+}
+
+fn chain_state(image_id: &ImageId, genesis_da_height: u64) -> serde_json::Value {
+    json!({
+        "current_time": { "secs": 0, "nanos": 0 },
+        "inner_code_commitment": image_id,
+        "outer_code_commitment": [0u8; 32],
+        "genesis_da_height": genesis_da_height,
+    })
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> anyhow::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)? + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,98 @@
+//! Picks the genesis layout from the `--genesis-paths` argument: a directory with one file per module, or a single
+//! genesis file (see [`GenesisFile`]) holding every module section plus the kernel `chain_state`.
+//!
+//! A single genesis file is unpacked into a temporary directory, so that the rest of the node reads both layouts
+//! the same way.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use stf_starter::genesis_config::{GenesisFile, GenesisPaths, GENESIS_SECTIONS};
+use stf_starter::genesis_validation::GenesisError;
+use tempfile::TempDir;
+
+/// The genesis given to the node, in either layout.
+pub struct GenesisLayout {
+    genesis_dir: PathBuf,
+    genesis_file: Option<PathBuf>,
+    _unpacked: Option<TempDir>,
+}
+
+impl GenesisLayout {
+    /// Opens the genesis at `path`, a directory or a single `.json` or `.toml` genesis file.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if path.is_dir() {
+            return Ok(Self {
+                genesis_dir: path.to_path_buf(),
+                genesis_file: None,
+                _unpacked: None,
+            });
+        }
+
+        let unpacked = tempfile::tempdir().context("Failed to create a genesis directory")?;
+        GenesisFile::read(path)?.write_to_dir(unpacked.path())?;
+        tracing::debug!(
+            genesis_file = %path.display(),
+            dir = %unpacked.path().display(),
+            "Unpacked the genesis file"
+        );
+        Ok(Self {
+            genesis_dir: unpacked.path().to_path_buf(),
+            genesis_file: Some(path.to_path_buf()),
+            _unpacked: Some(unpacked),
+        })
+    }
+
+    /// The genesis directory or single genesis file the layout was opened from.
+    pub fn path(&self) -> &Path {
+        self.genesis_file.as_deref().unwrap_or(&self.genesis_dir)
+    }
+
+    /// The paths of the module genesis files.
+    pub fn genesis_paths(&self) -> GenesisPaths {
+        GenesisPaths::from_dir(&self.genesis_dir)
+    }
+
+    /// The path of the kernel `chain_state` genesis: the `chain_state` section of a single genesis file,
+    /// or else `kernel_genesis_paths`, defaulting to the `chain_state.json` of the genesis directory.
+    pub fn chain_state_path(&self, kernel_genesis_paths: Option<&str>) -> anyhow::Result<PathBuf> {
+        match (&self.genesis_file, kernel_genesis_paths) {
+            (Some(genesis_file), Some(_)) => bail!(
+                "The kernel genesis is read from the `chain_state` section of {}, remove --kernel-genesis-paths",
+                genesis_file.display()
+            ),
+            (_, Some(kernel_genesis_paths)) => Ok(kernel_genesis_paths.into()),
+            (_, None) => Ok(self.genesis_dir.join("chain_state.json")),
+        }
+    }
+
+    /// Points an error in an unpacked file to its section of the single genesis file.
+    pub fn locate(&self, error: GenesisError) -> GenesisError {
+        let Some(genesis_file) = &self.genesis_file else {
+            return error;
+        };
+        let section = GENESIS_SECTIONS
+            .iter()
+            .find(|(_, file)| error.file == self.genesis_dir.join(file));
+        match section {
+            Some((section, _)) => GenesisError {
+                file: genesis_file.clone(),
+                path: error.path.replacen('$', &format!("$.{section}"), 1),
+                message: error.message,
+            },
+            None => error,
+        }
+    }
+}
+
+/// Converts the genesis at `from` to the other layout at `to`: a directory to a single file, or a single
+/// file to a directory.
+pub fn convert_genesis(from: impl AsRef<Path>, to: impl AsRef<Path>) -> anyhow::Result<()> {
+    let from = from.as_ref();
+    if from.is_dir() {
+        GenesisFile::from_dir(from)?.write(to)
+    } else {
+        GenesisFile::read(from)?.write_to_dir(to)
+    }
+}
@@ -0,0 +1,209 @@
+//! The genesis state root, for `rollup genesis root` and the genesis checks of the node at startup.
+//!
+//! The runtime and kernel genesis are run into a temporary storage, so that operators can compare the resulting
+//! state root, and the hash of the genesis inputs, before starting a node.
+
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use sha2::{Digest, Sha256};
+use sov_kernels::basic::{BasicKernel, BasicKernelGenesisConfig};
+use sov_modules_api::Spec;
+use sov_modules_rollup_blueprint::RollupBlueprint;
+use sov_modules_stf_blueprint::StfBlueprint;
+use sov_rollup_interface::stf::StateTransitionFunction;
+use sov_rollup_interface::storage::HierarchicalStorageManager;
+use sov_state::Storage;
+use sov_stf_runner::RollupConfig;
+use stf_starter::genesis_config::{GenesisFile, GenesisPaths};
+use stf_starter::Runtime;
+
+use crate::code_commitment::{check_chain_state_commitment, inner_code_commitment};
+use crate::config::{DaLayer, StarterConfig};
+
+/// The result of running the genesis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisRoot {
+    /// The state root after the runtime and kernel genesis, as `0x`-prefixed hex.
+    pub state_root: String,
+    /// The SHA-256 of the canonical JSON of the genesis inputs (see [`GenesisFile::to_canonical_json`]), as
+    /// `0x`-prefixed hex.
+    pub inputs_hash: String,
+}
+
+/// Runs the genesis at `genesis_paths` and `chain_state_path` into a temporary storage, and returns its state root.
+pub fn compute_genesis_root<R>(
+    rollup: &R,
+    rollup_config: &RollupConfig<R::DaConfig>,
+    genesis_paths: &GenesisPaths,
+    chain_state_path: &Path,
+) -> anyhow::Result<GenesisRoot>
+where
+    R: RollupBlueprint<
+        NativeRuntime = Runtime<<R as RollupBlueprint>::NativeSpec, <R as RollupBlueprint>::DaSpec>,
+        NativeKernel = BasicKernel<
+            <R as RollupBlueprint>::NativeSpec,
+            <R as RollupBlueprint>::DaSpec,
+        >,
+    >,
+    R::StorageManager:
+        HierarchicalStorageManager<R::DaSpec, NativeStorage = <R::NativeSpec as Spec>::Storage>,
+    <<R::NativeSpec as Spec>::Storage as Storage>::Root: AsRef<[u8]>,
+    RollupConfig<R::DaConfig>: Clone,
+{
+    let inputs = GenesisFile::from_paths(genesis_paths, chain_state_path)?;
+    let inputs_hash = Sha256::digest(inputs.to_canonical_json());
+
+    let kernel_genesis = BasicKernelGenesisConfig {
+        chain_state: serde_json::from_slice(
+            &std::fs::read(chain_state_path)
+                .with_context(|| format!("Failed to read {}", chain_state_path.display()))?,
+        )
+        .with_context(|| format!("Failed to parse {}", chain_state_path.display()))?,
+    };
+    let genesis_params =
+        rollup.create_genesis_config(genesis_paths, kernel_genesis, rollup_config)?;
+
+    let storage_dir = tempfile::tempdir().context("Failed to create a temporary storage")?;
+    let mut temporary_config = rollup_config.clone();
+    temporary_config.storage.path = storage_dir.path().to_path_buf();
+    let mut storage_manager = rollup.create_storage_manager(&temporary_config)?;
+    let storage = storage_manager
+        .create_finalized_storage()
+        .context("Failed to create a temporary storage")?;
+
+    let stf = StfBlueprint::<R::NativeSpec, R::DaSpec, R::NativeRuntime, R::NativeKernel>::new();
+    let (state_root, _) = stf.init_chain(storage, genesis_params);
+
+    Ok(GenesisRoot {
+        state_root: format!("0x{}", hex::encode(state_root.as_ref())),
+        inputs_hash: format!("0x{}", hex::encode(inputs_hash)),
+    })
+}
+
+/// The storage version of the genesis state.
+const GENESIS_VERSION: u64 = 0;
+
+/// Returns the genesis state root of the storage of `rollup_config`, as `0x`-prefixed hex, or `None` if the storage
+/// is new, so that the genesis has not been committed yet.
+///
+/// The node must be stopped, since the storage can only be opened by one process.
+pub fn stored_genesis_root<R>(
+    rollup: &R,
+    rollup_config: &RollupConfig<R::DaConfig>,
+) -> anyhow::Result<Option<String>>
+where
+    R: RollupBlueprint,
+    R::StorageManager:
+        HierarchicalStorageManager<R::DaSpec, NativeStorage = <R::NativeSpec as Spec>::Storage>,
+    <<R::NativeSpec as Spec>::Storage as Storage>::Root: AsRef<[u8]>,
+{
+    let storage_path = &rollup_config.storage.path;
+    let is_new = match std::fs::read_dir(storage_path) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => true,
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", storage_path.display()))
+        }
+    };
+    if is_new {
+        return Ok(None);
+    }
+
+    let storage_manager = rollup
+        .create_storage_manager(rollup_config)
+        .context("Failed to open the rollup storage")?;
+    let storage = storage_manager
+        .create_finalized_storage()
+        .context("Failed to read the finalized rollup storage")?;
+    match storage.get_root_hash(GENESIS_VERSION) {
+        Ok(root) => Ok(Some(format!("0x{}", hex::encode(root.as_ref())))),
+        // The storage was created, but the node stopped before committing the genesis
+        Err(_) if storage.is_empty() => Ok(None),
+        Err(e) => Err(e).with_context(|| {
+            format!(
+                "Failed to read the genesis state root of the storage at {}",
+                storage_path.display()
+            )
+        }),
+    }
+}
+
+/// Checks the genesis of the node before it starts, after its DA config: the code commitment of the kernel genesis at
+/// `chain_state_path` (see [`check_chain_state_commitment`]), then the genesis root (see [`check_genesis_root`]).
+///
+/// The genesis root is only computed if there is a root to compare it with: the `expected_genesis_root` of the
+/// rollup config, or the genesis root of an existing storage.
+pub fn check_node_genesis<R>(
+    rollup: &R,
+    rollup_config: &RollupConfig<R::DaConfig>,
+    starter_config: &StarterConfig,
+    da_layer: DaLayer,
+    genesis_paths: &GenesisPaths,
+    chain_state_path: &Path,
+) -> anyhow::Result<()>
+where
+    R: RollupBlueprint<
+        NativeRuntime = Runtime<<R as RollupBlueprint>::NativeSpec, <R as RollupBlueprint>::DaSpec>,
+        NativeKernel = BasicKernel<
+            <R as RollupBlueprint>::NativeSpec,
+            <R as RollupBlueprint>::DaSpec,
+        >,
+    >,
+    R::StorageManager:
+        HierarchicalStorageManager<R::DaSpec, NativeStorage = <R::NativeSpec as Spec>::Storage>,
+    <<R::NativeSpec as Spec>::Storage as Storage>::Root: AsRef<[u8]>,
+    RollupConfig<R::DaConfig>: Clone,
+{
+    check_chain_state_commitment(
+        chain_state_path,
+        &inner_code_commitment(da_layer),
+        starter_config.allow_placeholder_code_commitment,
+    )?;
+
+    let expected_root = starter_config.expected_genesis_root.as_deref();
+    let stored_root = stored_genesis_root(rollup, rollup_config)?;
+    if expected_root.is_none() && stored_root.is_none() {
+        return Ok(());
+    }
+    let genesis_root =
+        compute_genesis_root(rollup, rollup_config, genesis_paths, chain_state_path)?;
+    tracing::info!(
+        state_root = %genesis_root.state_root,
+        inputs_hash = %genesis_root.inputs_hash,
+        "Computed the genesis state root"
+    );
+    check_genesis_root(&genesis_root, expected_root, stored_root.as_deref())
+}
+
+/// Checks the genesis root before the node starts: it must match `expected_root` (the `expected_genesis_root` of
+/// the rollup config) if set, and the `stored_root` of the existing storage (see [`stored_genesis_root`]), if any.
+pub fn check_genesis_root(
+    genesis_root: &GenesisRoot,
+    expected_root: Option<&str>,
+    stored_root: Option<&str>,
+) -> anyhow::Result<()> {
+    let state_root = &genesis_root.state_root;
+    if let Some(expected_root) = expected_root {
+        if !same_root(expected_root, state_root) {
+            bail!(
+                "The genesis state root {state_root} differs from the `expected_genesis_root` {expected_root} of the \
+                 rollup config; check the genesis files with `rollup genesis root`"
+            );
+        }
+    }
+    if let Some(stored_root) = stored_root {
+        if !same_root(stored_root, state_root) {
+            bail!(
+                "The storage was created from a genesis with state root {stored_root}, not {state_root}; remove the \
+                 storage or start from the same genesis"
+            );
+        }
+    }
+    Ok(())
+}
+
+fn same_root(a: &str, b: &str) -> bool {
+    let normalize = |root: &str| root.trim_start_matches("0x").to_ascii_lowercase();
+    normalize(a) == normalize(b)
+}
@@ -0,0 +1,251 @@
+//! The `/healthz` and `/readyz` routes of the REST server, for load balancers.
+//!
+//! `/healthz` answers as long as the node serves requests. `/readyz` answers `200 OK` only if the node is ready
+//! to serve up-to-date state, and `503 Service Unavailable` otherwise, with a [`ReadinessReport`] explaining why.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use sov_modules_api::{ApiStateAccessor, Spec};
+use sov_modules_stf_blueprint::RuntimeEndpoints;
+use sov_rollup_interface::da::BlockHeaderTrait;
+use sov_rollup_interface::services::da::DaService;
+use stf_starter::Runtime;
+use tokio::sync::{watch, Mutex};
+
+use crate::shutdown::Shutdown;
+
+/// How far behind the DA head the node can be and still be ready, unless configured.
+pub const DEFAULT_MAX_DA_LAG_BLOCKS: u64 = 10;
+
+/// How long `/readyz` waits for the DA service before reporting it unreachable.
+const DA_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a readiness report is served to the next `/readyz` requests, so that frequent probes don't each
+/// query the DA service and write to the storage.
+const REPORT_CACHE_DURATION: Duration = Duration::from_secs(1);
+
+/// The prefix of the files written and removed under the storage path to check that it is writable.
+const STORAGE_PROBE_PREFIX: &str = ".readyz_probe";
+
+/// The body of `/readyz`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    /// Whether all the checks passed.
+    pub ready: bool,
+    /// The DA height of the latest slot processed by the node.
+    pub synced_da_height: u64,
+    /// The height of the head block of the DA layer, if it could be read.
+    pub da_head_height: Option<u64>,
+    /// The result of each check: `shutdown`, `sync`, `da` and `storage`.
+    pub checks: BTreeMap<String, CheckResult>,
+}
+
+/// The result of one readiness check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// Whether the check passed.
+    pub ok: bool,
+    /// What was checked, or why it failed.
+    pub message: String,
+}
+
+impl CheckResult {
+    fn new(ok: bool, message: impl Into<String>) -> Self {
+        Self {
+            ok,
+            message: message.into(),
+        }
+    }
+}
+
+/// The state the readiness of the node is checked against.
+pub struct Readiness<S: Spec, Da: DaService> {
+    runtime: Runtime<S, Da::Spec>,
+    storage: watch::Receiver<S::Storage>,
+    da_service: Da,
+    storage_path: PathBuf,
+    genesis_da_height: u64,
+    max_da_lag_blocks: u64,
+    shutdown: Shutdown,
+    /// The latest report and when it was made.
+    cached: Mutex<Option<(Instant, ReadinessReport)>>,
+}
+
+impl<S: Spec, Da: DaService> Readiness<S, Da> {
+    /// Checks the node with the given `storage` and `da_service`. The node is ready once the DA height of its
+    /// latest slot (`genesis_da_height` before the first one) is within `max_da_lag_blocks` of the DA head.
+    pub fn new(
+        storage: watch::Receiver<S::Storage>,
+        da_service: Da,
+        storage_path: PathBuf,
+        genesis_da_height: u64,
+        max_da_lag_blocks: u64,
+        shutdown: Shutdown,
+    ) -> Self {
+        Self {
+            runtime: Runtime::default(),
+            storage,
+            da_service,
+            storage_path,
+            genesis_da_height,
+            max_da_lag_blocks,
+            shutdown,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Runs the checks, or returns the report of the previous run if it is recent. Concurrent requests wait for the
+    /// same run. A shutdown is reported right away.
+    pub async fn check(&self) -> ReadinessReport {
+        let mut cached = self.cached.lock().await;
+        if let Some((checked_at, report)) = &*cached {
+            if checked_at.elapsed() < REPORT_CACHE_DURATION && !self.shutdown.is_requested() {
+                return report.clone();
+            }
+        }
+        let report = self.run_checks().await;
+        *cached = Some((Instant::now(), report.clone()));
+        report
+    }
+
+    async fn run_checks(&self) -> ReadinessReport {
+        let mut checks = BTreeMap::new();
+
+        let shutting_down = self.shutdown.is_requested();
+        checks.insert(
+            "shutdown".to_string(),
+            CheckResult::new(
+                !shutting_down,
+                if shutting_down {
+                    "The node is shutting down"
+                } else {
+                    "The node is running"
+                },
+            ),
+        );
+
+        let synced_da_height = self.synced_da_height();
+        let da_head_height =
+            match tokio::time::timeout(DA_TIMEOUT, self.da_service.get_head_block_header()).await {
+                Ok(Ok(header)) => {
+                    checks.insert(
+                        "da".to_string(),
+                        CheckResult::new(true, "The DA service is reachable"),
+                    );
+                    Some(header.height())
+                }
+                Ok(Err(e)) => {
+                    checks.insert(
+                        "da".to_string(),
+                        CheckResult::new(false, format!("Failed to read the DA head: {e:?}")),
+                    );
+                    None
+                }
+                Err(_) => {
+                    checks.insert(
+                        "da".to_string(),
+                        CheckResult::new(
+                            false,
+                            format!("The DA service didn't answer within {DA_TIMEOUT:?}"),
+                        ),
+                    );
+                    None
+                }
+            };
+
+        let sync = match da_head_height {
+            Some(head) => {
+                let lag = head.saturating_sub(synced_da_height);
+                CheckResult::new(
+                    lag <= self.max_da_lag_blocks,
+                    format!(
+                        "The node is {lag} DA blocks behind the DA head {head}, at most {} are allowed",
+                        self.max_da_lag_blocks
+                    ),
+                )
+            }
+            None => CheckResult::new(false, "The DA head is unknown"),
+        };
+        checks.insert("sync".to_string(), sync);
+        checks.insert("storage".to_string(), self.check_storage());
+
+        ReadinessReport {
+            ready: checks.values().all(|check| check.ok),
+            synced_da_height,
+            da_head_height,
+            checks,
+        }
+    }
+
+    fn synced_da_height(&self) -> u64 {
+        let storage = self.storage.borrow().clone();
+        let mut state = ApiStateAccessor::<S>::new(&storage);
+        self.runtime
+            .chain_info
+            .latest_slot(&mut state)
+            .map_or(self.genesis_da_height, |slot| slot.da_height)
+    }
+
+    fn check_storage(&self) -> CheckResult {
+        // A probe file of its own, so that concurrent checks don't remove each other's
+        let probe = tempfile::Builder::new()
+            .prefix(STORAGE_PROBE_PREFIX)
+            .tempfile_in(&self.storage_path);
+        match probe.and_then(|probe| probe.close()) {
+            Ok(()) => CheckResult::new(true, "The storage is writable"),
+            Err(e) => CheckResult::new(
+                false,
+                format!(
+                    "The storage at {} is not writable: {e}",
+                    self.storage_path.display()
+                ),
+            ),
+        }
+    }
+}
+
+/// Adds the `/healthz` and `/readyz` routes to the REST server of `endpoints`.
+pub fn add_health_routes<S, Da>(
+    endpoints: RuntimeEndpoints,
+    readiness: Readiness<S, Da>,
+) -> RuntimeEndpoints
+where
+    S: Spec,
+    Da: DaService,
+{
+    let health = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz::<S, Da>))
+        .with_state(Arc::new(readiness));
+    RuntimeEndpoints {
+        jsonrpsee_module: endpoints.jsonrpsee_module,
+        axum_router: endpoints.axum_router.merge(health),
+    }
+}
+
+async fn healthz() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+async fn readyz<S: Spec, Da: DaService>(
+    State(readiness): State<Arc<Readiness<S, Da>>>,
+) -> (StatusCode, Json<ReadinessReport>) {
+    let report = readiness.check().await;
+    if !report.ready {
+        tracing::debug!(?report, "The node is not ready");
+    }
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
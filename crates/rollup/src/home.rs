@@ -0,0 +1,298 @@
+//! The home directory of a node, so that the `rollup` binary doesn't depend on the directory it runs from.
+//!
+//! ```text
+//! <home>/
+//!   config.toml   the rollup config, written by `rollup init`
+//!   genesis/      the genesis files, written by `rollup init`
+//!   keys/         the keys of the operator, never touched by `rollup reset`
+//!   data/         the storage of the node (`storage.path`), wiped by `rollup reset`
+//! ```
+//!
+//! The home is `--home`, else `$ROLLUP_HOME`, else `~/.sov-rollup-starter`.
+
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+
+use crate::code_commitment::{inner_code_commitment, write_chain_state_commitment};
+use crate::config::DaLayer;
+use crate::config_loader::read_rollup_config_sections;
+
+/// The environment variable of the home directory, used when there is no `--home` flag.
+pub const HOME_ENV_VAR: &str = "ROLLUP_HOME";
+
+/// The home directory, relative to the home of the user, used when neither `--home` nor [`HOME_ENV_VAR`] is set.
+pub const DEFAULT_HOME_DIR: &str = ".sov-rollup-starter";
+
+/// The storage path of the config templates, replaced by the `data` directory of the home.
+const TEMPLATE_STORAGE_PATH: &str = r#"path = "../../rollup-starter-data""#;
+
+const MOCK_CONFIG: &str = include_str!("../../../rollup_config.toml");
+const CELESTIA_CONFIG: &str = include_str!("../../../celestia_rollup_config.toml");
+
+macro_rules! genesis_files {
+    ($da:literal) => {
+        [
+            genesis_file!($da, "accounts.json"),
+            genesis_file!($da, "bank.json"),
+            genesis_file!($da, "ibc.json"),
+            genesis_file!($da, "ibc_transfer.json"),
+            genesis_file!($da, "sequencer_registry.json"),
+            genesis_file!($da, "prover_incentives.json"),
+            genesis_file!($da, "evm.json"),
+            genesis_file!($da, "circuit_breaker.json"),
+            genesis_file!($da, "chain_state.json"),
+        ]
+    };
+}
+
+macro_rules! genesis_file {
+    ($da:literal, $file:literal) => {
+        (
+            $file,
+            include_str!(concat!("../../../test-data/genesis/", $da, "/", $file)),
+        )
+    };
+}
+
+const MOCK_GENESIS: [(&str, &str); 9] = genesis_files!("mock");
+const CELESTIA_GENESIS: [(&str, &str); 9] = genesis_files!("celestia");
+
+/// The home directory of a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollupHome {
+    root: PathBuf,
+}
+
+impl RollupHome {
+    /// The home at `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The home given by the `--home` flag, else by [`HOME_ENV_VAR`], else `~/`[`DEFAULT_HOME_DIR`].
+    pub fn resolve(home_flag: Option<&str>) -> anyhow::Result<Self> {
+        if let Some(home) = home_flag {
+            return Ok(Self::new(home));
+        }
+        if let Some(home) = std::env::var_os(HOME_ENV_VAR) {
+            return Ok(Self::new(home));
+        }
+        let user_home = std::env::var_os("HOME").with_context(|| {
+            format!("Neither --home, `{HOME_ENV_VAR}` nor `HOME` is set, pass --home")
+        })?;
+        Ok(Self::new(Path::new(&user_home).join(DEFAULT_HOME_DIR)))
+    }
+
+    /// The home directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The rollup config.
+    pub fn config_path(&self) -> PathBuf {
+        self.root.join("config.toml")
+    }
+
+    /// The genesis directory.
+    pub fn genesis_dir(&self) -> PathBuf {
+        self.root.join("genesis")
+    }
+
+    /// The directory of the keys of the operator.
+    pub fn keys_dir(&self) -> PathBuf {
+        self.root.join("keys")
+    }
+
+    /// The storage of the node, as written into the config by [`Self::init`].
+    pub fn data_dir(&self) -> PathBuf {
+        self.root.join("data")
+    }
+
+    /// Reads the rollup config of the home, failing with a hint to run `rollup init` if there is none.
+    pub fn require_config(&self) -> anyhow::Result<PathBuf> {
+        let config_path = self.config_path();
+        if !config_path.is_file() {
+            bail!(
+                "There is no rollup config at {}: run `rollup init --home {}`, or pass --rollup-config-path",
+                config_path.display(),
+                self.root.display()
+            );
+        }
+        Ok(config_path)
+    }
+
+    /// Writes the default rollup config and genesis of `da_layer` into the home, with the storage in
+    /// [`Self::data_dir`]. Existing config and genesis files are only overwritten if `force` is set; the data and
+    /// the keys are never touched.
+    pub fn init(&self, da_layer: DaLayer, force: bool) -> anyhow::Result<()> {
+        let config_path = self.config_path();
+        if config_path.exists() && !force {
+            bail!(
+                "{} already exists, pass --force to overwrite the config and the genesis",
+                config_path.display()
+            );
+        }
+
+        let (config, genesis) = match da_layer {
+            DaLayer::Mock => (MOCK_CONFIG, MOCK_GENESIS),
+            DaLayer::Celestia => (CELESTIA_CONFIG, CELESTIA_GENESIS),
+        };
+        std::fs::create_dir_all(&self.root)
+            .with_context(|| format!("Failed to create {}", self.root.display()))?;
+        // The storage path is absolute, so that the node can run from any directory
+        let storage_path = absolute(&self.data_dir())?;
+        let storage_path = storage_path
+            .to_str()
+            .with_context(|| format!("{} is not valid UTF-8", storage_path.display()))?;
+        if !config.contains(TEMPLATE_STORAGE_PATH) {
+            bail!("The {da_layer} config template has no `{TEMPLATE_STORAGE_PATH}` to replace");
+        }
+        let config = config.replace(
+            TEMPLATE_STORAGE_PATH,
+            &format!("path = {}", toml::Value::from(storage_path)),
+        );
+        std::fs::write(&config_path, config)
+            .with_context(|| format!("Failed to write {}", config_path.display()))?;
+
+        let genesis_dir = self.genesis_dir();
+        std::fs::create_dir_all(&genesis_dir)
+            .with_context(|| format!("Failed to create {}", genesis_dir.display()))?;
+        for (file, contents) in genesis {
+            let path = genesis_dir.join(file);
+            std::fs::write(&path, contents)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        // The genesis templates were compiled in, point them at the guest of this binary
+        write_chain_state_commitment(
+            genesis_dir.join("chain_state.json"),
+            &inner_code_commitment(da_layer),
+        )?;
+
+        let keys_dir = self.keys_dir();
+        std::fs::create_dir_all(&keys_dir)
+            .with_context(|| format!("Failed to create {}", keys_dir.display()))
+    }
+}
+
+/// The storage path and the RPC address of the rollup config at `rollup_config_path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeLocation {
+    /// The `storage.path` of the config.
+    pub storage_path: PathBuf,
+    /// The `runner.rpc_config` address of the config.
+    pub rpc_address: String,
+    /// The `runner.axum_config` address of the config.
+    pub rest_address: String,
+}
+
+impl NodeLocation {
+    /// Reads the node location from the `storage` and `runner` sections of the rollup config at
+    /// `rollup_config_path`. The variables of the other sections don't have to be set.
+    pub fn read(rollup_config_path: &Path) -> anyhow::Result<Self> {
+        let config = read_rollup_config_sections(rollup_config_path, &["storage", "runner"])?;
+        let storage_path = config
+            .get("storage")
+            .and_then(|storage| storage.get("path"))
+            .and_then(toml::Value::as_str)
+            .with_context(|| {
+                format!(
+                    "The rollup config {} has no `storage.path`",
+                    rollup_config_path.display()
+                )
+            })?;
+        let server_address = |section: &str| -> anyhow::Result<String> {
+            let server = config
+                .get("runner")
+                .and_then(|runner| runner.get(section))
+                .with_context(|| {
+                    format!(
+                        "The rollup config {} has no `runner.{section}`",
+                        rollup_config_path.display()
+                    )
+                })?;
+            let host = server
+                .get("bind_host")
+                .and_then(toml::Value::as_str)
+                .unwrap_or("127.0.0.1");
+            // A server bound to all the interfaces is reached on the loopback one
+            let host = match host {
+                "0.0.0.0" | "::" => "127.0.0.1",
+                host => host,
+            };
+            let port = server
+                .get("bind_port")
+                .and_then(toml::Value::as_integer)
+                .with_context(|| format!("`runner.{section}` has no `bind_port`"))?;
+            Ok(format!("{host}:{port}"))
+        };
+
+        Ok(Self {
+            storage_path: storage_path.into(),
+            rpc_address: server_address("rpc_config")?,
+            rest_address: server_address("axum_config")?,
+        })
+    }
+}
+
+/// Removes the storage of the node at `location`, keeping the rollup config, the genesis and the keys of `home`.
+///
+/// Refuses if a node answers on the RPC address of the config, or if the storage holds the home files. Without
+/// `confirmed`, only returns what would be removed.
+pub fn reset_data(
+    home: &RollupHome,
+    location: &NodeLocation,
+    confirmed: bool,
+) -> anyhow::Result<Option<PathBuf>> {
+    let storage_path = &location.storage_path;
+    if !storage_path.exists() {
+        return Ok(None);
+    }
+
+    let storage = std::fs::canonicalize(storage_path)
+        .with_context(|| format!("Failed to resolve {}", storage_path.display()))?;
+    for kept in [home.config_path(), home.genesis_dir(), home.keys_dir()] {
+        let kept = std::fs::canonicalize(&kept).or_else(|_| absolute(&kept))?;
+        if kept.starts_with(&storage) {
+            bail!(
+                "The storage path {} holds {}, which `rollup reset` must keep; fix `storage.path`",
+                storage.display(),
+                kept.display()
+            );
+        }
+    }
+
+    if is_listening(&location.rpc_address) {
+        bail!(
+            "A node is running on {}, stop it before resetting its data",
+            location.rpc_address
+        );
+    }
+    if !confirmed {
+        return Ok(Some(storage));
+    }
+    std::fs::remove_dir_all(&storage)
+        .with_context(|| format!("Failed to remove {}", storage.display()))?;
+    Ok(Some(storage))
+}
+
+fn absolute(path: &Path) -> anyhow::Result<PathBuf> {
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    let current_dir = std::env::current_dir().context("Failed to read the current directory")?;
+    Ok(current_dir.join(path))
+}
+
+/// Whether something accepts connections at `address`.
+fn is_listening(address: &str) -> bool {
+    let addresses: Vec<SocketAddr> = match address.to_socket_addrs() {
+        Ok(addresses) => addresses.collect(),
+        Err(_) => return false,
+    };
+    addresses
+        .iter()
+        .any(|address| TcpStream::connect_timeout(address, Duration::from_millis(500)).is_ok())
+}
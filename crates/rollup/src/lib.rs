@@ -1,5 +1,23 @@
+pub mod code_commitment;
+pub mod config;
+pub mod config_loader;
+pub mod genesis_export;
+pub mod genesis_generator;
+pub mod genesis_layout;
+pub mod genesis_root;
+pub mod health;
+pub mod home;
+pub mod metrics;
+pub mod shutdown;
+pub mod telemetry;
+
+#[cfg(feature = "mock_da")]
+pub mod mock_da;
 #[cfg(feature = "mock_da")]
 pub mod mock_rollup;
 
 #[cfg(feature = "celestia_da")]
 pub mod celestia_rollup;
+
+#[cfg(feature = "test")]
+pub mod test_harness;
@@ -0,0 +1,494 @@
+//! Prometheus metrics of the rollup node, served by the metrics exporter of the `rollup` binary.
+//!
+//! The slot metrics and the sequencer outcomes (the `SequencerRewarded` and `SequencerSlashed` events of the
+//! `sequencer_outcomes` module) are read from the ledger after each slot is stored, rather than from the runtime
+//! hooks, so that the simulation of transactions by the sequencer and the native execution of the prover don't
+//! count them again.
+//! The RPC metrics count the requests to the JSON-RPC methods and REST routes of [`RuntimeEndpoints`].
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use anyhow::Context;
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use jsonrpsee::core::server::{MethodCallback, Methods, MethodsError};
+use jsonrpsee::core::traits::ToRpcParams;
+use jsonrpsee::types::error::INTERNAL_ERROR_CODE;
+use jsonrpsee::types::{ErrorObject, ErrorObjectOwned, Params};
+use jsonrpsee::RpcModule;
+use prometheus::{
+    register_histogram, register_int_counter, register_int_counter_vec, register_int_gauge,
+    Histogram, IntCounter, IntCounterVec, IntGauge,
+};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use sov_db::ledger_db::LedgerDb;
+use sov_modules_api::{ApiStateAccessor, Spec};
+use sov_modules_stf_blueprint::RuntimeEndpoints;
+use sov_rollup_interface::da::BlockHeaderTrait;
+use sov_rollup_interface::rpc::{LedgerRpcProvider, QueryMode};
+use sov_rollup_interface::services::da::DaService;
+use stf_starter::Runtime;
+use tokio::sync::{broadcast, watch};
+
+/// The default address of the metrics exporter.
+pub const DEFAULT_METRICS_BIND_ADDRESS: &str = "127.0.0.1:9845";
+
+/// The number of slots waiting for a proof whose processing time is kept to measure the proof latency.
+const MAX_UNPROVEN_SLOTS: usize = 10_000;
+
+/// Parses the bind address of the metrics exporter: `HOST:PORT`, or a port on `127.0.0.1`.
+pub fn parse_bind_address(value: &str) -> anyhow::Result<SocketAddr> {
+    match value.parse::<u16>() {
+        Ok(port) => Ok(SocketAddr::from(([127, 0, 0, 1], port))),
+        Err(_) => value
+            .parse()
+            .with_context(|| format!("Invalid metrics bind address {value:?}")),
+    }
+}
+
+struct Metrics {
+    slots_processed: IntCounter,
+    rollup_height: IntGauge,
+    synced_da_height: IntGauge,
+    da_head_height: IntGauge,
+    batches_per_slot: Histogram,
+    txs_per_slot: Histogram,
+    gas_used_per_slot: Histogram,
+    tx_failures: IntCounterVec,
+    prover_queue_depth: IntGauge,
+    proof_latency: Histogram,
+    rpc_requests: IntCounterVec,
+    rest_requests: IntCounterVec,
+    sequencer_rewarded: IntCounterVec,
+    sequencer_slashed: IntCounterVec,
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics {
+        slots_processed: register_int_counter!(
+            "rollup_slots_processed_total",
+            "The number of slots processed by the node"
+        )
+        .expect("Failed to register the slot counter"),
+        rollup_height: register_int_gauge!(
+            "rollup_height",
+            "The number of the latest slot processed by the node"
+        )
+        .expect("Failed to register the rollup height gauge"),
+        synced_da_height: register_int_gauge!(
+            "rollup_synced_da_height",
+            "The DA height of the latest slot processed by the node"
+        )
+        .expect("Failed to register the synced DA height gauge"),
+        da_head_height: register_int_gauge!(
+            "rollup_da_head_height",
+            "The height of the head block of the DA layer"
+        )
+        .expect("Failed to register the DA head height gauge"),
+        batches_per_slot: register_histogram!(
+            "rollup_batches_per_slot",
+            "The number of batches applied in each slot",
+            vec![0.0, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0]
+        )
+        .expect("Failed to register the batches per slot histogram"),
+        txs_per_slot: register_histogram!(
+            "rollup_txs_per_slot",
+            "The number of transactions applied in each slot",
+            prometheus::exponential_buckets(1.0, 4.0, 8).expect("The buckets are valid")
+        )
+        .expect("Failed to register the transactions per slot histogram"),
+        gas_used_per_slot: register_histogram!(
+            "rollup_gas_used_per_slot",
+            "The gas used by the transactions of each slot",
+            prometheus::exponential_buckets(1_000.0, 10.0, 8).expect("The buckets are valid")
+        )
+        .expect("Failed to register the gas per slot histogram"),
+        tx_failures: register_int_counter_vec!(
+            "rollup_tx_failures_total",
+            "The number of transactions that were reverted or skipped, by reason",
+            &["result", "reason"]
+        )
+        .expect("Failed to register the transaction failure counter"),
+        prover_queue_depth: register_int_gauge!(
+            "rollup_prover_queue_depth",
+            "The number of processed slots not yet covered by an aggregated proof"
+        )
+        .expect("Failed to register the prover queue gauge"),
+        proof_latency: register_histogram!(
+            "rollup_proof_latency_seconds",
+            "The time from the processing of a slot to the aggregated proof covering it",
+            prometheus::exponential_buckets(1.0, 2.0, 14).expect("The buckets are valid")
+        )
+        .expect("Failed to register the proof latency histogram"),
+        rpc_requests: register_int_counter_vec!(
+            "rollup_rpc_requests_total",
+            "The number of JSON-RPC requests, by method and outcome",
+            &["method", "outcome"]
+        )
+        .expect("Failed to register the JSON-RPC request counter"),
+        rest_requests: register_int_counter_vec!(
+            "rollup_rest_requests_total",
+            "The number of REST requests, by HTTP method, route and status",
+            &["method", "route", "status"]
+        )
+        .expect("Failed to register the REST request counter"),
+        sequencer_rewarded: register_int_counter_vec!(
+            "sequencer_rewarded_amount_total",
+            "The amount rewarded to each sequencer",
+            &["sequencer"]
+        )
+        .expect("Failed to register the sequencer reward counter"),
+        sequencer_slashed: register_int_counter_vec!(
+            "sequencer_slashed_amount_total",
+            "The stake slashed from each sequencer, by reason",
+            &["sequencer", "reason"]
+        )
+        .expect("Failed to register the sequencer slashing counter"),
+    })
+}
+
+/// Starts collecting the slot, DA and proof metrics of the node in the background.
+pub fn spawn_node_metrics<S, Da>(
+    ledger_db: LedgerDb,
+    storage: watch::Receiver<S::Storage>,
+    da_service: Da,
+) where
+    S: Spec,
+    Da: DaService,
+{
+    tokio::spawn(async move {
+        let mut collector = Collector::<S, Da> {
+            runtime: Runtime::default(),
+            ledger_db,
+            storage,
+            da_service,
+            last_slot: None,
+            next_event: None,
+            last_proven_slot: 0,
+            unproven_slots: VecDeque::new(),
+        };
+        collector.run().await;
+    });
+}
+
+struct Collector<S: Spec, Da: DaService> {
+    runtime: Runtime<S, Da::Spec>,
+    ledger_db: LedgerDb,
+    storage: watch::Receiver<S::Storage>,
+    da_service: Da,
+    last_slot: Option<u64>,
+    /// The number of the next ledger event to collect. The events stored before the node started are skipped.
+    next_event: Option<u64>,
+    last_proven_slot: u64,
+    unproven_slots: VecDeque<(u64, Instant)>,
+}
+
+impl<S: Spec, Da: DaService> Collector<S, Da> {
+    async fn run(&mut self) {
+        let mut slots = self.ledger_db.subscribe_slots();
+        // The outcomes of the events stored before the node started were counted by the previous run
+        match self.event_count() {
+            Ok(count) => self.next_event = Some(count),
+            Err(e) => tracing::warn!(error = ?e, "Failed to count the ledger events"),
+        }
+        loop {
+            let slot_number = match slots.recv().await {
+                Ok(slot_number) => slot_number,
+                // The missed slots are collected with the next one
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+
+            let first = self.last_slot.map_or(slot_number, |last| last + 1);
+            for number in first..=slot_number {
+                if let Err(e) = self.collect_slot(number) {
+                    tracing::warn!(slot_number = number, error = ?e, "Failed to collect the slot metrics");
+                }
+            }
+            self.last_slot = Some(slot_number);
+
+            if let Err(e) = self.collect_events() {
+                tracing::warn!(error = ?e, "Failed to collect the sequencer outcomes");
+            }
+            if let Err(e) = self.collect_proofs(slot_number) {
+                tracing::warn!(error = ?e, "Failed to collect the proof metrics");
+            }
+            match self.da_service.get_head_block_header().await {
+                Ok(header) => metrics().da_head_height.set(header.height() as i64),
+                Err(e) => tracing::warn!(error = ?e, "Failed to read the DA head"),
+            }
+        }
+    }
+
+    fn collect_slot(&mut self, slot_number: u64) -> anyhow::Result<()> {
+        let metrics = metrics();
+        let slot = self
+            .ledger_db
+            .get_slot_by_number::<Value, Value, Value>(slot_number, QueryMode::Full)?
+            .with_context(|| format!("Slot {slot_number} is not in the ledger"))?;
+        let slot = serde_json::to_value(slot)?;
+
+        let batches = array(&slot["batches"]);
+        let mut tx_count = 0;
+        let mut gas_used = 0;
+        for batch in batches {
+            for tx in array(&batch["txs"]) {
+                tx_count += 1;
+                let receipt = &tx["receipt"];
+                gas_used += total_gas(&receipt["data"]["gas_used"]);
+                match receipt["result"].as_str() {
+                    Some("successful") => {}
+                    result => metrics
+                        .tx_failures
+                        .with_label_values(&[
+                            match result {
+                                Some(result @ ("reverted" | "skipped")) => result,
+                                _ => "unknown",
+                            },
+                            failure_reason_name(receipt["data"]["reason"].as_str()),
+                        ])
+                        .inc(),
+                }
+            }
+        }
+
+        metrics.slots_processed.inc();
+        metrics.rollup_height.set(slot_number as i64);
+        metrics.batches_per_slot.observe(batches.len() as f64);
+        metrics.txs_per_slot.observe(tx_count as f64);
+        metrics.gas_used_per_slot.observe(gas_used as f64);
+
+        let storage = self.storage.borrow().clone();
+        let mut state = ApiStateAccessor::<S>::new(&storage);
+        if let Some(slot) = self.runtime.chain_info.latest_slot(&mut state) {
+            metrics.synced_da_height.set(slot.da_height as i64);
+        }
+
+        if self.unproven_slots.len() == MAX_UNPROVEN_SLOTS {
+            self.unproven_slots.pop_front();
+        }
+        self.unproven_slots.push_back((slot_number, Instant::now()));
+        Ok(())
+    }
+
+    /// Counts the sequencer outcomes of the events stored since the last call.
+    fn collect_events(&mut self) -> anyhow::Result<()> {
+        let metrics = metrics();
+        let mut number = match self.next_event {
+            Some(number) => number,
+            None => self.event_count()?,
+        };
+        while let Some(event) = self.ledger_db.get_event_by_number::<Value>(number)? {
+            if let Some(reward) = find_variant(&event, "SequencerRewarded") {
+                metrics
+                    .sequencer_rewarded
+                    .with_label_values(&[reward["sequencer"].as_str().unwrap_or("unknown")])
+                    .inc_by(reward["amount"].as_u64().unwrap_or_default());
+            }
+            if let Some(slashing) = find_variant(&event, "SequencerSlashed") {
+                metrics
+                    .sequencer_slashed
+                    .with_label_values(&[
+                        slashing["sequencer"].as_str().unwrap_or("unknown"),
+                        slashing["reason"].as_str().unwrap_or("unknown"),
+                    ])
+                    .inc_by(slashing["amount"].as_u64().unwrap_or_default());
+            }
+            number += 1;
+        }
+        self.next_event = Some(number);
+        Ok(())
+    }
+
+    /// Returns the number of events in the ledger, found by a binary search over the event numbers.
+    fn event_count(&self) -> anyhow::Result<u64> {
+        let exists = |number: u64| -> anyhow::Result<bool> {
+            Ok(self
+                .ledger_db
+                .get_event_by_number::<Value>(number)?
+                .is_some())
+        };
+        if !exists(0)? {
+            return Ok(0);
+        }
+        // The event `low` exists and the event `high` doesn't
+        let (mut low, mut high) = (0u64, 1u64);
+        while exists(high)? {
+            low = high;
+            high = high.saturating_mul(2);
+        }
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if exists(middle)? {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        Ok(high)
+    }
+
+    fn collect_proofs(&mut self, latest_slot: u64) -> anyhow::Result<()> {
+        let metrics = metrics();
+        if let Some(proof) = self.ledger_db.get_latest_aggregated_proof()? {
+            let proof = serde_json::to_value(proof)?;
+            if let Some(final_slot) = proof["public_data"]["final_slot_number"].as_u64() {
+                self.last_proven_slot = self.last_proven_slot.max(final_slot);
+            }
+        }
+
+        while let Some((slot_number, processed_at)) = self.unproven_slots.front() {
+            if *slot_number > self.last_proven_slot {
+                break;
+            }
+            metrics
+                .proof_latency
+                .observe(processed_at.elapsed().as_secs_f64());
+            self.unproven_slots.pop_front();
+        }
+        metrics
+            .prover_queue_depth
+            .set(latest_slot.saturating_sub(self.last_proven_slot) as i64);
+        Ok(())
+    }
+}
+
+/// Finds the enum variant `name`, e.g. `SequencerRewarded`, in a ledger event.
+fn find_variant<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(fields) => fields
+            .get(name)
+            .or_else(|| fields.values().find_map(|field| find_variant(field, name))),
+        Value::Array(items) => items.iter().find_map(|item| find_variant(item, name)),
+        _ => None,
+    }
+}
+
+fn array(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// The gas used by a transaction, summed over the gas dimensions.
+fn total_gas(gas_used: &Value) -> u64 {
+    match gas_used {
+        Value::Number(gas) => gas.as_u64().unwrap_or_default(),
+        Value::Array(gas) => gas.iter().filter_map(Value::as_u64).sum(),
+        _ => 0,
+    }
+}
+
+/// The failure reasons labelling `rollup_tx_failures_total`, as the name of the reason and the phrases of the
+/// error messages reporting it. The first match wins.
+const FAILURE_REASONS: [(&str, &[&str]); 6] = [
+    ("out_of_gas", &["out of gas", "not enough gas", "gas limit"]),
+    (
+        "insufficient_funds",
+        &["insufficient", "not enough funds", "balance"],
+    ),
+    ("invalid_nonce", &["nonce"]),
+    ("invalid_signature", &["signature"]),
+    (
+        "unauthorized",
+        &[
+            "unauthorized",
+            "not authorized",
+            "not allowed",
+            "permission",
+        ],
+    ),
+    ("invalid_encoding", &["decode", "deserializ", "encoding"]),
+];
+
+/// Returns the name of the failure reported by the error message `reason`, from a fixed set so that it can label the
+/// metrics: a name of `FAILURE_REASONS`, `other` for the messages matching none of them, or `unknown` without a
+/// message.
+pub fn failure_reason_name(reason: Option<&str>) -> &'static str {
+    let Some(reason) = reason else {
+        return "unknown";
+    };
+    let reason = reason.to_lowercase();
+    FAILURE_REASONS
+        .iter()
+        .find(|(_, phrases)| phrases.iter().any(|phrase| reason.contains(phrase)))
+        .map_or("other", |(name, _)| name)
+}
+
+/// Counts the requests to the JSON-RPC methods and REST routes of `endpoints`.
+pub fn count_requests(endpoints: RuntimeEndpoints) -> anyhow::Result<RuntimeEndpoints> {
+    Ok(RuntimeEndpoints {
+        jsonrpsee_module: count_rpc_requests(endpoints.jsonrpsee_module)?,
+        axum_router: endpoints
+            .axum_router
+            .layer(axum::middleware::from_fn(count_rest_request)),
+    })
+}
+
+/// Forwards every JSON-RPC method of `module` through a method counting its calls. Subscriptions are kept as is.
+fn count_rpc_requests(module: RpcModule<()>) -> anyhow::Result<RpcModule<()>> {
+    let methods: Methods = module.into();
+    let mut subscriptions = methods.clone();
+    let mut counted = RpcModule::new(methods.clone());
+
+    for name in methods.method_names() {
+        let is_subscription = matches!(
+            methods.method(name),
+            Some(MethodCallback::Subscription(_) | MethodCallback::Unsubscription(_))
+        );
+        if is_subscription {
+            continue;
+        }
+        subscriptions.remove_method(name);
+        counted.register_async_method(
+            name,
+            move |params: Params<'static>, methods: Arc<Methods>| async move {
+                let result: Result<Value, ErrorObjectOwned> = methods
+                    .call::<_, Value>(name, RawParams(params.as_str().map(str::to_string)))
+                    .await
+                    .map_err(|error| match error {
+                        MethodsError::JsonRpc(error) => error,
+                        error => {
+                            ErrorObject::owned(INTERNAL_ERROR_CODE, error.to_string(), None::<()>)
+                        }
+                    });
+                let outcome = if result.is_ok() { "success" } else { "error" };
+                metrics()
+                    .rpc_requests
+                    .with_label_values(&[name, outcome])
+                    .inc();
+                result
+            },
+        )?;
+    }
+
+    counted.merge(subscriptions)?;
+    Ok(counted)
+}
+
+struct RawParams(Option<String>);
+
+impl ToRpcParams for RawParams {
+    fn to_rpc_params(self) -> Result<Option<Box<RawValue>>, serde_json::Error> {
+        self.0.map(RawValue::from_string).transpose()
+    }
+}
+
+async fn count_rest_request(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str)
+        .to_string();
+    let response = next.run(request).await;
+    metrics()
+        .rest_requests
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .inc();
+    response
+}
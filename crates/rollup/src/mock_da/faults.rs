@@ -0,0 +1,257 @@
+//! Fault injection for the mock DA.
+//!
+//! Faults are injected either by a seeded [`FaultSchedule`] from the config, or on command through a
+//! [`FaultInjector`]. Blob faults are applied to the blobs the rollup reads from a block and are pinned to
+//! the block height, so every component reading the same block sees the same misbehaviour.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use anyhow::bail;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// A seeded schedule of faults, set in the `[da.faults]` section of the rollup config.
+///
+/// The faults of a block only depend on the seed and the block height, so a run can be reproduced.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultSchedule {
+    /// The seed of the schedule.
+    pub seed: u64,
+    /// The probability that fetching a block is delayed.
+    #[serde(default)]
+    pub delay_probability: f64,
+    /// The maximum delay of a block, in milliseconds.
+    #[serde(default)]
+    pub max_delay_ms: u64,
+    /// The probability that a blob is dropped from its block.
+    #[serde(default)]
+    pub drop_probability: f64,
+    /// The probability that the blobs of a block are shuffled.
+    #[serde(default)]
+    pub reorder_probability: f64,
+    /// The probability that a blob is sent twice in its block.
+    #[serde(default)]
+    pub duplicate_probability: f64,
+    /// The probability that submitting a blob reorgs the unfinalized blocks.
+    #[serde(default)]
+    pub reorg_probability: f64,
+}
+
+impl FaultSchedule {
+    /// Checks that all the probabilities are in `[0, 1]`.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, probability) in [
+            ("delay_probability", self.delay_probability),
+            ("drop_probability", self.drop_probability),
+            ("reorder_probability", self.reorder_probability),
+            ("duplicate_probability", self.duplicate_probability),
+            ("reorg_probability", self.reorg_probability),
+        ] {
+            if !(0.0..=1.0).contains(&probability) {
+                bail!("Mock DA fault `{name}` must be between 0 and 1, got {probability}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the schedule drops, reorders or duplicates blobs.
+    pub fn has_blob_faults(&self) -> bool {
+        self.drop_probability > 0.0
+            || self.reorder_probability > 0.0
+            || self.duplicate_probability > 0.0
+    }
+
+    /// Returns the random generator of `fault` at `height`, so that the faults are drawn independently.
+    fn rng(&self, height: u64, fault: Draw) -> StdRng {
+        let stream = height.wrapping_mul(Draw::COUNT).wrapping_add(fault as u64);
+        StdRng::seed_from_u64(self.seed ^ stream.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Draw {
+    Delay,
+    Blobs,
+    Reorg,
+}
+
+impl Draw {
+    const COUNT: u64 = 3;
+}
+
+/// A fault applied to the blobs of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobFault {
+    /// The first blob of the block is dropped.
+    Drop,
+    /// The blobs of the block are read in reverse order.
+    Reorder,
+    /// The first blob of the block is read twice.
+    Duplicate,
+}
+
+#[derive(Default)]
+struct State {
+    schedule: Option<FaultSchedule>,
+    finalization_blocks: u32,
+    pending_delays: VecDeque<Duration>,
+    pending_blob_faults: VecDeque<BlobFault>,
+    pending_reorg: Option<u32>,
+    /// The delays and the blob faults of the blocks read, until they are finalized.
+    delays: HashMap<u64, Option<Duration>>,
+    blob_faults: HashMap<u64, Vec<BlobFault>>,
+}
+
+impl State {
+    /// Forgets the faults of the blocks below the last finalized one, given that the block at `height` exists.
+    /// Finalized blocks cannot be reorged, so they are not read again.
+    fn prune_finalized(&mut self, height: u64) {
+        let last_finalized = height.saturating_sub(self.finalization_blocks.into());
+        self.delays.retain(|height, _| *height >= last_finalized);
+        self.blob_faults
+            .retain(|height, _| *height >= last_finalized);
+    }
+}
+
+/// A handle to command the faults of a [`super::StarterMockDaService`], e.g. from a test.
+///
+/// Commands apply to the next blocks the rollup reads, in the order they were given.
+#[derive(Clone, Default)]
+pub struct FaultInjector {
+    state: Arc<Mutex<State>>,
+}
+
+impl FaultInjector {
+    /// Delays the next `count` blocks by `delay` each.
+    pub fn delay_next_blocks(&self, count: usize, delay: Duration) {
+        self.state()
+            .pending_delays
+            .extend(std::iter::repeat(delay).take(count));
+    }
+
+    /// Drops the first blob of the next block that has blobs.
+    pub fn drop_next_blob(&self) {
+        self.push_blob_fault(BlobFault::Drop);
+    }
+
+    /// Reverses the blobs of the next block that has blobs.
+    pub fn reorder_next_blobs(&self) {
+        self.push_blob_fault(BlobFault::Reorder);
+    }
+
+    /// Sends the first blob of the next block that has blobs twice.
+    pub fn duplicate_next_blob(&self) {
+        self.push_blob_fault(BlobFault::Duplicate);
+    }
+
+    /// Replaces the last `depth` blocks with empty ones when the next blob is submitted.
+    /// Only unfinalized blocks can be reorged, so `depth` must be at most `finalization_blocks`.
+    pub fn reorg(&self, depth: u32) -> anyhow::Result<()> {
+        let mut state = self.state();
+        if depth == 0 || depth > state.finalization_blocks {
+            bail!(
+                "Cannot reorg {depth} blocks: the mock DA finalizes blocks after {} blocks",
+                state.finalization_blocks
+            );
+        }
+        state.pending_reorg = Some(depth);
+        Ok(())
+    }
+
+    pub(super) fn configure(&self, schedule: Option<FaultSchedule>, finalization_blocks: u32) {
+        let mut state = self.state();
+        state.schedule = schedule;
+        state.finalization_blocks = finalization_blocks;
+    }
+
+    /// Returns the delay of the block at `height`, the first time it is read.
+    pub fn delay(&self, height: u64) -> Option<Duration> {
+        let mut state = self.state();
+        state.prune_finalized(height);
+        if let Some(delay) = state.delays.get_mut(&height) {
+            return delay.take();
+        }
+
+        let delay = state.pending_delays.pop_front().or_else(|| {
+            let schedule = state.schedule.as_ref()?;
+            let mut rng = schedule.rng(height, Draw::Delay);
+            rng.gen_bool(schedule.delay_probability)
+                .then(|| Duration::from_millis(rng.gen_range(0..=schedule.max_delay_ms)))
+        });
+        // The delay is only applied once: a retry reads the block without delay.
+        state.delays.insert(height, None);
+        delay
+    }
+
+    /// Applies the faults of the block at `height` to its `blobs`.
+    pub fn apply_blob_faults<B: Clone>(&self, height: u64, blobs: &mut Vec<B>) {
+        if blobs.is_empty() {
+            return;
+        }
+
+        let mut state = self.state();
+        state.prune_finalized(height);
+        let State {
+            pending_blob_faults,
+            blob_faults,
+            ..
+        } = &mut *state;
+        let faults = blob_faults
+            .entry(height)
+            .or_insert_with(|| pending_blob_faults.pop_front().into_iter().collect())
+            .clone();
+        for fault in faults {
+            match fault {
+                BlobFault::Drop => {
+                    blobs.remove(0);
+                }
+                BlobFault::Reorder => blobs.reverse(),
+                BlobFault::Duplicate => blobs.insert(0, blobs[0].clone()),
+            }
+        }
+
+        if let Some(schedule) = &state.schedule {
+            let mut rng = schedule.rng(height, Draw::Blobs);
+            blobs.retain(|_| !rng.gen_bool(schedule.drop_probability));
+            let mut duplicated = Vec::with_capacity(blobs.len());
+            for blob in blobs.drain(..) {
+                if rng.gen_bool(schedule.duplicate_probability) {
+                    duplicated.push(blob.clone());
+                }
+                duplicated.push(blob);
+            }
+            *blobs = duplicated;
+            if rng.gen_bool(schedule.reorder_probability) {
+                blobs.shuffle(&mut rng);
+            }
+        }
+    }
+
+    /// Returns the depth of the reorg to perform before submitting a blob at `head_height`.
+    pub fn reorg_depth(&self, head_height: u64) -> Option<u32> {
+        let mut state = self.state();
+        if let Some(depth) = state.pending_reorg.take() {
+            return Some(depth);
+        }
+
+        let schedule = state.schedule.as_ref()?;
+        if state.finalization_blocks == 0 {
+            return None;
+        }
+        let mut rng = schedule.rng(head_height, Draw::Reorg);
+        rng.gen_bool(schedule.reorg_probability)
+            .then(|| rng.gen_range(1..=state.finalization_blocks))
+    }
+
+    fn push_blob_fault(&self, fault: BlobFault) {
+        self.state().pending_blob_faults.push_back(fault);
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("Mock DA fault state poisoned")
+    }
+}
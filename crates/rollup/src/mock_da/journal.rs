@@ -0,0 +1,96 @@
+//! An append-only journal of the blobs submitted to the mock DA.
+//!
+//! Each record is a borsh-encoded [`JournalEntry`] prefixed by its length as a little-endian `u32`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+const JOURNAL_FILE_NAME: &str = "blobs.journal";
+
+/// A blob submitted to the mock DA.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum JournalEntry {
+    /// A batch, submitted with `send_transaction`.
+    Batch(Vec<u8>),
+    /// An aggregated proof, submitted with `send_aggregated_zk_proof`.
+    Proof(Vec<u8>),
+}
+
+/// The journal file of a persistent mock DA.
+pub struct BlobJournal {
+    path: PathBuf,
+    file: File,
+}
+
+impl BlobJournal {
+    /// Opens the journal in `dir`, creating it if needed, and returns the entries it already holds.
+    ///
+    /// A record that was only partially written (e.g. the node was killed mid-write) is dropped.
+    pub fn open(dir: &Path) -> anyhow::Result<(Self, Vec<JournalEntry>)> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create mock DA directory {}", dir.display()))?;
+        let path = dir.join(JOURNAL_FILE_NAME);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open mock DA journal {}", path.display()))?;
+
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+
+        let mut entries = Vec::new();
+        let mut offset = 0;
+        while let Some(entry) = Self::read_record(&contents[offset..]) {
+            let (entry, len) = entry?;
+            entries.push(entry);
+            offset += len;
+        }
+
+        if offset < contents.len() {
+            tracing::warn!(
+                path = %path.display(),
+                dropped_bytes = contents.len() - offset,
+                "Dropping a partially written record from the mock DA journal"
+            );
+            file.set_len(offset as u64)?;
+        }
+
+        Ok((Self { path, file }, entries))
+    }
+
+    /// Appends `entry` to the journal and syncs it to disk.
+    pub fn append(&mut self, entry: &JournalEntry) -> anyhow::Result<()> {
+        let data = entry.try_to_vec()?;
+        let len = u32::try_from(data.len()).context("Mock DA blob is too large")?;
+
+        let mut record = Vec::with_capacity(4 + data.len());
+        record.extend_from_slice(&len.to_le_bytes());
+        record.extend_from_slice(&data);
+
+        self.file
+            .write_all(&record)
+            .and_then(|_| self.file.sync_data())
+            .with_context(|| format!("Failed to write mock DA journal {}", self.path.display()))
+    }
+
+    /// Reads the record at the start of `bytes`, returning it with its encoded length.
+    /// Returns `None` if `bytes` does not hold a complete record.
+    fn read_record(bytes: &[u8]) -> Option<anyhow::Result<(JournalEntry, usize)>> {
+        let len_bytes: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        let data = bytes.get(4..4 + len)?;
+
+        Some(
+            JournalEntry::try_from_slice(data)
+                .map(|entry| (entry, 4 + len))
+                .context("Corrupted mock DA journal record"),
+        )
+    }
+}
@@ -0,0 +1,285 @@
+//! The mock DA used by [`crate::mock_rollup::MockRollup`].
+//!
+//! [`StarterMockDaService`] wraps the SDK [`MockDaService`] and, when [`StarterMockDaConfig::persistent`]
+//! is set, journals every submitted blob under the storage path. On start, the journal is replayed into
+//! a fresh [`MockDaService`], so the DA blocks match the ledger of a restarted node.
+//!
+//! For resilience testing, the service can also misbehave: see [`faults`].
+
+pub mod faults;
+mod journal;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::bail;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sov_mock_da::{MockDaConfig, MockDaService, PlannedFork};
+use sov_rollup_interface::da::{BlockHeaderTrait, DaSpec, RelevantBlobs, RelevantProofs};
+use sov_rollup_interface::services::da::{DaService, SlotData};
+use sov_stf_runner::RollupProverConfig;
+use tokio::sync::Mutex;
+
+use self::faults::{FaultInjector, FaultSchedule};
+use self::journal::{BlobJournal, JournalEntry};
+
+/// The directory of the mock DA journal, relative to the rollup storage path.
+pub const MOCK_DA_DIR: &str = "mock_da";
+
+/// Configuration of the [`StarterMockDaService`]: the SDK [`MockDaConfig`] with the starter options.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StarterMockDaConfig {
+    /// The SDK mock DA configuration.
+    #[serde(flatten)]
+    pub mock: MockDaConfig,
+    /// Keep the blocks and blobs in `<storage.path>/mock_da`, so that they survive restarts.
+    /// When unset, the DA is in memory and starts empty on every run.
+    #[serde(default)]
+    pub persistent: bool,
+    /// Inject faults by a seeded schedule. Cannot be combined with `persistent`.
+    #[serde(default)]
+    pub faults: Option<FaultSchedule>,
+}
+
+impl StarterMockDaConfig {
+    /// Checks the fault schedule, if any, and that it is not combined with `persistent`.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let Some(faults) = &self.faults else {
+            return Ok(());
+        };
+        if self.persistent {
+            bail!("The mock DA cannot both be persistent and inject faults: set `persistent = false` to use `[da.faults]`");
+        }
+        faults.validate()
+    }
+
+    /// Checks that blobs are only dropped, reordered or duplicated when proving is skipped: the extraction proofs
+    /// cover the unmodified blocks, so they would not match the faulty blobs. `commanded_faults` tells whether
+    /// faults can also be commanded through a [`FaultInjector`].
+    pub fn check_prover_config(
+        &self,
+        prover_config: Option<&RollupProverConfig>,
+        commanded_faults: bool,
+    ) -> anyhow::Result<()> {
+        let blob_faults = commanded_faults
+            || self
+                .faults
+                .as_ref()
+                .is_some_and(FaultSchedule::has_blob_faults);
+        let proving = !matches!(prover_config, None | Some(RollupProverConfig::Skip));
+        if blob_faults && proving {
+            bail!("The mock DA blob faults (drop, reorder, duplicate) can only be injected with the `skip` prover mode");
+        }
+        Ok(())
+    }
+}
+
+impl From<MockDaConfig> for StarterMockDaConfig {
+    fn from(mock: MockDaConfig) -> Self {
+        Self {
+            mock,
+            persistent: false,
+            faults: None,
+        }
+    }
+}
+
+/// A [`MockDaService`] that can persist its blobs across restarts, or inject faults.
+///
+/// Clones share the same DA.
+#[derive(Clone)]
+pub struct StarterMockDaService {
+    inner: Arc<MockDaService>,
+    journal: Option<Arc<Mutex<BlobJournal>>>,
+    faults: Option<FaultInjector>,
+}
+
+type Inner = MockDaService;
+type BlockHeader = <<Inner as DaService>::Spec as DaSpec>::BlockHeader;
+type BlobTransaction = <<Inner as DaService>::Spec as DaSpec>::BlobTransaction;
+type InclusionMultiProof = <<Inner as DaService>::Spec as DaSpec>::InclusionMultiProof;
+type CompletenessProof = <<Inner as DaService>::Spec as DaSpec>::CompletenessProof;
+
+impl StarterMockDaService {
+    /// Creates the service. If the config is persistent, opens the journal in `storage_path`
+    /// and replays the blobs it holds, re-creating the blocks in the same order.
+    ///
+    /// Faults are injected if the config has a fault schedule or a `fault_injector` is given.
+    /// Blocks are then only finalized after `finalization_blocks`, so that they can be reorged.
+    pub async fn new(
+        config: &StarterMockDaConfig,
+        storage_path: &Path,
+        fault_injector: Option<FaultInjector>,
+    ) -> anyhow::Result<Self> {
+        config.validate()?;
+        if config.faults.is_some() || fault_injector.is_some() {
+            if config.persistent {
+                bail!("The mock DA cannot both be persistent and inject faults");
+            }
+
+            let faults = fault_injector.unwrap_or_default();
+            faults.configure(config.faults.clone(), config.mock.finalization_blocks);
+            return Ok(Self {
+                inner: Arc::new(MockDaService::with_finality(
+                    config.mock.sender_address,
+                    config.mock.finalization_blocks,
+                )),
+                journal: None,
+                faults: Some(faults),
+            });
+        }
+
+        let inner = MockDaService::new(config.mock.sender_address);
+        if !config.persistent {
+            return Ok(Self {
+                inner: Arc::new(inner),
+                journal: None,
+                faults: None,
+            });
+        }
+
+        let (journal, entries) = BlobJournal::open(&storage_path.join(MOCK_DA_DIR))?;
+        tracing::info!(
+            blobs = entries.len(),
+            "Replaying the persistent mock DA journal"
+        );
+        for entry in entries {
+            match entry {
+                JournalEntry::Batch(blob) => {
+                    let fee = inner.estimate_fee(blob.len()).await?;
+                    inner.send_transaction(&blob, fee).await?;
+                }
+                JournalEntry::Proof(proof) => {
+                    let fee = inner.estimate_fee(proof.len()).await?;
+                    inner.send_aggregated_zk_proof(&proof, fee).await?;
+                }
+            }
+        }
+
+        Ok(Self {
+            inner: Arc::new(inner),
+            journal: Some(Arc::new(Mutex::new(journal))),
+            faults: None,
+        })
+    }
+
+    /// Plans the reorg drawn by the fault injector, if any, before a blob is submitted.
+    async fn inject_reorg(&self) -> anyhow::Result<()> {
+        let Some(faults) = &self.faults else {
+            return Ok(());
+        };
+
+        let head_height = self.inner.get_head_block_header().await?.height();
+        if let Some(depth) = faults.reorg_depth(head_height) {
+            let fork_height = (head_height + 1).saturating_sub(depth.into());
+            tracing::warn!(
+                depth,
+                fork_height,
+                "Mock DA fault: reorging the unfinalized blocks"
+            );
+            self.inner
+                .set_planned_fork(PlannedFork::new(head_height + 1, fork_height, vec![]))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DaService for StarterMockDaService {
+    type Spec = <Inner as DaService>::Spec;
+    type Config = StarterMockDaConfig;
+    type Verifier = <Inner as DaService>::Verifier;
+    type FilteredBlock = <Inner as DaService>::FilteredBlock;
+    type HeaderStream = <Inner as DaService>::HeaderStream;
+    type TransactionId = <Inner as DaService>::TransactionId;
+    type Error = <Inner as DaService>::Error;
+    type Fee = <Inner as DaService>::Fee;
+
+    async fn get_block_at(&self, height: u64) -> Result<Self::FilteredBlock, Self::Error> {
+        if let Some(delay) = self.faults.as_ref().and_then(|faults| faults.delay(height)) {
+            tracing::warn!(height, ?delay, "Mock DA fault: delaying block");
+            tokio::time::sleep(delay).await;
+        }
+        self.inner.get_block_at(height).await
+    }
+
+    async fn get_last_finalized_block_header(&self) -> Result<BlockHeader, Self::Error> {
+        self.inner.get_last_finalized_block_header().await
+    }
+
+    async fn subscribe_finalized_header(&self) -> Result<Self::HeaderStream, Self::Error> {
+        self.inner.subscribe_finalized_header().await
+    }
+
+    async fn get_head_block_header(&self) -> Result<BlockHeader, Self::Error> {
+        self.inner.get_head_block_header().await
+    }
+
+    fn extract_relevant_blobs(
+        &self,
+        block: &Self::FilteredBlock,
+    ) -> RelevantBlobs<BlobTransaction> {
+        let mut blobs = self.inner.extract_relevant_blobs(block);
+        if let Some(faults) = &self.faults {
+            faults.apply_blob_faults(block.header().height(), &mut blobs.batch_blobs);
+        }
+        blobs
+    }
+
+    async fn get_extraction_proof(
+        &self,
+        block: &Self::FilteredBlock,
+        blobs: &RelevantBlobs<BlobTransaction>,
+    ) -> RelevantProofs<InclusionMultiProof, CompletenessProof> {
+        self.inner.get_extraction_proof(block, blobs).await
+    }
+
+    async fn send_transaction(
+        &self,
+        blob: &[u8],
+        fee: Self::Fee,
+    ) -> Result<Self::TransactionId, Self::Error> {
+        self.inject_reorg().await?;
+        let Some(journal) = &self.journal else {
+            return self.inner.send_transaction(blob, fee).await;
+        };
+
+        // The journal is written once the DA accepted the blob, so that a rejected blob isn't replayed after a
+        // restart, and under the lock, so that it keeps the order of the blocks.
+        let mut journal = journal.lock().await;
+        let id = self.inner.send_transaction(blob, fee).await?;
+        journal.append(&JournalEntry::Batch(blob.to_vec()))?;
+        Ok(id)
+    }
+
+    async fn send_aggregated_zk_proof(
+        &self,
+        aggregated_proof_data: &[u8],
+        fee: Self::Fee,
+    ) -> Result<Self::TransactionId, Self::Error> {
+        let Some(journal) = &self.journal else {
+            return self
+                .inner
+                .send_aggregated_zk_proof(aggregated_proof_data, fee)
+                .await;
+        };
+
+        let mut journal = journal.lock().await;
+        let id = self
+            .inner
+            .send_aggregated_zk_proof(aggregated_proof_data, fee)
+            .await?;
+        journal.append(&JournalEntry::Proof(aggregated_proof_data.to_vec()))?;
+        Ok(id)
+    }
+
+    async fn get_aggregated_proofs_at(&self, height: u64) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.inner.get_aggregated_proofs_at(height).await
+    }
+
+    async fn estimate_fee(&self, blob_size: usize) -> Result<Self::Fee, Self::Error> {
+        self.inner.estimate_fee(blob_size).await
+    }
+}
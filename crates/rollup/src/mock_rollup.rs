@@ -1,10 +1,12 @@
 #![deny(missing_docs)]
 //! StarterRollup provides a minimal self-contained rollup implementation
 
+use std::sync::{Arc, OnceLock};
+
 use async_trait::async_trait;
 use sov_db::ledger_db::LedgerDb;
 use sov_kernels::basic::BasicKernel;
-use sov_mock_da::{MockDaConfig, MockDaService, MockDaSpec};
+use sov_mock_da::MockDaSpec;
 use sov_mock_zkvm::{MockCodeCommitment, MockZkvm};
 use sov_modules_api::default_spec::{DefaultSpec, ZkDefaultSpec};
 use sov_modules_api::{CryptoSpec, Spec, Zkvm};
@@ -12,7 +14,7 @@ use sov_modules_rollup_blueprint::RollupBlueprint;
 use sov_modules_stf_blueprint::{RuntimeEndpoints, StfBlueprint};
 use sov_prover_storage_manager::ProverStorageManager;
 use sov_risc0_adapter::host::Risc0Host;
-use sov_rollup_interface::zk::{aggregated_proof::CodeCommitment, ZkvmGuest, ZkvmHost};
+use sov_rollup_interface::zk::{ZkvmGuest, ZkvmHost};
 use sov_sequencer::SequencerDb;
 use sov_state::config::Config as StorageConfig;
 use sov_state::Storage;
@@ -24,17 +26,58 @@ use stf_starter::authentication::ModAuth;
 use stf_starter::Runtime;
 use tokio::sync::watch;
 
-/// Rollup with [`MockDaService`].
-pub struct MockRollup {}
+use crate::code_commitment::aggregated_code_commitment;
+use crate::config::HealthSection;
+use crate::health::{add_health_routes, Readiness, DEFAULT_MAX_DA_LAG_BLOCKS};
+use crate::metrics::{count_requests, spawn_node_metrics};
+use crate::mock_da::faults::FaultInjector;
+use crate::mock_da::{StarterMockDaConfig, StarterMockDaService};
+use crate::shutdown::{GracefulDaService, Shutdown};
+use crate::telemetry::TracedProverService;
+
+/// Rollup with [`StarterMockDaService`].
+#[derive(Default)]
+pub struct MockRollup {
+    fault_injector: Option<FaultInjector>,
+    shutdown: Shutdown,
+    health: HealthSection,
+    da_service: Arc<OnceLock<StarterMockDaService>>,
+}
+
+impl MockRollup {
+    /// Creates a rollup whose mock DA injects the faults commanded through `fault_injector`.
+    pub fn with_fault_injector(fault_injector: FaultInjector) -> Self {
+        Self {
+            fault_injector: Some(fault_injector),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the `[health]` section of the rollup config, read by `/readyz`.
+    pub fn with_health_config(mut self, health: HealthSection) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// The handle to stop the rollup at a slot boundary.
+    pub fn shutdown(&self) -> &Shutdown {
+        &self.shutdown
+    }
+
+    /// The mock DA of the rollup, once it was created.
+    pub fn da_service(&self) -> Option<&StarterMockDaService> {
+        self.da_service.get()
+    }
+}
 
 /// This is the place, where all the rollup components come together, and
 /// they can be easily swapped with alternative implementations as needed.
 #[async_trait]
 impl RollupBlueprint for MockRollup {
     /// This component defines the Data Availability layer.
-    type DaService = MockDaService;
+    type DaService = GracefulDaService<StarterMockDaService>;
     type DaSpec = MockDaSpec;
-    type DaConfig = MockDaConfig;
+    type DaConfig = StarterMockDaConfig;
 
     /// Inner Zkvm representing the rollup circuit
     type InnerZkvmHost = Risc0Host<'static>;
@@ -68,13 +111,15 @@ impl RollupBlueprint for MockRollup {
     type ZkKernel = BasicKernel<Self::ZkSpec, Self::DaSpec>;
 
     /// Prover service.
-    type ProverService = ParallelProverService<
-        <<Self::NativeSpec as Spec>::Storage as Storage>::Root,
-        <<Self::NativeSpec as Spec>::Storage as Storage>::Witness,
-        Self::DaService,
-        Self::InnerZkvmHost,
-        Self::OuterZkvmHost,
-        StfBlueprint<Self::ZkSpec, Self::DaSpec, Self::ZkRuntime, Self::ZkKernel>,
+    type ProverService = TracedProverService<
+        ParallelProverService<
+            <<Self::NativeSpec as Spec>::Storage as Storage>::Root,
+            <<Self::NativeSpec as Spec>::Storage as Storage>::Witness,
+            Self::DaService,
+            Self::InnerZkvmHost,
+            Self::OuterZkvmHost,
+            StfBlueprint<Self::ZkSpec, Self::DaSpec, Self::ZkRuntime, Self::ZkKernel>,
+        >,
     >;
 
     fn create_outer_code_commitment(
@@ -92,7 +137,7 @@ impl RollupBlueprint for MockRollup {
         da_service: &Self::DaService,
         rollup_config: &RollupConfig<Self::DaConfig>,
     ) -> anyhow::Result<RuntimeEndpoints> {
-        sov_modules_rollup_blueprint::register_endpoints::<
+        let endpoints = sov_modules_rollup_blueprint::register_endpoints::<
             Self,
             ModAuth<Self::NativeSpec, Self::DaSpec>,
         >(
@@ -100,15 +145,39 @@ impl RollupBlueprint for MockRollup {
             ledger_db,
             sequencer_db,
             da_service,
-            rollup_config.da.sender_address,
-        )
+            rollup_config.da.mock.sender_address,
+        )?;
+        self.shutdown
+            .close_databases_on_stop(ledger_db, sequencer_db, storage.clone());
+        let readiness = Readiness::<Self::NativeSpec, _>::new(
+            storage.clone(),
+            da_service.clone(),
+            rollup_config.storage.path.clone(),
+            rollup_config.runner.genesis_height,
+            self.health
+                .max_da_lag_blocks
+                .unwrap_or(DEFAULT_MAX_DA_LAG_BLOCKS),
+            self.shutdown.clone(),
+        );
+        let endpoints = add_health_routes(endpoints, readiness);
+        spawn_node_metrics::<Self::NativeSpec, _>(ledger_db.clone(), storage, da_service.clone());
+        count_requests(endpoints)
     }
 
     async fn create_da_service(
         &self,
         rollup_config: &RollupConfig<Self::DaConfig>,
     ) -> Self::DaService {
-        MockDaService::new(rollup_config.da.sender_address)
+        let da_service = StarterMockDaService::new(
+            &rollup_config.da,
+            &rollup_config.storage.path,
+            self.fault_injector.clone(),
+        )
+        .await
+        .expect("Failed to create the mock DA service");
+        // The rollup creates its DA once, the first service is kept
+        let _ = self.da_service.set(da_service.clone());
+        GracefulDaService::new(da_service, self.shutdown.clone())
     }
 
     async fn create_prover_service(
@@ -123,15 +192,15 @@ impl RollupBlueprint for MockRollup {
         let zk_storage = ZkStorage::new();
         let da_verifier = Default::default();
 
-        ParallelProverService::new_with_default_workers(
+        TracedProverService::new(ParallelProverService::new_with_default_workers(
             inner_vm,
             outer_vm,
             zk_stf,
             da_verifier,
             prover_config,
             zk_storage,
-            CodeCommitment::default(),
-        )
+            aggregated_code_commitment(&risc0_starter::MOCK_DA_ID),
+        ))
     }
 
     fn create_storage_manager(
@@ -0,0 +1,323 @@
+//! Graceful shutdown of the node on SIGTERM or SIGINT.
+//!
+//! The runner only yields between the steps of a slot, and writes the state and the ledger of a slot together
+//! once it is applied. So the node stops the runner at a slot boundary: a [`GracefulDaService`] refuses to hand out
+//! DA blocks once the shutdown is requested, so the runner returns when it asks for the next block, and
+//! [`Shutdown::run_runner`] reports that it stopped. If the current slot doesn't finish within the shutdown
+//! timeout, the runner is dropped mid-slot, which abandons the slot as a whole; it is processed again after a
+//! restart.
+//!
+//! Once the runner has returned or was dropped, the close hooks registered with
+//! [`Shutdown::close_databases_on_stop`] flush the sequencer, ledger and state databases, so that nothing written is
+//! left to the closing of the tokio runtime. The pending batch of the sequencer is not submitted. The RPC and REST
+//! servers are then closed by shutting down the runtime, see `main` of the rollup binary.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use sov_db::ledger_db::LedgerDb;
+use sov_rollup_interface::da::{DaSpec, RelevantBlobs, RelevantProofs};
+use sov_rollup_interface::services::da::DaService;
+use sov_sequencer::SequencerDb;
+use sov_state::{DefaultStorageSpec, ProverStorage};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// The time the node has to stop after a shutdown signal, unless configured.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Persists a component of the node once the runner is stopped.
+type CloseHook = Box<dyn FnOnce() -> anyhow::Result<()> + Send>;
+
+/// A handle to request the shutdown of the node, and to wait for the runner to stop. Clones share the same state.
+#[derive(Clone)]
+pub struct Shutdown {
+    requested: Arc<watch::Sender<bool>>,
+    /// Whether a [`GracefulDaService`] refused a block because of the shutdown.
+    refused_block: Arc<AtomicBool>,
+    runner_stopped: Arc<watch::Sender<bool>>,
+    close_hooks: Arc<Mutex<Vec<(&'static str, CloseHook)>>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self {
+            requested: Arc::new(watch::channel(false).0),
+            refused_block: Arc::default(),
+            runner_stopped: Arc::new(watch::channel(false).0),
+            close_hooks: Arc::default(),
+        }
+    }
+}
+
+impl Shutdown {
+    /// Requests the shutdown: the [`GracefulDaService`]s sharing this handle refuse to hand out DA blocks.
+    pub fn request(&self) {
+        self.requested.send_replace(true);
+    }
+
+    /// Whether the shutdown was requested.
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// Waits until the shutdown is requested.
+    pub async fn requested(&self) {
+        let mut requested = self.requested.subscribe();
+        // The sender is owned by `self`, so the channel can't be closed while waiting
+        let _ = requested.wait_for(|requested| *requested).await;
+    }
+
+    /// Waits until the runner run by [`Shutdown::run_runner`] returns after the shutdown was requested.
+    pub async fn runner_stopped(&self) {
+        let mut stopped = self.runner_stopped.subscribe();
+        let _ = stopped.wait_for(|stopped| *stopped).await;
+    }
+
+    /// Registers `hook` to persist the component `name` once the runner is stopped, see [`Shutdown::close`].
+    pub fn on_close(
+        &self,
+        name: &'static str,
+        hook: impl FnOnce() -> anyhow::Result<()> + Send + 'static,
+    ) {
+        self.close_hooks
+            .lock()
+            .expect("The close hooks are poisoned")
+            .push((name, Box::new(hook)));
+    }
+
+    /// Flushes the sequencer database, then the ledger and state databases, once the runner is stopped.
+    ///
+    /// The sequencer database goes first, so that the transactions it holds are kept even if flushing the other
+    /// databases fails.
+    pub fn close_databases_on_stop(
+        &self,
+        ledger_db: &LedgerDb,
+        sequencer_db: &SequencerDb,
+        storage: watch::Receiver<ProverStorage<DefaultStorageSpec>>,
+    ) {
+        let sequencer_db = sequencer_db.clone();
+        self.on_close("sequencer database", move || sequencer_db.flush());
+        let ledger_db = ledger_db.clone();
+        self.on_close("ledger database", move || ledger_db.flush());
+        // The latest storage holds the handles of the state database
+        self.on_close("state database", move || storage.borrow().flush());
+    }
+
+    /// Runs the close hooks in the order they were registered, once. Every hook runs even if one fails; the first
+    /// error is returned.
+    pub fn close(&self) -> anyhow::Result<()> {
+        let hooks = std::mem::take(
+            &mut *self
+                .close_hooks
+                .lock()
+                .expect("The close hooks are poisoned"),
+        );
+        let mut result = Ok(());
+        for (name, hook) in hooks {
+            match hook().with_context(|| format!("Failed to persist the {name}")) {
+                Ok(()) => tracing::info!("Persisted the {name}"),
+                Err(error) => {
+                    tracing::error!(?error, "Failed to persist the {name}");
+                    if result.is_ok() {
+                        result = Err(error);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Runs the `runner` of the node, and reports when it returns after the shutdown was requested, see
+    /// [`Shutdown::runner_stopped`]. The runner then returns at a slot boundary, with the error of the
+    /// [`GracefulDaService`] that refused it the next block, which is not an error of the node.
+    pub async fn run_runner(
+        &self,
+        runner: impl Future<Output = anyhow::Result<()>>,
+    ) -> anyhow::Result<()> {
+        let result = runner.await;
+        if !self.is_requested() {
+            return result;
+        }
+
+        self.runner_stopped.send_replace(true);
+        match result {
+            Err(error) if self.refused_block.load(Ordering::SeqCst) => {
+                tracing::info!(?error, "The runner stopped on shutdown");
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Requests the shutdown and waits for the runner `task` to stop at a slot boundary, or drops it once `timeout`
+    /// elapses, then runs the close hooks. Returns whether the runner stopped at a slot boundary (or exited by
+    /// itself).
+    pub async fn stop_runner(
+        &self,
+        task: &mut JoinHandle<anyhow::Result<()>>,
+        timeout: Duration,
+    ) -> anyhow::Result<bool> {
+        self.request();
+        tokio::select! {
+            result = &mut *task => {
+                result.context("The rollup task panicked")??;
+                tracing::info!("The runner stopped at a slot boundary");
+                self.close()?;
+                return Ok(true);
+            }
+            _ = tokio::time::sleep(timeout) => {}
+        }
+
+        tracing::warn!(
+            ?timeout,
+            "The current slot didn't finish within the shutdown timeout, abandoning it"
+        );
+        task.abort();
+        // The task was aborted, its result is a cancellation
+        let _ = (&mut *task).await;
+        self.close()?;
+        Ok(false)
+    }
+}
+
+/// Waits for SIGTERM or SIGINT (Ctrl-C), and returns the name of the signal.
+pub async fn wait_for_signal() -> anyhow::Result<&'static str> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut terminate =
+            signal(SignalKind::terminate()).context("Failed to listen for SIGTERM")?;
+        tokio::select! {
+            _ = terminate.recv() => Ok("SIGTERM"),
+            result = tokio::signal::ctrl_c() => {
+                result.context("Failed to listen for SIGINT")?;
+                Ok("SIGINT")
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c()
+            .await
+            .context("Failed to listen for Ctrl-C")?;
+        Ok("Ctrl-C")
+    }
+}
+
+/// A [`DaService`] that refuses to hand out DA blocks once the [`Shutdown`] is requested, so that the runner
+/// returns between slots. Submitting blobs and proofs is not affected.
+#[derive(Clone)]
+pub struct GracefulDaService<Da> {
+    inner: Da,
+    shutdown: Shutdown,
+}
+
+impl<Da> GracefulDaService<Da> {
+    /// Wraps `inner`, stopping on `shutdown`.
+    pub fn new(inner: Da, shutdown: Shutdown) -> Self {
+        Self { inner, shutdown }
+    }
+
+    /// The wrapped DA service.
+    pub fn inner(&self) -> &Da {
+        &self.inner
+    }
+}
+
+type BlockHeader<Da> = <<Da as DaService>::Spec as DaSpec>::BlockHeader;
+type BlobTransaction<Da> = <<Da as DaService>::Spec as DaSpec>::BlobTransaction;
+type InclusionMultiProof<Da> = <<Da as DaService>::Spec as DaSpec>::InclusionMultiProof;
+type CompletenessProof<Da> = <<Da as DaService>::Spec as DaSpec>::CompletenessProof;
+
+#[async_trait]
+impl<Da> DaService for GracefulDaService<Da>
+where
+    Da: DaService + Clone,
+    Da::Error: From<anyhow::Error>,
+{
+    type Spec = Da::Spec;
+    type Config = Da::Config;
+    type Verifier = Da::Verifier;
+    type FilteredBlock = Da::FilteredBlock;
+    type HeaderStream = Da::HeaderStream;
+    type TransactionId = Da::TransactionId;
+    type Error = Da::Error;
+    type Fee = Da::Fee;
+
+    async fn get_block_at(&self, height: u64) -> Result<Self::FilteredBlock, Self::Error> {
+        if !self.shutdown.is_requested() {
+            // Waiting for a block is a slot boundary too, the wait is dropped on shutdown
+            tokio::select! {
+                biased;
+                _ = self.shutdown.requested() => {}
+                block = self.inner.get_block_at(height) => return block,
+            }
+        }
+
+        tracing::info!(height, "Shutting down: not handing out the DA block");
+        self.shutdown.refused_block.store(true, Ordering::SeqCst);
+        Err(
+            anyhow!("The node is shutting down, the DA block at height {height} is not handed out")
+                .into(),
+        )
+    }
+
+    async fn get_last_finalized_block_header(&self) -> Result<BlockHeader<Da>, Self::Error> {
+        self.inner.get_last_finalized_block_header().await
+    }
+
+    async fn subscribe_finalized_header(&self) -> Result<Self::HeaderStream, Self::Error> {
+        self.inner.subscribe_finalized_header().await
+    }
+
+    async fn get_head_block_header(&self) -> Result<BlockHeader<Da>, Self::Error> {
+        self.inner.get_head_block_header().await
+    }
+
+    fn extract_relevant_blobs(
+        &self,
+        block: &Self::FilteredBlock,
+    ) -> RelevantBlobs<BlobTransaction<Da>> {
+        self.inner.extract_relevant_blobs(block)
+    }
+
+    async fn get_extraction_proof(
+        &self,
+        block: &Self::FilteredBlock,
+        blobs: &RelevantBlobs<BlobTransaction<Da>>,
+    ) -> RelevantProofs<InclusionMultiProof<Da>, CompletenessProof<Da>> {
+        self.inner.get_extraction_proof(block, blobs).await
+    }
+
+    async fn send_transaction(
+        &self,
+        blob: &[u8],
+        fee: Self::Fee,
+    ) -> Result<Self::TransactionId, Self::Error> {
+        self.inner.send_transaction(blob, fee).await
+    }
+
+    async fn send_aggregated_zk_proof(
+        &self,
+        aggregated_proof_data: &[u8],
+        fee: Self::Fee,
+    ) -> Result<Self::TransactionId, Self::Error> {
+        self.inner
+            .send_aggregated_zk_proof(aggregated_proof_data, fee)
+            .await
+    }
+
+    async fn get_aggregated_proofs_at(&self, height: u64) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.inner.get_aggregated_proofs_at(height).await
+    }
+
+    async fn estimate_fee(&self, blob_size: usize) -> Result<Self::Fee, Self::Error> {
+        self.inner.estimate_fee(blob_size).await
+    }
+}
@@ -0,0 +1,169 @@
+//! Structured logs and OpenTelemetry traces of the node.
+//!
+//! The STF opens the `slot`, `batch`, `authenticate` and `tx` spans (see [`stf_starter::spans`]), and
+//! [`TracedProverService`] the `submit_witness`, `prove` and `aggregate_proofs` spans of the prover. The spans are
+//! exported to an OTLP collector by the layer of [`otlp_layer`].
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use sov_rollup_interface::da::{BlockHeaderTrait, DaSpec};
+use sov_rollup_interface::services::da::DaService;
+use sov_rollup_interface::zk::AggregatedProof;
+use sov_stf_runner::{
+    ProofProcessingStatus, ProverService, ProverServiceError, StateTransitionData,
+    WitnessSubmissionStatus,
+};
+use tracing::Instrument;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// The `service.name` of the exported traces.
+pub const SERVICE_NAME: &str = "sov-rollup-starter";
+
+/// The format of the logs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields of the event and of its spans.
+    Json,
+}
+
+/// A layer exporting the `info` spans to the OTLP collector at `endpoint` (gRPC, e.g. `http://127.0.0.1:4317`).
+///
+/// Must be called within a tokio runtime, which runs the exporter. The spans are sent in batches, call
+/// [`shutdown_otlp`] to send the last ones.
+pub fn otlp_layer<S>(endpoint: &str) -> anyhow::Result<impl Layer<S>>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            opentelemetry_sdk::trace::config()
+                .with_resource(Resource::new([KeyValue::new("service.name", SERVICE_NAME)])),
+        )
+        .install_batch(opentelemetry_sdk::runtime::Tokio)
+        .with_context(|| format!("Failed to start the OTLP exporter to {endpoint}"))?;
+    // The debug spans of the SDK are too many to export
+    Ok(tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .with_filter(LevelFilter::INFO))
+}
+
+/// Sends the spans not exported yet. Must be called while the tokio runtime of [`otlp_layer`] is running.
+pub fn shutdown_otlp() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+/// The number of slots whose DA height is kept for the spans of their proofs.
+const MAX_TRACKED_SLOTS: usize = 1024;
+
+type SlotHash<Da> = <<Da as DaService>::Spec as DaSpec>::SlotHash;
+
+/// A [`ProverService`] that opens a span over each of its steps, tagged with the DA height of the slot.
+pub struct TracedProverService<P: ProverService> {
+    inner: P,
+    /// The DA heights of the latest slots whose witness was submitted.
+    heights: Mutex<VecDeque<(SlotHash<P::DaService>, u64)>>,
+}
+
+impl<P: ProverService> TracedProverService<P> {
+    /// Wraps `inner`.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            heights: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn da_height(&self, block_header_hash: &SlotHash<P::DaService>) -> Option<u64> {
+        self.heights
+            .lock()
+            .expect("The heights of the prover are poisoned")
+            .iter()
+            .find(|(hash, _)| hash == block_header_hash)
+            .map(|(_, da_height)| *da_height)
+    }
+}
+
+#[async_trait]
+impl<P> ProverService for TracedProverService<P>
+where
+    P: ProverService + Send + Sync,
+    P::StateRoot: Send + Sync,
+    P::Witness: Send,
+{
+    type StateRoot = P::StateRoot;
+    type Witness = P::Witness;
+    type DaService = P::DaService;
+    type Verifier = P::Verifier;
+
+    async fn submit_witness(
+        &self,
+        state_transition_data: StateTransitionData<
+            Self::StateRoot,
+            Self::Witness,
+            <Self::DaService as DaService>::Spec,
+        >,
+    ) -> WitnessSubmissionStatus {
+        let header = &state_transition_data.da_block_header;
+        let da_height = header.height();
+        {
+            let mut heights = self
+                .heights
+                .lock()
+                .expect("The heights of the prover are poisoned");
+            if heights.len() == MAX_TRACKED_SLOTS {
+                heights.pop_front();
+            }
+            heights.push_back((header.hash(), da_height));
+        }
+        self.inner
+            .submit_witness(state_transition_data)
+            .instrument(tracing::info_span!("submit_witness", da_height))
+            .await
+    }
+
+    async fn prove(
+        &self,
+        block_header_hash: SlotHash<Self::DaService>,
+    ) -> Result<ProofProcessingStatus, ProverServiceError> {
+        let span = tracing::info_span!("prove", da_height = self.da_height(&block_header_hash));
+        self.inner.prove(block_header_hash).instrument(span).await
+    }
+
+    async fn create_aggregated_proof(
+        &self,
+        block_header_hashes: &[SlotHash<Self::DaService>],
+        genesis_state_root: &Self::StateRoot,
+    ) -> anyhow::Result<AggregatedProof> {
+        let span = tracing::info_span!(
+            "aggregate_proofs",
+            first_da_height = block_header_hashes
+                .first()
+                .and_then(|hash| self.da_height(hash)),
+            last_da_height = block_header_hashes
+                .last()
+                .and_then(|hash| self.da_height(hash)),
+            slots = block_header_hashes.len(),
+        );
+        self.inner
+            .create_aggregated_proof(block_header_hashes, genesis_state_root)
+            .instrument(span)
+            .await
+    }
+}
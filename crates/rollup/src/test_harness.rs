@@ -0,0 +1,493 @@
+//! A harness to run a [`MockRollup`] in tests.
+//!
+//! ```ignore
+//! let mut rollup = TestRollup::builder().prefunded_keys(1, 1_000_000).start().await?;
+//! let tx = rollup.signers_mut()[0].sign(&call);
+//! rollup.send_transactions(&[tx]).await?;
+//! rollup.wait_for_slot().await?;
+//! let address = rollup.signers()[0].address();
+//! let balance = sov_bank::BankRpcClient::<TestSpec>::balance_of(rollup.http(), None, address, token_id).await?;
+//! rollup.shutdown().await;
+//! ```
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use borsh::BorshSerialize;
+use jsonrpsee::core::client::{ClientT, Subscription, SubscriptionClientT};
+use jsonrpsee::http_client::HttpClient;
+use jsonrpsee::rpc_params;
+use serde_json::Value;
+use sov_cli::wallet_state::PrivateKeyAndAddress;
+use sov_kernels::basic::BasicKernelGenesisConfig;
+use sov_mock_da::{MockAddress, MockDaConfig, MockDaSpec};
+use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
+use sov_modules_api::{CryptoSpec, PrivateKey, Spec};
+use sov_modules_rollup_blueprint::RollupBlueprint;
+use sov_sequencer::utils::SimpleClient;
+use sov_stf_runner::{
+    HttpServerConfig, ProofManagerConfig, RollupConfig, RollupProverConfig, RunnerConfig,
+    StorageConfig,
+};
+use stf_starter::genesis_config::GenesisPaths;
+use stf_starter::RuntimeCall;
+use tempfile::TempDir;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::mock_da::faults::FaultInjector;
+use crate::mock_da::{StarterMockDaConfig, StarterMockDaService};
+use crate::mock_rollup::MockRollup;
+use crate::shutdown::Shutdown;
+
+/// The native spec of the [`MockRollup`].
+pub type TestSpec = <MockRollup as RollupBlueprint>::NativeSpec;
+
+/// The default maximum fee of the transactions signed by a [`TestSigner`].
+pub const DEFAULT_MAX_TX_FEE: u64 = 10_000;
+
+/// The time [`TestRollup::shutdown`] gives the rollup to stop at a slot boundary, like the node by default.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Returns the mock genesis of this repository.
+pub fn default_genesis_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test-data/genesis/mock")
+}
+
+/// Returns the rollup config of a test rollup: RPC servers on free local ports and storage in `storage_path`.
+pub fn test_rollup_config(
+    storage_path: &Path,
+    da_config: StarterMockDaConfig,
+) -> RollupConfig<StarterMockDaConfig> {
+    RollupConfig {
+        storage: StorageConfig {
+            path: storage_path.to_path_buf(),
+        },
+        runner: RunnerConfig {
+            genesis_height: 0,
+            da_polling_interval_ms: 1000,
+            rpc_config: HttpServerConfig {
+                bind_host: "127.0.0.1".into(),
+                bind_port: 0,
+            },
+            axum_config: HttpServerConfig {
+                bind_host: "127.0.0.1".into(),
+                bind_port: 0,
+            },
+        },
+        da: da_config,
+        proof_manager: ProofManagerConfig {
+            aggregated_proof_block_jump: 1,
+        },
+    }
+}
+
+/// Signs transactions for a key, tracking its nonce.
+#[derive(Clone)]
+pub struct TestSigner {
+    private_key: <<TestSpec as Spec>::CryptoSpec as CryptoSpec>::PrivateKey,
+    address: <TestSpec as Spec>::Address,
+    nonce: u64,
+    chain_id: u64,
+    max_fee: u64,
+}
+
+impl TestSigner {
+    /// Creates a signer for `key`, whose next transaction has nonce `nonce`.
+    pub fn new(key: PrivateKeyAndAddress<TestSpec>, nonce: u64) -> Self {
+        Self {
+            private_key: key.private_key,
+            address: key.address,
+            nonce,
+            chain_id: 0,
+            max_fee: DEFAULT_MAX_TX_FEE,
+        }
+    }
+
+    /// Generates a new key.
+    pub fn generate() -> Self {
+        let private_key = <<TestSpec as Spec>::CryptoSpec as CryptoSpec>::PrivateKey::generate();
+        let address = private_key.to_address::<<TestSpec as Spec>::Address>();
+        Self::new(
+            PrivateKeyAndAddress {
+                private_key,
+                address,
+            },
+            0,
+        )
+    }
+
+    /// The address of the key.
+    pub fn address(&self) -> <TestSpec as Spec>::Address {
+        self.address.clone()
+    }
+
+    /// The nonce of the next transaction.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Signs `call` with the next nonce.
+    pub fn sign(&mut self, call: &RuntimeCall<TestSpec, MockDaSpec>) -> Transaction<TestSpec> {
+        let tx = Transaction::<TestSpec>::new_signed_tx(
+            &self.private_key,
+            UnsignedTransaction::new(
+                call.try_to_vec().expect("Runtime calls are serializable"),
+                self.chain_id,
+                PriorityFeeBips::ZERO,
+                self.max_fee,
+                self.nonce,
+                None,
+            ),
+        );
+        self.nonce += 1;
+        tx
+    }
+}
+
+/// Builds and starts a [`TestRollup`].
+pub struct TestRollupBuilder {
+    genesis_dir: PathBuf,
+    genesis_overrides: Vec<(String, Value)>,
+    prefunded_keys: usize,
+    prefunded_amount: u64,
+    prover_config: RollupProverConfig,
+    da_config: StarterMockDaConfig,
+    fault_injector: Option<FaultInjector>,
+}
+
+impl Default for TestRollupBuilder {
+    fn default() -> Self {
+        Self {
+            genesis_dir: default_genesis_dir(),
+            genesis_overrides: Vec::new(),
+            prefunded_keys: 0,
+            prefunded_amount: 0,
+            prover_config: RollupProverConfig::Skip,
+            da_config: MockDaConfig {
+                sender_address: MockAddress::new([0; 32]),
+                finalization_blocks: 3,
+                wait_attempts: 10,
+            }
+            .into(),
+            fault_injector: None,
+        }
+    }
+}
+
+impl TestRollupBuilder {
+    /// Reads the genesis from `dir` instead of the mock genesis of this repository.
+    pub fn genesis_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.genesis_dir = dir.into();
+        self
+    }
+
+    /// Merges `patch` into the genesis file `file` (e.g. `bank.json`), as a JSON merge patch.
+    pub fn override_genesis(mut self, file: impl Into<String>, patch: Value) -> Self {
+        self.genesis_overrides.push((file.into(), patch));
+        self
+    }
+
+    /// Generates `count` keys, each holding `amount` gas tokens at genesis.
+    pub fn prefunded_keys(mut self, count: usize, amount: u64) -> Self {
+        self.prefunded_keys = count;
+        self.prefunded_amount = amount;
+        self
+    }
+
+    /// Sets the prover mode. Proving is skipped by default.
+    pub fn prover_config(mut self, prover_config: RollupProverConfig) -> Self {
+        self.prover_config = prover_config;
+        self
+    }
+
+    /// Sets the mock DA config.
+    pub fn da_config(mut self, da_config: StarterMockDaConfig) -> Self {
+        self.da_config = da_config;
+        self
+    }
+
+    /// Injects the faults commanded through `fault_injector` into the mock DA.
+    pub fn fault_injector(mut self, fault_injector: FaultInjector) -> Self {
+        self.fault_injector = Some(fault_injector);
+        self
+    }
+
+    /// Writes the genesis, starts the rollup and waits for its RPC server.
+    pub async fn start(self) -> anyhow::Result<TestRollup> {
+        self.da_config
+            .check_prover_config(Some(&self.prover_config), self.fault_injector.is_some())?;
+        let temp_dir = tempfile::tempdir()?;
+        let genesis_dir = temp_dir.path().join("genesis");
+        let signers: Vec<_> = (0..self.prefunded_keys)
+            .map(|_| TestSigner::generate())
+            .collect();
+        self.write_genesis(&genesis_dir, &signers)?;
+
+        let mock_rollup = match self.fault_injector {
+            Some(fault_injector) => MockRollup::with_fault_injector(fault_injector),
+            None => MockRollup::default(),
+        };
+        let kernel_genesis = BasicKernelGenesisConfig {
+            chain_state: serde_json::from_value(read_json(&genesis_dir.join("chain_state.json"))?)?,
+        };
+        let shutdown = mock_rollup.shutdown().clone();
+        let rollup_config = test_rollup_config(&temp_dir.path().join("storage"), self.da_config);
+
+        let rollup = mock_rollup
+            .create_new_rollup(
+                &GenesisPaths::from_dir(&genesis_dir),
+                kernel_genesis,
+                rollup_config.clone(),
+                Some(self.prover_config),
+            )
+            .await?;
+
+        let da_service = mock_rollup
+            .da_service()
+            .cloned()
+            .context("The rollup did not create its mock DA")?;
+
+        let (rpc_addr_tx, rpc_addr_rx) = oneshot::channel();
+        let (rest_addr_tx, rest_addr_rx) = oneshot::channel();
+        let runner_shutdown = shutdown.clone();
+        let task = tokio::spawn(async move {
+            runner_shutdown
+                .run_runner(rollup.run_and_report_addr(Some(rpc_addr_tx), Some(rest_addr_tx)))
+                .await
+        });
+        let (Ok(rpc_addr), Ok(rest_addr)) = (rpc_addr_rx.await, rest_addr_rx.await) else {
+            task.await??;
+            bail!("The rollup stopped before starting its RPC servers");
+        };
+
+        let client = SimpleClient::new("localhost", rpc_addr.port()).await?;
+        let slots = client
+            .ws()
+            .subscribe(
+                "ledger_subscribeSlots",
+                rpc_params![],
+                "ledger_unsubscribeSlots",
+            )
+            .await?;
+
+        Ok(TestRollup {
+            rpc_addr,
+            rest_addr,
+            client,
+            slots,
+            signers,
+            task,
+            shutdown,
+            da_service,
+            genesis_dir,
+            rollup_config,
+            stopped: false,
+            _temp_dir: temp_dir,
+        })
+    }
+
+    /// Copies the genesis to `dir`, then applies the overrides and funds the `signers`.
+    fn write_genesis(&self, dir: &Path, signers: &[TestSigner]) -> anyhow::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        for entry in std::fs::read_dir(&self.genesis_dir)
+            .with_context(|| format!("Failed to read genesis dir {}", self.genesis_dir.display()))?
+        {
+            let entry = entry?;
+            std::fs::copy(entry.path(), dir.join(entry.file_name()))?;
+        }
+
+        for (file, patch) in &self.genesis_overrides {
+            let path = dir.join(file);
+            let mut genesis = read_json(&path)?;
+            merge_patch(&mut genesis, patch);
+            write_json(&path, &genesis)?;
+        }
+
+        if !signers.is_empty() {
+            let path = dir.join("bank.json");
+            let mut bank = read_json(&path)?;
+            let balances = bank
+                .pointer_mut("/gas_token_config/address_and_balances")
+                .and_then(Value::as_array_mut)
+                .context("The bank genesis has no gas token balances")?;
+            for signer in signers {
+                balances.push(serde_json::json!([
+                    signer.address().to_string(),
+                    self.prefunded_amount
+                ]));
+            }
+            write_json(&path, &bank)?;
+        }
+        Ok(())
+    }
+}
+
+/// A running [`MockRollup`], stopped when dropped.
+pub struct TestRollup {
+    rpc_addr: SocketAddr,
+    rest_addr: SocketAddr,
+    client: SimpleClient,
+    slots: Subscription<u64>,
+    signers: Vec<TestSigner>,
+    task: JoinHandle<anyhow::Result<()>>,
+    shutdown: Shutdown,
+    da_service: StarterMockDaService,
+    genesis_dir: PathBuf,
+    rollup_config: RollupConfig<StarterMockDaConfig>,
+    stopped: bool,
+    _temp_dir: TempDir,
+}
+
+impl TestRollup {
+    /// Returns a builder with the mock genesis of this repository.
+    pub fn builder() -> TestRollupBuilder {
+        TestRollupBuilder::default()
+    }
+
+    /// The address of the JSON-RPC server.
+    pub fn rpc_addr(&self) -> SocketAddr {
+        self.rpc_addr
+    }
+
+    /// The address of the REST server.
+    pub fn rest_addr(&self) -> SocketAddr {
+        self.rest_addr
+    }
+
+    /// The HTTP client of the JSON-RPC server, to use with the typed module clients,
+    /// e.g. `sov_bank::BankRpcClient::<TestSpec>::balance_of(rollup.http(), ..)`.
+    pub fn http(&self) -> &HttpClient {
+        self.client.http()
+    }
+
+    /// The client of the JSON-RPC server.
+    pub fn client(&self) -> &SimpleClient {
+        &self.client
+    }
+
+    /// The signers of the prefunded keys, in the order they were generated.
+    pub fn signers(&self) -> &[TestSigner] {
+        &self.signers
+    }
+
+    /// The signers of the prefunded keys, to sign transactions.
+    pub fn signers_mut(&mut self) -> &mut [TestSigner] {
+        &mut self.signers
+    }
+
+    /// The directory of the genesis files the rollup was started with.
+    pub fn genesis_dir(&self) -> &Path {
+        &self.genesis_dir
+    }
+
+    /// The config the rollup was started with.
+    pub fn rollup_config(&self) -> &RollupConfig<StarterMockDaConfig> {
+        &self.rollup_config
+    }
+
+    /// The mock DA the rollup reads its blocks from.
+    pub fn da_service(&self) -> &StarterMockDaService {
+        &self.da_service
+    }
+
+    /// Submits `txs` to the sequencer.
+    pub async fn send_transactions(&self, txs: &[Transaction<TestSpec>]) -> anyhow::Result<()> {
+        self.client.send_transactions(txs).await?;
+        Ok(())
+    }
+
+    /// Waits until the rollup processes its next slot, and returns its number.
+    pub async fn wait_for_slot(&mut self) -> anyhow::Result<u64> {
+        self.slots
+            .next()
+            .await
+            .context("The slot subscription was closed")?
+            .context("Failed to read the slot subscription")
+    }
+
+    /// Waits until the transaction with the hex hash `tx_hash` is processed, for at most `max_slots` slots.
+    pub async fn wait_for_tx(&mut self, tx_hash: &str, max_slots: usize) -> anyhow::Result<Value> {
+        for _ in 0..=max_slots {
+            let tx: Option<Value> = self
+                .http()
+                .request("ledger_getTxByHash", rpc_params![tx_hash])
+                .await?;
+            if let Some(tx) = tx {
+                return Ok(tx);
+            }
+            tokio::time::timeout(Duration::from_secs(60), self.wait_for_slot())
+                .await
+                .context("Timed out waiting for a slot")??;
+        }
+        bail!("Transaction {tx_hash} was not processed within {max_slots} slots")
+    }
+
+    /// Stops the rollup, keeping its storage until the `TestRollup` is dropped, e.g. to open it.
+    pub async fn stop(&mut self) {
+        if self.stopped {
+            return;
+        }
+        self.stopped = true;
+        self.task.abort();
+        let _ = (&mut self.task).await;
+    }
+
+    /// Stops the rollup like the node does on SIGTERM: at a slot boundary, or mid-slot after `timeout`.
+    /// Returns whether it stopped at a slot boundary.
+    pub async fn stop_gracefully(&mut self, timeout: Duration) -> anyhow::Result<bool> {
+        if self.stopped {
+            bail!("The rollup was already stopped");
+        }
+        self.stopped = true;
+        self.shutdown.stop_runner(&mut self.task, timeout).await
+    }
+
+    /// Stops the rollup gracefully, like [`Self::stop_gracefully`], unless it was already stopped, and panics if
+    /// the rollup failed. Its storage is removed when the `TestRollup` is dropped at the end of the call.
+    pub async fn shutdown(mut self) {
+        if !self.stopped {
+            self.stop_gracefully(SHUTDOWN_TIMEOUT)
+                .await
+                .expect("The rollup failed to stop");
+        }
+    }
+}
+
+impl Drop for TestRollup {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn read_json(path: &Path) -> anyhow::Result<Value> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn write_json(path: &Path, value: &Value) -> anyhow::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Applies a JSON merge patch (RFC 7386): objects are merged, `null` removes a field, anything else replaces.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    let target = target.as_object_mut().expect("target is an object");
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
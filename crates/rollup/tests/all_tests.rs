@@ -1,3 +1,18 @@
 mod bank;
+mod chain_info;
+mod circuit_breaker;
+mod code_commitment;
+mod config;
+mod evm;
+mod genesis;
+mod harness;
+mod health;
+mod home;
+mod metrics;
+mod mock_da;
+mod sequencer_outcomes;
+mod shutdown;
+mod telemetry;
+mod tx_index;
 // Add additional tests here
 mod test_helpers;
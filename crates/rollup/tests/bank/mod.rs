@@ -2,7 +2,7 @@ use std::env;
 use std::net::SocketAddr;
 use std::str::FromStr;
 
-use super::test_helpers::{read_private_keys, start_rollup};
+use super::test_helpers::{read_private_keys, start_rollup, start_rollup_with_faults};
 use borsh::BorshSerialize;
 use jsonrpsee::core::client::{Subscription, SubscriptionClientT};
 use jsonrpsee::rpc_params;
@@ -10,6 +10,7 @@ use sov_kernels::basic::BasicKernelGenesisPaths;
 use sov_mock_da::{MockAddress, MockDaConfig, MockDaSpec};
 use sov_modules_api::transaction::{PriorityFeeBips, Transaction, UnsignedTransaction};
 use sov_modules_api::Spec;
+use sov_rollup_starter::mock_da::faults::FaultInjector;
 use sov_sequencer::utils::SimpleClient;
 use sov_stf_runner::RollupProverConfig;
 use stf_starter::genesis_config::GenesisPaths;
@@ -21,15 +22,16 @@ use tracing_subscriber::{fmt, EnvFilter};
 const TOKEN_SALT: u64 = 0;
 const TOKEN_NAME: &str = "sov-token";
 const MAX_TX_FEE: u64 = 10_000;
+const INITIAL_BALANCE: u64 = 1000;
 
 type TestSpec = sov_modules_api::default_spec::DefaultSpec<
     sov_mock_zkvm::MockZkVerifier,
     sov_mock_zkvm::MockZkVerifier,
 >;
 
-#[tokio::test]
-async fn bank_tx_tests() -> Result<(), anyhow::Error> {
-    tracing_subscriber::registry()
+fn init_logging() {
+    // Tests share the global subscriber, so only the first one sets it.
+    let _ = tracing_subscriber::registry()
         .with(fmt::layer())
         .with(
             EnvFilter::from_str(
@@ -38,7 +40,12 @@ async fn bank_tx_tests() -> Result<(), anyhow::Error> {
             )
             .unwrap(),
         )
-        .init();
+        .try_init();
+}
+
+#[tokio::test]
+async fn bank_tx_tests() -> Result<(), anyhow::Error> {
+    init_logging();
     let (port_tx, port_rx) = tokio::sync::oneshot::channel();
 
     let rollup_task = tokio::spawn(async {
@@ -53,7 +60,8 @@ async fn bank_tx_tests() -> Result<(), anyhow::Error> {
                 sender_address: MockAddress::new([0; 32]),
                 finalization_blocks: 3,
                 wait_attempts: 10,
-            },
+            }
+            .into(),
         )
         .await;
     });
@@ -67,19 +75,63 @@ async fn bank_tx_tests() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn bank_tx_with_dropped_blob_tests() -> Result<(), anyhow::Error> {
+    init_logging();
+    let (port_tx, port_rx) = tokio::sync::oneshot::channel();
+    let fault_injector = FaultInjector::default();
+    fault_injector.drop_next_blob();
+
+    let rollup_task = tokio::spawn(async {
+        start_rollup_with_faults(
+            port_tx,
+            GenesisPaths::from_dir("../../test-data/genesis/mock/"),
+            BasicKernelGenesisPaths {
+                chain_state: "../../test-data/genesis/mock/chain_state.json".into(),
+            },
+            RollupProverConfig::Skip,
+            MockDaConfig {
+                sender_address: MockAddress::new([0; 32]),
+                finalization_blocks: 3,
+                wait_attempts: 10,
+            }
+            .into(),
+            fault_injector,
+        )
+        .await;
+    });
+    let port = port_rx.await.unwrap();
+
+    // The batch never reaches the rollup, so the token is not created
+    tokio::select! {
+        err = rollup_task => err?,
+        res = create_test_token(port) => assert_eq!(0, res?, "token created from a dropped blob"),
+    }
+    Ok(())
+}
+
 async fn send_test_create_token_tx(rpc_address: SocketAddr) -> Result<(), anyhow::Error> {
+    assert_eq!(
+        INITIAL_BALANCE,
+        create_test_token(rpc_address).await?,
+        "deployer initial balance is not correct"
+    );
+    Ok(())
+}
+
+/// Sends a token creation transaction and returns the balance of the deployer after the next slot.
+async fn create_test_token(rpc_address: SocketAddr) -> Result<u64, anyhow::Error> {
     let key_and_address = read_private_keys::<TestSpec>("tx_signer_private_key.json");
     let key = key_and_address.private_key;
     let user_address: <TestSpec as Spec>::Address = key_and_address.address;
 
     let token_id = sov_bank::get_token_id::<TestSpec>(TOKEN_NAME, &user_address, TOKEN_SALT);
-    let initial_balance = 1000;
 
     let msg =
         RuntimeCall::<TestSpec, MockDaSpec>::bank(sov_bank::CallMessage::<TestSpec>::CreateToken {
             salt: TOKEN_SALT,
             token_name: TOKEN_NAME.to_string(),
-            initial_balance,
+            initial_balance: INITIAL_BALANCE,
             minter_address: user_address,
             authorized_minters: vec![],
         });
@@ -122,10 +174,5 @@ async fn send_test_create_token_tx(rpc_address: SocketAddr) -> Result<(), anyhow
         token_id,
     )
     .await?;
-    assert_eq!(
-        initial_balance,
-        balance_response.amount.unwrap_or_default(),
-        "deployer initial balance is not correct"
-    );
-    Ok(())
+    Ok(balance_response.amount.unwrap_or_default())
 }
@@ -0,0 +1,111 @@
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::params::ArrayParams;
+use jsonrpsee::rpc_params;
+use serde_json::Value;
+use sov_rollup_interface::da::BlockHeaderTrait;
+use sov_rollup_interface::services::da::{DaService, SlotData};
+use sov_rollup_starter::test_harness::TestRollup;
+use stf_starter::chain_info::MAX_SLOTS_PER_QUERY;
+
+const SLOTS: u64 = 3;
+const MAX_SLOTS: usize = 10;
+
+async fn chain_info(
+    rollup: &TestRollup,
+    method: &str,
+    params: ArrayParams,
+) -> anyhow::Result<Value> {
+    Ok(rollup
+        .http()
+        .request(&format!("chainInfo_{method}"), params)
+        .await?)
+}
+
+async fn wait_for_slots(rollup: &mut TestRollup, count: u64) -> anyhow::Result<Value> {
+    for _ in 0..MAX_SLOTS {
+        let latest = chain_info(rollup, "latestSlot", rpc_params![]).await?;
+        if latest["slot_number"]
+            .as_u64()
+            .is_some_and(|number| number >= count)
+        {
+            return Ok(latest);
+        }
+        rollup.wait_for_slot().await?;
+    }
+    anyhow::bail!("{count} slots were not recorded within {MAX_SLOTS} slots")
+}
+
+#[tokio::test]
+async fn slots_are_recorded_with_their_da_blocks() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder().start().await?;
+    let chain_state: Value = serde_json::from_str(&std::fs::read_to_string(
+        rollup.genesis_dir().join("chain_state.json"),
+    )?)?;
+    let genesis_da_height = chain_state["genesis_da_height"]
+        .as_u64()
+        .expect("The chain state has no genesis DA height");
+
+    let latest = wait_for_slots(&mut rollup, SLOTS).await?;
+    let count = latest["slot_number"].as_u64().unwrap();
+    let slots = chain_info(&rollup, "getSlots", rpc_params![1, count]).await?;
+    let slots = slots.as_array().expect("The slots are not an array");
+    assert_eq!(slots.len() as u64, count);
+    assert_eq!(slots.last(), Some(&latest));
+
+    for (slot_number, slot) in (1..).zip(slots) {
+        assert_eq!(slot["slot_number"], slot_number);
+        assert_eq!(
+            &chain_info(&rollup, "getSlot", rpc_params![slot_number]).await?,
+            slot
+        );
+
+        // Each slot is the next block of the DA after the genesis
+        let da_height = slot["da_height"].as_u64().unwrap();
+        assert_eq!(da_height, genesis_da_height + slot_number);
+        let block = rollup.da_service().get_block_at(da_height).await?;
+        let header = block.header();
+        assert_eq!(header.height(), da_height);
+        assert_eq!(slot["da_block_hash"], serde_json::to_value(header.hash())?);
+        assert_eq!(slot["time"], serde_json::to_value(header.time())?);
+    }
+
+    rollup.shutdown().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn slot_queries_are_bounded() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder().start().await?;
+    let latest = wait_for_slots(&mut rollup, 1).await?;
+    let count = latest["slot_number"].as_u64().unwrap();
+
+    // Slots start at 1, the genesis has none
+    assert_eq!(
+        chain_info(&rollup, "getSlot", rpc_params![0]).await?,
+        Value::Null
+    );
+    assert_eq!(
+        chain_info(&rollup, "getSlot", rpc_params![count + MAX_SLOTS_PER_QUERY]).await?,
+        Value::Null
+    );
+    assert_eq!(
+        chain_info(&rollup, "getSlots", rpc_params![0, 10]).await?,
+        serde_json::json!([])
+    );
+    assert_eq!(
+        chain_info(&rollup, "getSlots", rpc_params![u64::MAX, u64::MAX]).await?,
+        serde_json::json!([])
+    );
+
+    let slots = chain_info(
+        &rollup,
+        "getSlots",
+        rpc_params![1, MAX_SLOTS_PER_QUERY + 10],
+    )
+    .await?;
+    let returned = slots.as_array().map(Vec::len).unwrap_or_default() as u64;
+    assert!(returned >= count && returned <= MAX_SLOTS_PER_QUERY);
+
+    rollup.shutdown().await;
+    Ok(())
+}
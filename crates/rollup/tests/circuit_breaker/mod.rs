@@ -0,0 +1,233 @@
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::Spec;
+use sov_rollup_starter::test_harness::{TestRollup, TestSigner, TestSpec};
+use stf_starter::circuit_breaker::CallMessage;
+use stf_starter::RuntimeCall;
+
+use super::test_helpers::{find_event, ledger_events, read_private_keys};
+
+const TRANSFER_AMOUNT: u64 = 100;
+const TOKEN_SALT: u64 = 0;
+const TOKEN_NAME: &str = "paused-token";
+const INITIAL_BALANCE: u64 = 1000;
+const MAX_SLOTS: usize = 10;
+
+type Address = <TestSpec as Spec>::Address;
+
+/// The admin of the circuit breaker in the mock genesis.
+fn admin() -> TestSigner {
+    TestSigner::new(
+        read_private_keys::<TestSpec>("token_deployer_private_key.json"),
+        0,
+    )
+}
+
+fn pause(target: &str) -> RuntimeCall<TestSpec, MockDaSpec> {
+    RuntimeCall::<TestSpec, MockDaSpec>::circuit_breaker(CallMessage::Pause {
+        target: target.to_string(),
+    })
+}
+
+fn unpause(target: &str) -> RuntimeCall<TestSpec, MockDaSpec> {
+    RuntimeCall::<TestSpec, MockDaSpec>::circuit_breaker(CallMessage::Unpause {
+        target: target.to_string(),
+    })
+}
+
+fn transfer(to: &Address) -> RuntimeCall<TestSpec, MockDaSpec> {
+    RuntimeCall::<TestSpec, MockDaSpec>::bank(sov_bank::CallMessage::Transfer {
+        to: to.clone(),
+        coins: sov_bank::Coins {
+            amount: TRANSFER_AMOUNT,
+            token_id: sov_bank::config_gas_token_id(),
+        },
+    })
+}
+
+fn create_token(minter: &Address) -> RuntimeCall<TestSpec, MockDaSpec> {
+    RuntimeCall::<TestSpec, MockDaSpec>::bank(sov_bank::CallMessage::<TestSpec>::CreateToken {
+        salt: TOKEN_SALT,
+        token_name: TOKEN_NAME.to_string(),
+        initial_balance: INITIAL_BALANCE,
+        minter_address: minter.clone(),
+        authorized_minters: vec![],
+    })
+}
+
+async fn balance(
+    rollup: &TestRollup,
+    address: &Address,
+    token_id: sov_bank::TokenId,
+) -> anyhow::Result<u64> {
+    let balance = sov_bank::BankRpcClient::<TestSpec>::balance_of(
+        rollup.http(),
+        None,
+        address.clone(),
+        token_id,
+    )
+    .await?;
+    Ok(balance.amount.unwrap_or_default())
+}
+
+async fn wait_for_balance(
+    rollup: &mut TestRollup,
+    address: &Address,
+    token_id: sov_bank::TokenId,
+    amount: u64,
+) -> anyhow::Result<()> {
+    for _ in 0..MAX_SLOTS {
+        if balance(rollup, address, token_id).await? == amount {
+            return Ok(());
+        }
+        rollup.wait_for_slot().await?;
+    }
+    anyhow::bail!("The balance of {address} did not reach {amount} within {MAX_SLOTS} slots")
+}
+
+async fn has_event(rollup: &TestRollup, name: &str, target: &str) -> anyhow::Result<bool> {
+    Ok(ledger_events(rollup)
+        .await?
+        .iter()
+        .any(|event| find_event(event, name).is_some_and(|event| event["target"] == target)))
+}
+
+async fn wait_for_event(rollup: &mut TestRollup, name: &str, target: &str) -> anyhow::Result<()> {
+    for _ in 0..MAX_SLOTS {
+        if has_event(rollup, name, target).await? {
+            return Ok(());
+        }
+        rollup.wait_for_slot().await?;
+    }
+    anyhow::bail!("No {name} event for `{target}` within {MAX_SLOTS} slots")
+}
+
+#[tokio::test]
+async fn paused_call_is_rejected_until_unpaused() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(3, 1_000_000)
+        .start()
+        .await?;
+    let mut admin = admin();
+    let gas_token_id = sov_bank::config_gas_token_id();
+
+    let tx = admin.sign(&pause("bank.transfer"));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_event(&mut rollup, "Paused", "bank.transfer").await?;
+
+    // Only the paused call of the module is rejected
+    let rejected_recipient = TestSigner::generate().address();
+    let minter = rollup.signers()[1].address();
+    let txs = [
+        rollup.signers_mut()[0].sign(&transfer(&rejected_recipient)),
+        rollup.signers_mut()[1].sign(&create_token(&minter)),
+    ];
+    rollup.send_transactions(&txs).await?;
+    let token_id = sov_bank::get_token_id::<TestSpec>(TOKEN_NAME, &minter, TOKEN_SALT);
+    wait_for_balance(&mut rollup, &minter, token_id, INITIAL_BALANCE).await?;
+    // The dropped transaction is reported by a rejection event
+    wait_for_event(&mut rollup, "TxRejected", "bank.transfer").await?;
+    assert_eq!(
+        balance(&rollup, &rejected_recipient, gas_token_id).await?,
+        0
+    );
+
+    let tx = admin.sign(&unpause("bank.transfer"));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_event(&mut rollup, "Unpaused", "bank.transfer").await?;
+
+    let recipient = TestSigner::generate().address();
+    let tx = rollup.signers_mut()[2].sign(&transfer(&recipient));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_balance(&mut rollup, &recipient, gas_token_id, TRANSFER_AMOUNT).await?;
+
+    rollup.shutdown().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn module_and_global_pauses_match_every_call() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(3, 1_000_000)
+        .start()
+        .await?;
+    let mut admin = admin();
+    let gas_token_id = sov_bank::config_gas_token_id();
+
+    let tx = admin.sign(&pause("bank"));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_event(&mut rollup, "Paused", "bank").await?;
+
+    let minter = rollup.signers()[0].address();
+    let tx = rollup.signers_mut()[0].sign(&create_token(&minter));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_event(&mut rollup, "TxRejected", "bank").await?;
+    let token_id = sov_bank::get_token_id::<TestSpec>(TOKEN_NAME, &minter, TOKEN_SALT);
+    assert_eq!(balance(&rollup, &minter, token_id).await?, 0);
+
+    let txs = [admin.sign(&unpause("bank")), admin.sign(&pause("*"))];
+    rollup.send_transactions(&txs).await?;
+    wait_for_event(&mut rollup, "Paused", "*").await?;
+
+    let rejected_recipient = TestSigner::generate().address();
+    let tx = rollup.signers_mut()[1].sign(&transfer(&rejected_recipient));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_event(&mut rollup, "TxRejected", "*").await?;
+    assert_eq!(
+        balance(&rollup, &rejected_recipient, gas_token_id).await?,
+        0
+    );
+
+    // The circuit breaker itself is never paused
+    let tx = admin.sign(&unpause("*"));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_event(&mut rollup, "Unpaused", "*").await?;
+
+    let recipient = TestSigner::generate().address();
+    let tx = rollup.signers_mut()[2].sign(&transfer(&recipient));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_balance(&mut rollup, &recipient, gas_token_id, TRANSFER_AMOUNT).await?;
+
+    rollup.shutdown().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn only_admins_pause_known_targets() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(2, 1_000_000)
+        .start()
+        .await?;
+    let mut admin = admin();
+    let gas_token_id = sov_bank::config_gas_token_id();
+
+    let mut txs = vec![rollup.signers_mut()[0].sign(&pause("bank.transfer"))];
+    for target in [
+        "",
+        "bank.unknown",
+        "bank.transfer.extra",
+        "evm.call",
+        "chain_info",
+        "circuit_breaker",
+        "unknown",
+    ] {
+        txs.push(admin.sign(&pause(target)));
+    }
+    rollup.send_transactions(&txs).await?;
+
+    let recipient = TestSigner::generate().address();
+    let tx = rollup.signers_mut()[1].sign(&transfer(&recipient));
+    rollup.send_transactions(&[tx]).await?;
+    wait_for_balance(&mut rollup, &recipient, gas_token_id, TRANSFER_AMOUNT).await?;
+    rollup.wait_for_slot().await?;
+
+    let events = ledger_events(&rollup).await?;
+    assert!(
+        events
+            .iter()
+            .all(|event| find_event(event, "Paused").is_none()),
+        "Unexpected pause: {events:?}"
+    );
+
+    rollup.shutdown().await;
+    Ok(())
+}
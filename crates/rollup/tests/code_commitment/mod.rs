@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+
+use sov_rollup_starter::code_commitment::{
+    check_chain_state_commitment, write_chain_state_commitment, ImageId,
+};
+
+const IMAGE_ID: ImageId = [1, 2, 3, 4, 5, 6, 7, 8];
+const OTHER_IMAGE_ID: ImageId = [8, 7, 6, 5, 4, 3, 2, 1];
+const PLACEHOLDER: ImageId = [0; 8];
+
+/// Copies the mock chain state genesis into `dir`, with a placeholder commitment.
+fn chain_state(dir: &Path) -> anyhow::Result<PathBuf> {
+    let path = dir.join("chain_state.json");
+    std::fs::copy("../../test-data/genesis/mock/chain_state.json", &path)?;
+    write_chain_state_commitment(&path, &PLACEHOLDER)?;
+    Ok(path)
+}
+
+fn read(path: &Path) -> anyhow::Result<serde_json::Value> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[test]
+fn written_commitment_is_checked() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let path = chain_state(temp_dir.path())?;
+    let before = read(&path)?;
+
+    write_chain_state_commitment(&path, &IMAGE_ID)?;
+    let after = read(&path)?;
+    assert_eq!(after["inner_code_commitment"], serde_json::json!(IMAGE_ID));
+    // The other keys are kept
+    for key in ["current_time", "outer_code_commitment", "genesis_da_height"] {
+        assert_eq!(after[key], before[key], "{key}");
+    }
+
+    check_chain_state_commitment(&path, &IMAGE_ID, false)?;
+    let error = check_chain_state_commitment(&path, &OTHER_IMAGE_ID, true).unwrap_err();
+    assert!(error.to_string().contains("does not match"), "{error:#}");
+    Ok(())
+}
+
+#[test]
+fn placeholder_commitment_is_refused_unless_allowed() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let path = chain_state(temp_dir.path())?;
+
+    let error = check_chain_state_commitment(&path, &IMAGE_ID, false).unwrap_err();
+    assert!(error.to_string().contains("placeholder"), "{error:#}");
+    check_chain_state_commitment(&path, &IMAGE_ID, true)?;
+    Ok(())
+}
+
+#[test]
+fn unbuilt_guest_is_not_checked() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let path = chain_state(temp_dir.path())?;
+
+    check_chain_state_commitment(&path, &PLACEHOLDER, false)?;
+    write_chain_state_commitment(&path, &IMAGE_ID)?;
+    check_chain_state_commitment(&path, &PLACEHOLDER, false)?;
+    Ok(())
+}
+
+#[test]
+fn invalid_chain_state_is_an_error() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let path = temp_dir.path().join("chain_state.json");
+
+    assert!(write_chain_state_commitment(&path, &IMAGE_ID).is_err());
+    std::fs::write(&path, "[]")?;
+    assert!(write_chain_state_commitment(&path, &IMAGE_ID).is_err());
+    std::fs::write(&path, "{}")?;
+    let error = check_chain_state_commitment(&path, &IMAGE_ID, true).unwrap_err();
+    assert!(error.to_string().contains("Missing"), "{error:#}");
+    Ok(())
+}
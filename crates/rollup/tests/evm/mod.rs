@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use ethers_core::types::transaction::eip2718::TypedTransaction;
+use ethers_core::types::{Address, TransactionRequest};
+use ethers_signers::{LocalWallet, Signer};
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::capabilities::RuntimeAuthenticator;
+use sov_modules_api::{Authenticator, Spec};
+use sov_rollup_starter::test_harness::{default_genesis_dir, TestSpec};
+use sov_sequencer_registry::SequencerStakeMeter;
+use stf_starter::authentication::{EvmAuth, ModAuth};
+use stf_starter::circuit_breaker::CallTarget;
+use stf_starter::{Runtime, RuntimeCall};
+
+/// The key of the development account funded by the mock EVM genesis.
+const DEV_PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+/// Returns a transfer from the development account, signed for `chain_id` and RLP-encoded like by a wallet.
+fn signed_transfer(chain_id: u64) -> anyhow::Result<Vec<u8>> {
+    let wallet = LocalWallet::from_str(DEV_PRIVATE_KEY)?.with_chain_id(chain_id);
+    let tx: TypedTransaction = TransactionRequest::new()
+        .from(wallet.address())
+        .to(Address::repeat_byte(0x11))
+        .value(1000)
+        .nonce(0)
+        .gas(21_000)
+        .gas_price(1_000_000_000u64)
+        .chain_id(chain_id)
+        .into();
+    let signature = wallet.sign_transaction_sync(&tx)?;
+    Ok(tx.rlp_signed(&signature).to_vec())
+}
+
+fn stake_meter() -> SequencerStakeMeter<<TestSpec as Spec>::Gas> {
+    SequencerStakeMeter::new(u64::MAX, Default::default())
+}
+
+#[test]
+fn rlp_signed_tx_is_authenticated_as_evm() -> anyhow::Result<()> {
+    let evm_genesis: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(
+        default_genesis_dir().join("evm.json"),
+    )?)?;
+    let chain_id = evm_genesis["chain_id"]
+        .as_u64()
+        .expect("The EVM chain ID is a number");
+    // Transactions signed for Ethereum mainnet must not be replayable on the rollup
+    assert_ne!(chain_id, 1);
+
+    let runtime = Runtime::<TestSpec, MockDaSpec>::default();
+    let rlp = signed_transfer(chain_id)?;
+    let raw_tx = EvmAuth::<TestSpec, MockDaSpec>::encode(rlp.clone())?;
+    let (_, call) = runtime
+        .authenticate(&raw_tx, &mut stake_meter())
+        .map_err(|e| anyhow::anyhow!("Failed to authenticate the EVM transaction: {e:?}"))?;
+    assert!(matches!(call, RuntimeCall::evm(_)));
+    assert_eq!(
+        CallTarget::of(&call),
+        CallTarget {
+            module: "evm",
+            call: None
+        }
+    );
+
+    // The envelope selects the authenticator: the same bytes are not a native transaction
+    let raw_tx = ModAuth::<TestSpec, MockDaSpec>::encode(rlp)?;
+    assert!(runtime.authenticate(&raw_tx, &mut stake_meter()).is_err());
+    Ok(())
+}
@@ -0,0 +1,548 @@
+use std::path::Path;
+
+use sov_bank::BankConfig;
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::{ApiStateAccessor, Spec};
+use sov_modules_rollup_blueprint::RollupBlueprint;
+use sov_rollup_interface::storage::HierarchicalStorageManager;
+use sov_rollup_starter::config::DaLayer;
+use sov_rollup_starter::genesis_export::export_rollup_genesis;
+use sov_rollup_starter::genesis_generator::generate_genesis;
+use sov_rollup_starter::genesis_layout::{convert_genesis, GenesisLayout};
+use sov_rollup_starter::genesis_root::{
+    check_genesis_root, compute_genesis_root, stored_genesis_root, GenesisRoot,
+};
+use sov_rollup_starter::mock_da::StarterMockDaConfig;
+use sov_rollup_starter::mock_rollup::MockRollup;
+use sov_rollup_starter::test_harness::{default_genesis_dir, TestRollup, TestSigner, TestSpec};
+use sov_sequencer_registry::SequencerConfig;
+use sov_stf_runner::{from_toml_path, RollupConfig};
+use stf_starter::genesis_config::{create_genesis_config, GenesisFile, GenesisPaths};
+use stf_starter::genesis_validation::validate_genesis;
+use stf_starter::{Runtime, RuntimeCall};
+
+const TRANSFER_AMOUNT: u64 = 1000;
+const TOKEN_SALT: u64 = 0;
+const TOKEN_NAME: &str = "exported-token";
+const TOKEN_BALANCE: u64 = 500;
+
+type Address = <TestSpec as Spec>::Address;
+
+/// Copies the mock genesis files to `dir`.
+fn copy_genesis(dir: &Path) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(default_genesis_dir())? {
+        let entry = entry?;
+        std::fs::copy(entry.path(), dir.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn mock_genesis_is_valid() {
+    let genesis_dir = default_genesis_dir();
+    let errors = validate_genesis::<TestSpec, MockDaSpec>(
+        &GenesisPaths::from_dir(&genesis_dir),
+        &genesis_dir.join("chain_state.json"),
+        Some(0),
+    );
+    assert!(errors.is_empty(), "Unexpected errors: {errors:?}");
+}
+
+#[test]
+fn genesis_errors_point_to_file_and_path() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    copy_genesis(temp_dir.path())?;
+
+    // Leave the sequencer without enough gas tokens for its bond
+    let bank_path = temp_dir.path().join("bank.json");
+    let mut bank: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&bank_path)?)?;
+    bank["gas_token_config"]["address_and_balances"][0][1] = 1.into();
+    std::fs::write(&bank_path, serde_json::to_string_pretty(&bank)?)?;
+
+    // Make the EVM config unreadable
+    let evm_path = temp_dir.path().join("evm.json");
+    let mut evm: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&evm_path)?)?;
+    evm["chain_id"] = "not a number".into();
+    std::fs::write(&evm_path, serde_json::to_string_pretty(&evm)?)?;
+
+    let chain_state_path = temp_dir.path().join("chain_state.json");
+    let errors = validate_genesis::<TestSpec, MockDaSpec>(
+        &GenesisPaths::from_dir(temp_dir.path()),
+        &chain_state_path,
+        Some(5),
+    );
+
+    let locations: Vec<_> = errors
+        .iter()
+        .map(|error| (error.file.clone(), error.path.as_str()))
+        .collect();
+    assert_eq!(
+        locations,
+        vec![
+            (evm_path, "$.chain_id"),
+            (
+                temp_dir.path().join("sequencer_registry.json"),
+                "$.seq_rollup_address"
+            ),
+            (
+                temp_dir.path().join("prover_incentives.json"),
+                "$.initial_provers[0][0]"
+            ),
+            (chain_state_path, "$.genesis_da_height"),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn bonds_of_an_address_are_summed() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    copy_genesis(temp_dir.path())?;
+    let genesis_paths = GenesisPaths::from_dir(temp_dir.path());
+    let sequencer: SequencerConfig<TestSpec, MockDaSpec> = serde_json::from_str(
+        &std::fs::read_to_string(&genesis_paths.sequencer_genesis_path)?,
+    )?;
+
+    // The sequencer is also the prover of the mock genesis: its balance covers each bond, but not both
+    let bank_path = &genesis_paths.bank_genesis_path;
+    let mut bank: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(bank_path)?)?;
+    assert_eq!(
+        bank["gas_token_config"]["address_and_balances"][0][0],
+        sequencer.seq_rollup_address.to_string()
+    );
+    bank["gas_token_config"]["address_and_balances"][0][1] = (sequencer.minimum_bond + 1).into();
+    std::fs::write(bank_path, serde_json::to_string_pretty(&bank)?)?;
+
+    let errors = validate_genesis::<TestSpec, MockDaSpec>(
+        &genesis_paths,
+        &temp_dir.path().join("chain_state.json"),
+        Some(0),
+    );
+    let locations: Vec<_> = errors
+        .iter()
+        .map(|error| (error.file.clone(), error.path.as_str()))
+        .collect();
+    assert_eq!(
+        locations,
+        vec![
+            (
+                genesis_paths.sequencer_genesis_path.clone(),
+                "$.seq_rollup_address"
+            ),
+            (
+                genesis_paths.prover_incentives_genesis_path.clone(),
+                "$.initial_provers[0][0]"
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn ibc_genesis_is_checked() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    copy_genesis(temp_dir.path())?;
+    let genesis_paths = GenesisPaths::from_dir(temp_dir.path());
+    let chain_state_path = temp_dir.path().join("chain_state.json");
+    let errors = || {
+        validate_genesis::<TestSpec, MockDaSpec>(&genesis_paths, &chain_state_path, Some(0))
+            .into_iter()
+            .map(|error| (error.file, error.path))
+            .collect::<Vec<_>>()
+    };
+
+    // A missing file is an empty config
+    std::fs::remove_file(&genesis_paths.ibc_genesis_path)?;
+    std::fs::remove_file(&genesis_paths.ibc_transfer_genesis_path)?;
+    assert_eq!(errors(), vec![]);
+    create_genesis_config::<TestSpec, MockDaSpec>(&genesis_paths)?;
+
+    // Unknown fields are rejected instead of dropped
+    std::fs::write(
+        &genesis_paths.ibc_genesis_path,
+        r#"{ "clients": [], "light_clients": [] }"#,
+    )?;
+    assert_eq!(errors().len(), 1);
+    assert_eq!(errors()[0].0, genesis_paths.ibc_genesis_path);
+    let error = create_genesis_config::<TestSpec, MockDaSpec>(&genesis_paths).unwrap_err();
+    assert!(format!("{error:?}").contains("light_clients"), "{error:?}");
+
+    // The identifiers are numbered in order, and the escrows are for known channels
+    let client = serde_json::json!({
+        "client_id": "07-tendermint-1",
+        "client_state": { "type_url": "/ibc.lightclients.tendermint.v1.ClientState", "value": "" },
+        "consensus_states": [],
+    });
+    std::fs::write(
+        &genesis_paths.ibc_genesis_path,
+        serde_json::to_string(&serde_json::json!({ "clients": [client] }))?,
+    )?;
+    let bank: BankConfig<TestSpec> =
+        serde_json::from_str(&std::fs::read_to_string(&genesis_paths.bank_genesis_path)?)?;
+    let escrow = serde_json::json!({
+        "channel_id": "channel-0",
+        "token_id": sov_bank::config_gas_token_id(),
+        "amount": 0,
+        "from": bank.gas_token_config.address_and_balances[0].0,
+    });
+    std::fs::write(
+        &genesis_paths.ibc_transfer_genesis_path,
+        serde_json::to_string(&serde_json::json!({ "escrows": [escrow] }))?,
+    )?;
+    assert_eq!(
+        errors(),
+        vec![
+            (
+                genesis_paths.ibc_genesis_path.clone(),
+                "$.clients[0].client_id".to_string()
+            ),
+            (
+                genesis_paths.ibc_transfer_genesis_path.clone(),
+                "$.escrows[0].channel_id".to_string()
+            ),
+            (
+                genesis_paths.ibc_transfer_genesis_path.clone(),
+                "$.escrows[0].amount".to_string()
+            ),
+        ]
+    );
+    assert!(create_genesis_config::<TestSpec, MockDaSpec>(&genesis_paths).is_err());
+    Ok(())
+}
+
+#[test]
+fn generated_genesis_is_valid() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    generate_genesis::<TestSpec>(
+        &default_genesis_dir().join("../manifest.toml"),
+        temp_dir.path(),
+        DaLayer::Mock,
+    )?;
+
+    let errors = validate_genesis::<TestSpec, MockDaSpec>(
+        &GenesisPaths::from_dir(temp_dir.path()),
+        &temp_dir.path().join("chain_state.json"),
+        Some(0),
+    );
+    assert!(errors.is_empty(), "Unexpected errors: {errors:?}");
+
+    // The generated accounts are the ones of the mock genesis, which uses the same keys
+    let read = |dir: &Path, file: &str| -> anyhow::Result<serde_json::Value> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(
+            dir.join(file),
+        )?)?)
+    };
+    assert_eq!(
+        read(temp_dir.path(), "accounts.json")?,
+        read(&default_genesis_dir(), "accounts.json")?
+    );
+
+    // The development account of the mock EVM genesis is not funded
+    let evm = read(temp_dir.path(), "evm.json")?;
+    assert_eq!(evm["data"], serde_json::json!([]));
+    assert_eq!(
+        evm["chain_id"],
+        read(&default_genesis_dir(), "evm.json")?["chain_id"]
+    );
+    Ok(())
+}
+
+#[test]
+fn single_file_genesis_round_trip() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let json_path = temp_dir.path().join("genesis.json");
+    let toml_path = temp_dir.path().join("genesis.toml");
+    let unpacked_dir = temp_dir.path().join("unpacked");
+
+    convert_genesis(default_genesis_dir(), &json_path)?;
+    GenesisFile::read(&json_path)?.write(&toml_path)?;
+    convert_genesis(&toml_path, &unpacked_dir)?;
+    assert_eq!(
+        GenesisFile::read(&toml_path)?,
+        GenesisFile::from_dir(default_genesis_dir())?
+    );
+    assert_eq!(
+        GenesisFile::from_dir(&unpacked_dir)?,
+        GenesisFile::from_dir(default_genesis_dir())?
+    );
+
+    // Errors point to the section of the single file
+    let mut genesis: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&json_path)?)?;
+    genesis["chain_state"]["genesis_da_height"] = 5.into();
+    std::fs::write(&json_path, serde_json::to_string_pretty(&genesis)?)?;
+
+    let layout = GenesisLayout::open(&json_path)?;
+    let errors = validate_genesis::<TestSpec, MockDaSpec>(
+        &layout.genesis_paths(),
+        &layout.chain_state_path(None)?,
+        Some(0),
+    );
+    let locations: Vec<_> = errors
+        .into_iter()
+        .map(|error| {
+            let error = layout.locate(error);
+            (error.file, error.path)
+        })
+        .collect();
+    assert_eq!(
+        locations,
+        vec![(json_path, "$.chain_state.genesis_da_height".to_string())]
+    );
+    assert!(layout.chain_state_path(Some("chain_state.json")).is_err());
+    Ok(())
+}
+
+fn genesis_root_of(genesis_dir: &Path) -> anyhow::Result<GenesisRoot> {
+    let rollup_config: RollupConfig<StarterMockDaConfig> =
+        from_toml_path(Path::new(env!("CARGO_MANIFEST_DIR")).join("../../rollup_config.toml"))?;
+    compute_genesis_root(
+        &MockRollup::default(),
+        &rollup_config,
+        &GenesisPaths::from_dir(genesis_dir),
+        &genesis_dir.join("chain_state.json"),
+    )
+}
+
+#[test]
+fn genesis_root_depends_only_on_the_genesis_contents() -> anyhow::Result<()> {
+    let default_root = genesis_root_of(&default_genesis_dir())?;
+
+    // Converting the layout back and forth keeps the same contents
+    let temp_dir = tempfile::tempdir()?;
+    let toml_path = temp_dir.path().join("genesis.toml");
+    let unpacked_dir = temp_dir.path().join("unpacked");
+    convert_genesis(default_genesis_dir(), &toml_path)?;
+    convert_genesis(&toml_path, &unpacked_dir)?;
+    assert_eq!(genesis_root_of(&unpacked_dir)?, default_root);
+
+    let bank_path = unpacked_dir.join("bank.json");
+    let mut bank: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&bank_path)?)?;
+    bank["gas_token_config"]["address_and_balances"][0][1] = 1_000_000_000u64.into();
+    std::fs::write(&bank_path, serde_json::to_string_pretty(&bank)?)?;
+    let changed_root = genesis_root_of(&unpacked_dir)?;
+    assert_ne!(changed_root.state_root, default_root.state_root);
+    assert_ne!(changed_root.inputs_hash, default_root.inputs_hash);
+    Ok(())
+}
+
+#[test]
+fn genesis_root_mismatch_is_rejected() -> anyhow::Result<()> {
+    let genesis_root = genesis_root_of(&default_genesis_dir())?;
+    let other_root = GenesisRoot {
+        state_root: format!("0x{}", "00".repeat(32)),
+        inputs_hash: genesis_root.inputs_hash.clone(),
+    };
+
+    check_genesis_root(&genesis_root, None, None)?;
+    check_genesis_root(&genesis_root, Some(&genesis_root.state_root), None)?;
+    assert!(check_genesis_root(&genesis_root, Some(&other_root.state_root), None).is_err());
+
+    // A storage can't be reused with another genesis
+    let stored_root = genesis_root.state_root.to_uppercase().replacen("0X", "", 1);
+    check_genesis_root(&genesis_root, None, Some(&stored_root))?;
+    assert!(check_genesis_root(&genesis_root, None, Some(&other_root.state_root)).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn stored_genesis_root_is_the_committed_genesis() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder().start().await?;
+    // The genesis is committed before the first slot
+    rollup.wait_for_slot().await?;
+    rollup.stop().await;
+
+    let genesis_dir = rollup.genesis_dir();
+    let genesis_root = compute_genesis_root(
+        &MockRollup::default(),
+        rollup.rollup_config(),
+        &GenesisPaths::from_dir(genesis_dir),
+        &genesis_dir.join("chain_state.json"),
+    )?;
+    let stored_root = stored_genesis_root(&MockRollup::default(), rollup.rollup_config())?;
+    assert_eq!(stored_root, Some(genesis_root.state_root));
+
+    let mut new_config = rollup.rollup_config().clone();
+    let new_storage = tempfile::tempdir()?;
+    new_config.storage.path = new_storage.path().join("storage");
+    assert_eq!(
+        stored_genesis_root(&MockRollup::default(), &new_config)?,
+        None
+    );
+    Ok(())
+}
+
+/// Exports the state of `rollup` at `height` to `output_dir`.
+fn export(rollup: &TestRollup, height: u64, output_dir: &Path) -> anyhow::Result<()> {
+    export_rollup_genesis(
+        &MockRollup::default(),
+        rollup.rollup_config(),
+        height,
+        &GenesisPaths::from_dir(rollup.genesis_dir()),
+        &rollup.genesis_dir().join("chain_state.json"),
+        output_dir,
+    )
+}
+
+/// The gas token balance, prover bond and sequencer stake of each of `addresses`, read from the storage of the
+/// stopped `rollup` at `height`.
+fn holdings(
+    rollup: &TestRollup,
+    height: u64,
+    addresses: &[Address],
+) -> anyhow::Result<Vec<(u64, u64, u64)>> {
+    let sequencer: SequencerConfig<TestSpec, MockDaSpec> = serde_json::from_str(
+        &std::fs::read_to_string(rollup.genesis_dir().join("sequencer_registry.json"))?,
+    )?;
+    let storage_manager = MockRollup::default().create_storage_manager(rollup.rollup_config())?;
+    let storage = storage_manager.create_finalized_storage()?;
+    let mut state = ApiStateAccessor::<TestSpec>::new(&storage);
+    state.set_archival_version(height);
+
+    let runtime = Runtime::<TestSpec, MockDaSpec>::default();
+    let gas_token_id = sov_bank::config_gas_token_id();
+    Ok(addresses
+        .iter()
+        .map(|address| {
+            let balance = runtime
+                .bank
+                .get_balance_of(address, gas_token_id, &mut state)
+                .unwrap_or_default();
+            let bond = runtime
+                .prover_incentives
+                .get_bond_amount(address, &mut state);
+            let stake = if *address == sequencer.seq_rollup_address {
+                runtime
+                    .sequencer_registry
+                    .get_sender_balance(&sequencer.seq_da_address, &mut state)
+                    .unwrap_or_default()
+            } else {
+                0
+            };
+            (balance, bond, stake)
+        })
+        .collect())
+}
+
+#[tokio::test]
+async fn export_genesis_round_trip_test() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(1, 1_000_000)
+        .start()
+        .await?;
+    let sender = rollup.signers()[0].address();
+    let recipient = TestSigner::generate().address();
+    // The holders of the token are only seen in its creation and mint events
+    let token_holder = TestSigner::generate().address();
+    let mint_recipient = TestSigner::generate().address();
+    let calls = [
+        sov_bank::CallMessage::Transfer {
+            to: recipient.clone(),
+            coins: sov_bank::Coins {
+                amount: TRANSFER_AMOUNT,
+                token_id: sov_bank::config_gas_token_id(),
+            },
+        },
+        sov_bank::CallMessage::CreateToken {
+            salt: TOKEN_SALT,
+            token_name: TOKEN_NAME.to_string(),
+            initial_balance: TOKEN_BALANCE,
+            minter_address: token_holder.clone(),
+            authorized_minters: vec![sender.clone()],
+        },
+        sov_bank::CallMessage::Mint {
+            coins: sov_bank::Coins {
+                amount: TOKEN_BALANCE,
+                token_id: sov_bank::get_token_id::<TestSpec>(TOKEN_NAME, &sender, TOKEN_SALT),
+            },
+            mint_to_address: mint_recipient.clone(),
+        },
+    ];
+    let txs: Vec<_> = calls
+        .into_iter()
+        .map(|call| rollup.signers_mut()[0].sign(&RuntimeCall::<TestSpec, MockDaSpec>::bank(call)))
+        .collect();
+    rollup.send_transactions(&txs).await?;
+
+    // The transactions are applied in order, so the mint is the last one
+    let mut height = rollup.wait_for_slot().await?;
+    for _ in 0..10 {
+        let balance = sov_bank::BankRpcClient::<TestSpec>::balance_of(
+            rollup.http(),
+            None,
+            mint_recipient.clone(),
+            sov_bank::get_token_id::<TestSpec>(TOKEN_NAME, &sender, TOKEN_SALT),
+        )
+        .await?;
+        if balance.amount == Some(TOKEN_BALANCE) {
+            break;
+        }
+        height = rollup.wait_for_slot().await?;
+    }
+    rollup.stop().await;
+
+    let export_dir = tempfile::tempdir()?;
+    let first_export = export_dir.path().join("first");
+    export(&rollup, height, &first_export)?;
+
+    let bank: BankConfig<TestSpec> =
+        serde_json::from_str(&std::fs::read_to_string(first_export.join("bank.json"))?)?;
+    let gas_holders: Vec<Address> = bank
+        .gas_token_config
+        .address_and_balances
+        .iter()
+        .map(|(address, _)| address.clone())
+        .collect();
+    assert!(bank
+        .gas_token_config
+        .address_and_balances
+        .contains(&(recipient.clone(), TRANSFER_AMOUNT)));
+    let token = bank
+        .tokens
+        .iter()
+        .find(|token| token.token_name == TOKEN_NAME)
+        .expect("The created token is exported");
+    let mut token_holders = vec![
+        (token_holder, TOKEN_BALANCE),
+        (mint_recipient, TOKEN_BALANCE),
+    ];
+    token_holders.sort_by_key(|(address, _)| address.to_string());
+    assert_eq!(token.address_and_balances, token_holders);
+
+    // A rollup started from the exported genesis has the state of the exported height
+    let mut reimported = TestRollup::builder()
+        .genesis_dir(&first_export)
+        .start()
+        .await?;
+    let reimported_height = reimported.wait_for_slot().await?;
+    reimported.stop().await;
+    let before = holdings(&rollup, height, &gas_holders)?;
+    let after = holdings(&reimported, reimported_height, &gas_holders)?;
+    for ((address, before), after) in gas_holders.iter().zip(before).zip(after) {
+        let (balance, bond, stake) = before;
+        // The sequencer stake is re-bonded at the minimum bond, and the rest returned to its balance
+        assert_eq!(
+            balance + bond + stake,
+            after.0 + after.1 + after.2,
+            "{address}"
+        );
+        assert_eq!(bond, after.1, "{address}");
+    }
+
+    // Exporting the re-imported rollup is a no-op
+    let second_export = export_dir.path().join("second");
+    export(&reimported, reimported_height, &second_export)?;
+    for file in [
+        "accounts.json",
+        "bank.json",
+        "sequencer_registry.json",
+        "prover_incentives.json",
+    ] {
+        assert_eq!(
+            std::fs::read_to_string(first_export.join(file))?,
+            std::fs::read_to_string(second_export.join(file))?,
+            "{file} changed after re-import"
+        );
+    }
+    Ok(())
+}
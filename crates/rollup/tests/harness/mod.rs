@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use sov_mock_da::MockDaSpec;
+use sov_rollup_starter::test_harness::{TestRollup, TestSpec};
+use stf_starter::RuntimeCall;
+
+const TOKEN_SALT: u64 = 0;
+const TOKEN_NAME: &str = "harness-token";
+const INITIAL_BALANCE: u64 = 1000;
+
+#[tokio::test]
+async fn harness_create_token_test() -> Result<(), anyhow::Error> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(1, 1_000_000)
+        .start()
+        .await?;
+    let user_address = rollup.signers()[0].address();
+
+    let call =
+        RuntimeCall::<TestSpec, MockDaSpec>::bank(sov_bank::CallMessage::<TestSpec>::CreateToken {
+            salt: TOKEN_SALT,
+            token_name: TOKEN_NAME.to_string(),
+            initial_balance: INITIAL_BALANCE,
+            minter_address: user_address.clone(),
+            authorized_minters: vec![],
+        });
+    let tx = rollup.signers_mut()[0].sign(&call);
+    rollup.send_transactions(&[tx]).await?;
+    rollup.wait_for_slot().await?;
+
+    let token_id = sov_bank::get_token_id::<TestSpec>(TOKEN_NAME, &user_address, TOKEN_SALT);
+    let balance_response = sov_bank::BankRpcClient::<TestSpec>::balance_of(
+        rollup.http(),
+        None,
+        user_address,
+        token_id,
+    )
+    .await?;
+    assert_eq!(INITIAL_BALANCE, balance_response.amount.unwrap_or_default());
+    assert_eq!(1, rollup.signers()[0].nonce());
+
+    rollup.shutdown().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn harness_stops_once() -> Result<(), anyhow::Error> {
+    let mut rollup = TestRollup::builder().start().await?;
+    rollup.wait_for_slot().await?;
+
+    assert!(rollup.stop_gracefully(Duration::from_secs(60)).await?);
+    assert!(rollup
+        .stop_gracefully(Duration::from_secs(60))
+        .await
+        .is_err());
+    // The rollup was already stopped, so there is nothing left to stop
+    rollup.stop().await;
+    rollup.shutdown().await;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use sov_rollup_starter::health::ReadinessReport;
+use sov_rollup_starter::test_harness::TestRollup;
+
+async fn readyz(rollup: &TestRollup) -> anyhow::Result<(StatusCode, ReadinessReport)> {
+    let response = reqwest::get(format!("http://{}/readyz", rollup.rest_addr())).await?;
+    Ok((response.status(), response.json().await?))
+}
+
+#[tokio::test]
+async fn health_and_readiness_test() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder().start().await?;
+
+    let response = reqwest::get(format!("http://{}/healthz", rollup.rest_addr())).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (status, report) = readyz(&rollup).await?;
+    assert_eq!(status, StatusCode::OK, "{report:?}");
+    assert!(report.ready);
+    for name in ["shutdown", "sync", "da", "storage"] {
+        assert!(report.checks[name].ok, "{name}: {report:?}");
+    }
+
+    // Concurrent probes share the checks, and leave no probe file behind
+    let reports = tokio::try_join!(readyz(&rollup), readyz(&rollup), readyz(&rollup))?;
+    for (status, report) in [reports.0, reports.1, reports.2] {
+        assert_eq!(status, StatusCode::OK, "{report:?}");
+    }
+    let storage_path = &rollup.rollup_config().storage.path;
+    for entry in std::fs::read_dir(storage_path)? {
+        let name = entry?.file_name();
+        assert!(
+            !name.to_string_lossy().starts_with(".readyz_probe"),
+            "{name:?} was left in {}",
+            storage_path.display()
+        );
+    }
+
+    // The servers of the test rollup keep running after it stops, but it isn't ready anymore
+    rollup.stop_gracefully(Duration::from_secs(60)).await?;
+    let (status, report) = readyz(&rollup).await?;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!report.ready);
+    assert!(!report.checks["shutdown"].ok);
+    assert_eq!(
+        report.checks["shutdown"].message,
+        "The node is shutting down"
+    );
+
+    rollup.shutdown().await;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use sov_mock_da::MockDaSpec;
+use sov_rollup_starter::config::{DaLayer, StarterConfig};
+use sov_rollup_starter::home::{reset_data, NodeLocation, RollupHome};
+use sov_rollup_starter::test_harness::TestSpec;
+use stf_starter::genesis_config::GenesisPaths;
+use stf_starter::genesis_validation::validate_genesis;
+
+#[test]
+fn init_writes_a_valid_config_and_genesis() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let home = RollupHome::new(temp_dir.path().join("home"));
+    home.init(DaLayer::Mock, false)?;
+
+    let config = StarterConfig::from_toml_path(home.config_path())?;
+    assert_eq!(config.da_layer, Some(DaLayer::Mock));
+    let location = NodeLocation::read(&home.config_path())?;
+    assert_eq!(location.storage_path, home.data_dir());
+    assert!(location.storage_path.is_absolute());
+    assert!(home.keys_dir().is_dir());
+
+    let genesis_dir = home.genesis_dir();
+    let errors = validate_genesis::<TestSpec, MockDaSpec>(
+        &GenesisPaths::from_dir(&genesis_dir),
+        &genesis_dir.join("chain_state.json"),
+        config.runner.genesis_height,
+    );
+    assert!(errors.is_empty(), "Unexpected errors: {errors:?}");
+
+    // An initialized home is only overwritten with `force`
+    assert!(home.init(DaLayer::Mock, false).is_err());
+    home.init(DaLayer::Celestia, true)?;
+    let config = StarterConfig::from_toml_path(home.config_path())?;
+    assert_eq!(config.da_layer, Some(DaLayer::Celestia));
+    // The Celestia auth token doesn't have to be set to find the node
+    let location = NodeLocation::read(&home.config_path())?;
+    assert_eq!(location.storage_path, home.data_dir());
+    Ok(())
+}
+
+#[test]
+fn reset_keeps_config_genesis_and_keys() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let home = RollupHome::new(temp_dir.path());
+    home.init(DaLayer::Mock, false)?;
+    let location = NodeLocation::read(&home.config_path())?;
+    assert_eq!(reset_data(&home, &location, true)?, None);
+
+    std::fs::create_dir_all(home.data_dir().join("ledger"))?;
+    std::fs::write(home.keys_dir().join("operator.json"), "{}")?;
+
+    // Without confirmation, nothing is removed
+    let storage = reset_data(&home, &location, false)?;
+    assert_eq!(storage, Some(std::fs::canonicalize(home.data_dir())?));
+    assert!(home.data_dir().exists());
+
+    reset_data(&home, &location, true)?;
+    assert!(!home.data_dir().exists());
+    assert!(home.config_path().is_file());
+    assert!(home.genesis_dir().join("chain_state.json").is_file());
+    assert!(home.keys_dir().join("operator.json").is_file());
+    Ok(())
+}
+
+#[test]
+fn reset_refuses_a_storage_holding_the_home() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let home = RollupHome::new(temp_dir.path());
+    home.init(DaLayer::Mock, false)?;
+    let mut location = NodeLocation::read(&home.config_path())?;
+    location.storage_path = temp_dir.path().to_path_buf();
+
+    assert!(reset_data(&home, &location, true).is_err());
+    assert!(home.config_path().is_file());
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::rpc_params;
+use sov_mock_da::MockDaSpec;
+use sov_rollup_starter::metrics::{failure_reason_name, parse_bind_address};
+use sov_rollup_starter::test_harness::{TestRollup, TestSigner, TestSpec};
+use stf_starter::RuntimeCall;
+
+/// Returns the sum of the samples of the metric `name` whose labels include `labels`.
+fn metric_value(name: &str, labels: &[(&str, &str)]) -> f64 {
+    prometheus::gather()
+        .iter()
+        .filter(|family| family.get_name() == name)
+        .flat_map(|family| family.get_metric())
+        .filter(|metric| {
+            labels.iter().all(|(name, value)| {
+                metric
+                    .get_label()
+                    .iter()
+                    .any(|label| label.get_name() == *name && label.get_value() == *value)
+            })
+        })
+        .map(|metric| {
+            if metric.has_histogram() {
+                metric.get_histogram().get_sample_sum()
+            } else if metric.has_gauge() {
+                metric.get_gauge().get_value()
+            } else {
+                metric.get_counter().get_value()
+            }
+        })
+        .sum()
+}
+
+#[test]
+fn metrics_bind_address_is_a_port_or_an_address() -> anyhow::Result<()> {
+    assert_eq!(
+        parse_bind_address("9845")?,
+        "127.0.0.1:9845".parse::<SocketAddr>()?
+    );
+    assert_eq!(
+        parse_bind_address("0.0.0.0:9000")?,
+        "0.0.0.0:9000".parse::<SocketAddr>()?
+    );
+    assert!(parse_bind_address("localhost").is_err());
+    Ok(())
+}
+
+#[test]
+fn tx_failure_reasons_have_fixed_names() {
+    assert_eq!(
+        failure_reason_name(Some(
+            "Insufficient balance from=sov1abc, got=10, needed=20, token=sov1def"
+        )),
+        "insufficient_funds"
+    );
+    assert_eq!(
+        failure_reason_name(Some("Transaction ran out of gas: 1000 used")),
+        "out_of_gas"
+    );
+    assert_eq!(
+        failure_reason_name(Some("Unexpected failure 0xdeadbeef")),
+        "other"
+    );
+    assert_eq!(failure_reason_name(None), "unknown");
+}
+
+#[tokio::test]
+async fn node_metrics_test() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(1, 1_000_000)
+        .start()
+        .await?;
+    let call = RuntimeCall::<TestSpec, MockDaSpec>::bank(sov_bank::CallMessage::Transfer {
+        to: TestSigner::generate().address(),
+        coins: sov_bank::Coins {
+            amount: 100,
+            token_id: sov_bank::config_gas_token_id(),
+        },
+    });
+    let tx = rollup.signers_mut()[0].sign(&call);
+    rollup.send_transactions(&[tx]).await?;
+    let _: serde_json::Value = rollup
+        .http()
+        .request("ledger_getHead", rpc_params![])
+        .await?;
+
+    // The metrics are collected in the background after the slots are stored
+    for _ in 0..10 {
+        if metric_value("rollup_txs_per_slot", &[]) >= 1.0 {
+            break;
+        }
+        rollup.wait_for_slot().await?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    assert!(metric_value("rollup_slots_processed_total", &[]) >= 1.0);
+    assert!(metric_value("rollup_height", &[]) >= 1.0);
+    assert!(metric_value("rollup_txs_per_slot", &[]) >= 1.0);
+    assert!(metric_value("rollup_gas_used_per_slot", &[]) > 0.0);
+    assert!(metric_value("rollup_da_head_height", &[]) >= 1.0);
+    assert!(
+        metric_value(
+            "rollup_rpc_requests_total",
+            &[("method", "ledger_getHead"), ("outcome", "success")]
+        ) >= 1.0
+    );
+    rollup.shutdown().await;
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use std::path::Path;
+use std::time::Duration;
+
+use sov_mock_da::{MockAddress, MockDaConfig};
+use sov_rollup_interface::da::BlockHeaderTrait;
+use sov_rollup_interface::services::da::DaService;
+use sov_rollup_starter::mock_da::faults::{FaultInjector, FaultSchedule};
+use sov_rollup_starter::mock_da::{StarterMockDaConfig, StarterMockDaService};
+use sov_stf_runner::RollupProverConfig;
+
+const FINALIZATION_BLOCKS: u32 = 3;
+
+fn da_config(faults: Option<FaultSchedule>) -> StarterMockDaConfig {
+    StarterMockDaConfig {
+        mock: MockDaConfig {
+            sender_address: MockAddress::new([0; 32]),
+            finalization_blocks: FINALIZATION_BLOCKS,
+            wait_attempts: 10,
+        },
+        persistent: false,
+        faults,
+    }
+}
+
+/// Returns the injector of a new mock DA service, configured like by the rollup.
+async fn injector(
+    storage_path: &Path,
+    faults: Option<FaultSchedule>,
+) -> anyhow::Result<FaultInjector> {
+    let injector = FaultInjector::default();
+    StarterMockDaService::new(&da_config(faults), storage_path, Some(injector.clone())).await?;
+    Ok(injector)
+}
+
+fn schedule(seed: u64) -> FaultSchedule {
+    FaultSchedule {
+        seed,
+        delay_probability: 0.5,
+        max_delay_ms: 1000,
+        drop_probability: 0.2,
+        reorder_probability: 0.5,
+        duplicate_probability: 0.2,
+        reorg_probability: 0.2,
+    }
+}
+
+#[tokio::test]
+async fn delays_apply_once_to_the_next_blocks() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let injector = injector(temp_dir.path(), None).await?;
+    let delay = Duration::from_millis(10);
+    injector.delay_next_blocks(2, delay);
+
+    assert_eq!(injector.delay(1), Some(delay));
+    // A retry reads the block without delay
+    assert_eq!(injector.delay(1), None);
+    assert_eq!(injector.delay(2), Some(delay));
+    assert_eq!(injector.delay(3), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn blob_faults_apply_to_the_next_block_with_blobs() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let injector = injector(temp_dir.path(), None).await?;
+    injector.reorder_next_blobs();
+    injector.duplicate_next_blob();
+    injector.drop_next_blob();
+
+    // A block without blobs doesn't take a fault
+    let mut blobs: Vec<u32> = vec![];
+    injector.apply_blob_faults(1, &mut blobs);
+    assert!(blobs.is_empty());
+
+    let mut blobs = vec![1, 2, 3];
+    injector.apply_blob_faults(2, &mut blobs);
+    assert_eq!(blobs, [3, 2, 1]);
+    // The fault is pinned to the block, so every reader sees it
+    let mut blobs = vec![1, 2, 3];
+    injector.apply_blob_faults(2, &mut blobs);
+    assert_eq!(blobs, [3, 2, 1]);
+
+    let mut blobs = vec![1, 2];
+    injector.apply_blob_faults(3, &mut blobs);
+    assert_eq!(blobs, [1, 1, 2]);
+
+    let mut blobs = vec![1, 2];
+    injector.apply_blob_faults(4, &mut blobs);
+    assert_eq!(blobs, [2]);
+
+    let mut blobs = vec![1, 2];
+    injector.apply_blob_faults(5, &mut blobs);
+    assert_eq!(blobs, [1, 2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn reorgs_are_limited_to_unfinalized_blocks() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let injector = injector(temp_dir.path(), None).await?;
+    assert!(injector.reorg(0).is_err());
+    assert!(injector.reorg(FINALIZATION_BLOCKS + 1).is_err());
+
+    injector.reorg(FINALIZATION_BLOCKS)?;
+    assert_eq!(injector.reorg_depth(10), Some(FINALIZATION_BLOCKS));
+    assert_eq!(injector.reorg_depth(11), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn schedule_is_determined_by_the_seed() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let draw = |injector: &FaultInjector| {
+        (1..100u64)
+            .map(|height| {
+                let mut blobs: Vec<u32> = (0..8).collect();
+                injector.apply_blob_faults(height, &mut blobs);
+                (injector.delay(height), blobs, injector.reorg_depth(height))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let first = draw(&injector(temp_dir.path(), Some(schedule(7))).await?);
+    let second = draw(&injector(temp_dir.path(), Some(schedule(7))).await?);
+    let other_seed = draw(&injector(temp_dir.path(), Some(schedule(8))).await?);
+    assert_eq!(first, second);
+    assert_ne!(first, other_seed);
+
+    // Every kind of fault is drawn
+    assert!(first.iter().any(|(delay, _, _)| delay.is_some()));
+    assert!(first.iter().any(|(_, blobs, _)| blobs.len() < 8));
+    assert!(first.iter().any(|(_, blobs, _)| blobs.len() > 8));
+    assert!(first
+        .iter()
+        .any(|(_, blobs, _)| blobs.windows(2).any(|pair| pair[0] > pair[1])));
+    assert!(first.iter().any(|(_, _, depth)| depth.is_some()));
+    assert!(first
+        .iter()
+        .all(|(_, _, depth)| depth.map_or(true, |depth| depth <= FINALIZATION_BLOCKS)));
+    Ok(())
+}
+
+#[test]
+fn faults_cannot_be_persistent() {
+    let mut config = da_config(Some(schedule(0)));
+    assert!(config.validate().is_ok());
+    config.persistent = true;
+    assert!(config.validate().is_err());
+
+    let mut config = da_config(Some(schedule(0)));
+    config.faults.as_mut().unwrap().drop_probability = 1.5;
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn blob_faults_require_skipping_proofs() {
+    let config = da_config(Some(schedule(0)));
+    assert!(config.check_prover_config(None, false).is_ok());
+    assert!(config
+        .check_prover_config(Some(&RollupProverConfig::Skip), false)
+        .is_ok());
+    assert!(config
+        .check_prover_config(Some(&RollupProverConfig::Execute), false)
+        .is_err());
+
+    // Delays and reorgs leave the blobs of a block untouched
+    let mut config = da_config(Some(FaultSchedule {
+        drop_probability: 0.0,
+        reorder_probability: 0.0,
+        duplicate_probability: 0.0,
+        ..schedule(0)
+    }));
+    assert!(config
+        .check_prover_config(Some(&RollupProverConfig::Prove), false)
+        .is_ok());
+    // The faults commanded through an injector may drop blobs
+    assert!(config
+        .check_prover_config(Some(&RollupProverConfig::Prove), true)
+        .is_err());
+    config.faults = None;
+    assert!(config
+        .check_prover_config(Some(&RollupProverConfig::Simulate), false)
+        .is_ok());
+}
+
+#[tokio::test]
+async fn persistent_blobs_are_replayed() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let mut config = da_config(None);
+    config.persistent = true;
+
+    let head = {
+        let da_service = StarterMockDaService::new(&config, temp_dir.path(), None).await?;
+        for blob in [[1, 2, 3], [4, 5, 6]] {
+            let fee = da_service.estimate_fee(blob.len()).await?;
+            da_service.send_transaction(&blob, fee).await?;
+        }
+        da_service.get_head_block_header().await?
+    };
+
+    let da_service = StarterMockDaService::new(&config, temp_dir.path(), None).await?;
+    let replayed_head = da_service.get_head_block_header().await?;
+    assert_eq!(replayed_head.height(), head.height());
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use sov_mock_da::MockDaSpec;
+use sov_modules_stf_blueprint::SlashingReason;
+use sov_rollup_starter::test_harness::{TestRollup, TestSigner, TestSpec};
+use sov_sequencer_registry::SequencerConfig;
+use stf_starter::sequencer_outcomes::slashing_reason_name;
+use stf_starter::RuntimeCall;
+
+use super::test_helpers::{find_event, ledger_events};
+
+const MAX_SLOTS: usize = 10;
+
+/// Returns the value of the counter `name` of `sequencer`.
+fn counter_value(name: &str, sequencer: &str) -> f64 {
+    prometheus::gather()
+        .iter()
+        .filter(|family| family.get_name() == name)
+        .flat_map(|family| family.get_metric())
+        .filter(|metric| {
+            metric
+                .get_label()
+                .iter()
+                .any(|label| label.get_name() == "sequencer" && label.get_value() == sequencer)
+        })
+        .map(|metric| metric.get_counter().get_value())
+        .sum()
+}
+
+#[test]
+fn slashing_reasons_have_fixed_names() {
+    let names: HashSet<_> = [
+        SlashingReason::InvalidBatchEncoding,
+        SlashingReason::StatelessVerificationFailed,
+        SlashingReason::InvalidTransactionEncoding,
+    ]
+    .iter()
+    .map(slashing_reason_name)
+    .collect();
+    assert_eq!(names.len(), 3);
+    assert!(names
+        .iter()
+        .all(|name| name.chars().all(|c| c.is_ascii_lowercase() || c == '_')));
+}
+
+#[tokio::test]
+async fn rewards_are_reported_as_events_and_counters() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(1, 1_000_000)
+        .start()
+        .await?;
+    let sequencer: SequencerConfig<TestSpec, MockDaSpec> = serde_json::from_str(
+        &std::fs::read_to_string(rollup.genesis_dir().join("sequencer_registry.json"))?,
+    )?;
+    let sequencer = sequencer.seq_da_address.to_string();
+
+    let call = RuntimeCall::<TestSpec, MockDaSpec>::bank(sov_bank::CallMessage::Transfer {
+        to: TestSigner::generate().address(),
+        coins: sov_bank::Coins {
+            amount: 100,
+            token_id: sov_bank::config_gas_token_id(),
+        },
+    });
+    let tx = rollup.signers_mut()[0].sign(&call);
+    rollup.send_transactions(&[tx]).await?;
+
+    let mut rewards = Vec::new();
+    for _ in 0..MAX_SLOTS {
+        rewards = ledger_events(&rollup)
+            .await?
+            .iter()
+            .filter_map(|event| find_event(event, "SequencerRewarded").cloned())
+            .collect();
+        if !rewards.is_empty() {
+            break;
+        }
+        rollup.wait_for_slot().await?;
+    }
+    assert!(!rewards.is_empty(), "No reward within {MAX_SLOTS} slots");
+
+    let rewarded: u64 = rewards
+        .iter()
+        .map(|reward| {
+            assert_eq!(reward["sequencer"], sequencer.as_str());
+            reward["amount"].as_u64().expect("The reward has no amount")
+        })
+        .sum();
+    assert!(rewarded > 0);
+    // The counters are collected from the ledger in the background. Other tests reward the same sequencer in
+    // this process, so the counter is at least the rewards of this rollup
+    for _ in 0..MAX_SLOTS {
+        if counter_value("sequencer_rewarded_amount_total", &sequencer) >= rewarded as f64 {
+            break;
+        }
+        rollup.wait_for_slot().await?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(counter_value("sequencer_rewarded_amount_total", &sequencer) >= rewarded as f64);
+
+    rollup.shutdown().await;
+    Ok(())
+}
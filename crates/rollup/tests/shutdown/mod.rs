@@ -0,0 +1,161 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::rpc_params;
+use sov_mock_da::{MockAddress, MockDaConfig, MockDaSpec};
+use sov_rollup_interface::da::BlockHeaderTrait;
+use sov_rollup_interface::services::da::DaService;
+use sov_rollup_starter::mock_da::StarterMockDaService;
+use sov_rollup_starter::shutdown::{GracefulDaService, Shutdown};
+use sov_rollup_starter::test_harness::{TestRollup, TestSigner, TestSpec};
+use stf_starter::RuntimeCall;
+
+fn transfer() -> RuntimeCall<TestSpec, MockDaSpec> {
+    RuntimeCall::<TestSpec, MockDaSpec>::bank(sov_bank::CallMessage::Transfer {
+        to: TestSigner::generate().address(),
+        coins: sov_bank::Coins {
+            amount: 100,
+            token_id: sov_bank::config_gas_token_id(),
+        },
+    })
+}
+
+#[tokio::test]
+async fn da_service_stops_handing_out_blocks_on_shutdown() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let shutdown = Shutdown::default();
+    let da_config = MockDaConfig {
+        sender_address: MockAddress::new([0; 32]),
+        finalization_blocks: 3,
+        wait_attempts: 10,
+    };
+    let da_service = GracefulDaService::new(
+        StarterMockDaService::new(&da_config.into(), temp_dir.path(), None).await?,
+        shutdown.clone(),
+    );
+
+    let fee = da_service.estimate_fee(3).await?;
+    da_service.send_transaction(&[1, 2, 3], fee).await?;
+    let head = da_service.get_head_block_header().await?.height();
+    da_service.get_block_at(head).await?;
+
+    // A wait for the next block is refused on shutdown
+    let next_block = tokio::spawn({
+        let da_service = da_service.clone();
+        async move { da_service.get_block_at(head + 1).await }
+    });
+    shutdown.request();
+    let next_block = tokio::time::timeout(Duration::from_secs(5), next_block).await??;
+    assert!(
+        next_block.is_err(),
+        "A block was handed out after the shutdown"
+    );
+    assert!(
+        da_service.get_block_at(head).await.is_err(),
+        "A block was handed out after the shutdown"
+    );
+
+    // Blobs can still be submitted, e.g. by the sequencer
+    let fee = da_service.estimate_fee(3).await?;
+    da_service.send_transaction(&[4, 5, 6], fee).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn rollup_stops_at_a_slot_boundary() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(1, 1_000_000)
+        .start()
+        .await?;
+    let tx = rollup.signers_mut()[0].sign(&transfer());
+    rollup.send_transactions(&[tx]).await?;
+    rollup.wait_for_slot().await?;
+
+    assert!(rollup.stop_gracefully(Duration::from_secs(60)).await?);
+    let head: serde_json::Value = rollup
+        .http()
+        .request("ledger_getHead", rpc_params![])
+        .await?;
+
+    // The servers of the test rollup keep running, but the stopped runner doesn't process the new batch
+    let tx = rollup.signers_mut()[0].sign(&transfer());
+    rollup.send_transactions(&[tx]).await?;
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    let head_after: serde_json::Value = rollup
+        .http()
+        .request("ledger_getHead", rpc_params![])
+        .await?;
+    assert_eq!(head["number"], head_after["number"]);
+
+    rollup.shutdown().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn close_hooks_run_once_after_the_runner_stops() -> anyhow::Result<()> {
+    let shutdown = Shutdown::default();
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    for name in ["mempool", "ledger", "state"] {
+        let closed = closed.clone();
+        shutdown.on_close(name, move || {
+            closed.lock().unwrap().push(name);
+            anyhow::ensure!(name != "ledger", "The ledger failed to flush");
+            Ok(())
+        });
+    }
+
+    let mut runner = tokio::spawn(std::future::pending::<anyhow::Result<()>>());
+    // A failing hook doesn't stop the next ones
+    let result = shutdown
+        .stop_runner(&mut runner, Duration::from_millis(100))
+        .await;
+    assert!(result.is_err());
+    assert_eq!(*closed.lock().unwrap(), ["mempool", "ledger", "state"]);
+
+    shutdown.close()?;
+    assert_eq!(closed.lock().unwrap().len(), 3);
+    Ok(())
+}
+
+#[tokio::test]
+async fn runner_is_stopped_only_when_it_returns() -> anyhow::Result<()> {
+    let shutdown = Shutdown::default();
+    let da_config = MockDaConfig {
+        sender_address: MockAddress::new([0; 32]),
+        finalization_blocks: 3,
+        wait_attempts: 10,
+    };
+    let temp_dir = tempfile::tempdir()?;
+    let da_service = GracefulDaService::new(
+        StarterMockDaService::new(&da_config.into(), temp_dir.path(), None).await?,
+        shutdown.clone(),
+    );
+    let (slot_done_tx, slot_done_rx) = tokio::sync::oneshot::channel::<()>();
+
+    // A runner in the middle of a slot, which asks for the next block once the slot is done
+    let runner = {
+        let da_service = da_service.clone();
+        async move {
+            let _ = slot_done_rx.await;
+            da_service.get_block_at(1).await?;
+            Ok(())
+        }
+    };
+    let runner = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { shutdown.run_runner(runner).await }
+    });
+
+    shutdown.request();
+    // Other callers are refused a block, but don't stop the runner
+    assert!(da_service.get_block_at(1).await.is_err());
+    let stopped = tokio::time::timeout(Duration::from_millis(200), shutdown.runner_stopped()).await;
+    assert!(stopped.is_err(), "The runner was reported stopped mid-slot");
+
+    slot_done_tx.send(()).expect("The runner is waiting");
+    tokio::time::timeout(Duration::from_secs(5), shutdown.runner_stopped()).await?;
+    // The refused block is not an error of the node
+    runner.await??;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use stf_starter::spans;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::Layer;
+
+type Fields = BTreeMap<String, String>;
+
+/// Records the name and the fields of every span.
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    spans: Arc<Mutex<Vec<(Id, &'static str, Fields)>>>,
+}
+
+impl SpanRecorder {
+    fn fields(&self, name: &str) -> Vec<Fields> {
+        let spans = self.spans.lock().unwrap();
+        spans
+            .iter()
+            .filter(|(_, span_name, _)| *span_name == name)
+            .map(|(_, _, fields)| fields.clone())
+            .collect()
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for SpanRecorder {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = Fields::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let mut spans = self.spans.lock().unwrap();
+        spans.push((id.clone(), attrs.metadata().name(), fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let mut spans = self.spans.lock().unwrap();
+        // The IDs of closed spans are reused, so the span is the latest one with the ID
+        let span = spans.iter_mut().rev().find(|(span_id, _, _)| span_id == id);
+        if let Some((_, _, fields)) = span {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+}
+
+#[test]
+fn slot_and_tx_spans_are_tagged() {
+    let recorder = SpanRecorder::default();
+    let subscriber = tracing_subscriber::registry().with(recorder.clone());
+    // The spans live in thread-locals, like during the application of a slot
+    tracing::subscriber::with_default(subscriber, || {
+        spans::enter_slot(7, 42);
+        spans::enter_batch(&[0xab; 4], &"sequencer");
+        {
+            let span = spans::authenticate_span();
+            let _entered = span.enter();
+            spans::record_authenticated_tx(&span, &"0x1234");
+        }
+        spans::enter_tx();
+        spans::exit_tx();
+        spans::exit_slot();
+    });
+
+    let slot = &recorder.fields("slot")[0];
+    assert_eq!(slot["slot"], "7");
+    assert_eq!(slot["da_height"], "42");
+    let batch = &recorder.fields("batch")[0];
+    assert_eq!(batch["slot"], "7");
+    assert_eq!(batch["batch_id"], "abababab");
+    assert_eq!(batch["sequencer"], "sequencer");
+    for name in ["authenticate", "tx"] {
+        let tx = &recorder.fields(name)[0];
+        assert_eq!(tx["slot"], "7", "{name}");
+        assert_eq!(tx["tx_hash"], "0x1234", "{name}");
+    }
+}
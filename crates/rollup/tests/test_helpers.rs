@@ -1,15 +1,18 @@
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::rpc_params;
+use serde_json::Value;
 use sov_cli::wallet_state::PrivateKeyAndAddress;
 use std::net::SocketAddr;
 use std::path::Path;
 
 use sov_kernels::basic::{BasicKernelGenesisConfig, BasicKernelGenesisPaths};
-use sov_mock_da::MockDaConfig;
 use sov_modules_api::Spec;
 use sov_modules_rollup_blueprint::RollupBlueprint;
+use sov_rollup_starter::mock_da::faults::FaultInjector;
+use sov_rollup_starter::mock_da::StarterMockDaConfig;
 use sov_rollup_starter::mock_rollup::MockRollup;
+use sov_rollup_starter::test_harness::{test_rollup_config, TestRollup};
 use sov_stf_runner::RollupProverConfig;
-use sov_stf_runner::{HttpServerConfig, ProofManagerConfig};
-use sov_stf_runner::{RollupConfig, RunnerConfig, StorageConfig};
 use stf_starter::genesis_config::GenesisPaths;
 use tokio::sync::oneshot;
 
@@ -18,34 +21,51 @@ pub async fn start_rollup(
     rt_genesis_paths: GenesisPaths,
     kernel_genesis_paths: BasicKernelGenesisPaths,
     rollup_prover_config: RollupProverConfig,
-    da_config: MockDaConfig,
+    da_config: StarterMockDaConfig,
+) {
+    run_mock_rollup(
+        MockRollup::default(),
+        rpc_reporting_channel,
+        rt_genesis_paths,
+        kernel_genesis_paths,
+        rollup_prover_config,
+        da_config,
+    )
+    .await
+}
+
+/// Starts a rollup whose mock DA injects the faults commanded through `fault_injector`.
+pub async fn start_rollup_with_faults(
+    rpc_reporting_channel: oneshot::Sender<SocketAddr>,
+    rt_genesis_paths: GenesisPaths,
+    kernel_genesis_paths: BasicKernelGenesisPaths,
+    rollup_prover_config: RollupProverConfig,
+    da_config: StarterMockDaConfig,
+    fault_injector: FaultInjector,
+) {
+    run_mock_rollup(
+        MockRollup::with_fault_injector(fault_injector),
+        rpc_reporting_channel,
+        rt_genesis_paths,
+        kernel_genesis_paths,
+        rollup_prover_config,
+        da_config,
+    )
+    .await
+}
+
+async fn run_mock_rollup(
+    mock_demo_rollup: MockRollup,
+    rpc_reporting_channel: oneshot::Sender<SocketAddr>,
+    rt_genesis_paths: GenesisPaths,
+    kernel_genesis_paths: BasicKernelGenesisPaths,
+    rollup_prover_config: RollupProverConfig,
+    da_config: StarterMockDaConfig,
 ) {
     let temp_dir = tempfile::tempdir().unwrap();
     let temp_path = temp_dir.path();
 
-    let rollup_config = RollupConfig {
-        storage: StorageConfig {
-            path: temp_path.to_path_buf(),
-        },
-        runner: RunnerConfig {
-            genesis_height: 0,
-            da_polling_interval_ms: 1000,
-            rpc_config: HttpServerConfig {
-                bind_host: "127.0.0.1".into(),
-                bind_port: 0,
-            },
-            axum_config: HttpServerConfig {
-                bind_host: "127.0.0.1".into(),
-                bind_port: 0,
-            },
-        },
-        da: da_config,
-        proof_manager: ProofManagerConfig {
-            aggregated_proof_block_jump: 1,
-        },
-    };
-
-    let mock_demo_rollup = MockRollup {};
+    let rollup_config = test_rollup_config(temp_path, da_config);
 
     let kernel_genesis = BasicKernelGenesisConfig {
         chain_state: serde_json::from_str(
@@ -94,3 +114,29 @@ pub fn read_private_keys<S: Spec>(suffix: &str) -> PrivateKeyAndAddress<S> {
 
     key_and_address
 }
+
+/// Returns the events stored in the ledger, in order.
+pub async fn ledger_events(rollup: &TestRollup) -> anyhow::Result<Vec<Value>> {
+    let mut events = Vec::new();
+    loop {
+        let event: Option<Value> = rollup
+            .http()
+            .request("ledger_getEventByNumber", rpc_params![events.len() as u64])
+            .await?;
+        match event {
+            Some(event) => events.push(event),
+            None => return Ok(events),
+        }
+    }
+}
+
+/// Finds the event variant `name`, e.g. `TxRejected`, in a ledger event.
+pub fn find_event<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(fields) => fields
+            .get(name)
+            .or_else(|| fields.values().find_map(|field| find_event(field, name))),
+        Value::Array(items) => items.iter().find_map(|item| find_event(item, name)),
+        _ => None,
+    }
+}
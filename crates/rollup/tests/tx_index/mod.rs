@@ -0,0 +1,107 @@
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::rpc_params;
+use serde_json::Value;
+use sov_mock_da::MockDaSpec;
+use sov_modules_api::Spec;
+use sov_rollup_starter::test_harness::{TestRollup, TestSigner, TestSpec};
+use stf_starter::tx_index::{page_range, MAX_PAGE_SIZE};
+use stf_starter::RuntimeCall;
+
+const TRANSFER_AMOUNT: u64 = 100;
+const MAX_SLOTS: usize = 10;
+
+type Address = <TestSpec as Spec>::Address;
+
+fn transfer(to: &Address, amount: u64) -> RuntimeCall<TestSpec, MockDaSpec> {
+    RuntimeCall::<TestSpec, MockDaSpec>::bank(sov_bank::CallMessage::Transfer {
+        to: to.clone(),
+        coins: sov_bank::Coins {
+            amount,
+            token_id: sov_bank::config_gas_token_id(),
+        },
+    })
+}
+
+async fn txs_by_address(
+    rollup: &TestRollup,
+    address: &Address,
+    offset: u64,
+    limit: u64,
+) -> anyhow::Result<Value> {
+    Ok(rollup
+        .http()
+        .request(
+            "index_txsByAddress",
+            rpc_params![address.to_string(), offset, limit],
+        )
+        .await?)
+}
+
+async fn wait_for_txs(
+    rollup: &mut TestRollup,
+    address: &Address,
+    total: u64,
+) -> anyhow::Result<Value> {
+    for _ in 0..MAX_SLOTS {
+        let page = txs_by_address(rollup, address, 0, MAX_PAGE_SIZE).await?;
+        if page["total"] == total {
+            return Ok(page);
+        }
+        rollup.wait_for_slot().await?;
+    }
+    anyhow::bail!("{address} was not indexed in {total} transactions within {MAX_SLOTS} slots")
+}
+
+#[tokio::test]
+async fn indexed_txs_are_found_in_the_ledger() -> anyhow::Result<()> {
+    let mut rollup = TestRollup::builder()
+        .prefunded_keys(3, 1_000_000)
+        .start()
+        .await?;
+    let recipient = TestSigner::generate().address();
+    let own_address = rollup.signers()[2].address();
+
+    // The first transaction is reverted, so the ledger has one more transaction than the index before the others
+    let txs = [
+        rollup.signers_mut()[0].sign(&transfer(&recipient, u64::MAX)),
+        rollup.signers_mut()[1].sign(&transfer(&recipient, TRANSFER_AMOUNT)),
+        rollup.signers_mut()[2].sign(&transfer(&own_address, TRANSFER_AMOUNT)),
+    ];
+    rollup.send_transactions(&txs).await?;
+
+    let page = wait_for_txs(&mut rollup, &recipient, 1).await?;
+    let tx_hash = &page["items"][0]["tx_hash"];
+    let tx: Option<Value> = rollup
+        .http()
+        .request("ledger_getTxByHash", rpc_params![tx_hash])
+        .await?;
+    let tx = tx.expect("The indexed transaction is not in the ledger");
+    assert_eq!(&tx["hash"], tx_hash);
+
+    // A transfer to its own sender is indexed once for the address
+    let page = wait_for_txs(&mut rollup, &own_address, 1).await?;
+    assert_eq!(page["items"].as_array().map(Vec::len), Some(1));
+    assert_ne!(&page["items"][0]["tx_hash"], tx_hash);
+
+    // Pages past the end are empty
+    let page = txs_by_address(&rollup, &recipient, 1, 10).await?;
+    assert_eq!(page["total"], 1);
+    assert_eq!(page["items"], serde_json::json!([]));
+
+    rollup.shutdown().await;
+    Ok(())
+}
+
+#[test]
+fn pages_are_bounded() {
+    assert_eq!(page_range(0, 10, 5), 0..5);
+    assert_eq!(page_range(3, 10, 5), 3..5);
+    assert_eq!(page_range(5, 10, 5), 5..5);
+    assert_eq!(page_range(u64::MAX, u64::MAX, 5), 5..5);
+    assert_eq!(page_range(0, 0, 5), 0..0);
+    assert_eq!(page_range(10, u64::MAX, 1000), 10..10 + MAX_PAGE_SIZE);
+    assert_eq!(
+        page_range(u64::MAX - 1, u64::MAX, u64::MAX),
+        u64::MAX - 1..u64::MAX
+    );
+}
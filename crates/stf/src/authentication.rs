@@ -1,17 +1,21 @@
-//! The stf-rollup supports `sov-module` authenticator. To support other authentication schemes,
-//! you can check out how we support `EVM` authenticator here:
-//! https://github.com/Sovereign-Labs/sovereign-sdk-wip/blob/146d5c2c5fa07ab7bb59ba6b2e64690ac9b63830/examples/demo-rollup/stf/src/authentication.rs#L29-L32
+//! The stf-rollup supports two authenticators:
+//! - `sov-module` transactions, signed with the rollup's native crypto and encoded with borsh ([`ModAuth`]).
+//! - `EVM` transactions, RLP-encoded and signed with secp256k1, as produced by Ethereum wallets ([`EvmAuth`]).
+//!
+//! Every raw transaction is wrapped in the `Auth` envelope, which tells the runtime which authenticator to use.
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
 use sov_modules_api::capabilities::{FatalError, RuntimeAuthenticator};
 use sov_modules_api::runtime::capabilities::{AuthenticationError, RawTx};
-use sov_modules_api::transaction::AuthenticatedTransactionAndRawHash;
-use sov_modules_api::{Authenticator, DaSpec, DispatchCall, GasMeter, Spec};
+use sov_modules_api::transaction::{AuthenticatedTransactionAndRawHash, Transaction};
+use sov_modules_api::{Authenticator, DaSpec, DispatchCall, GasMeter, Spec, UnlimitedGasMeter};
 use sov_sequencer_registry::SequencerStakeMeter;
 
-use crate::runtime::Runtime;
+use crate::circuit_breaker::CallTarget;
+use crate::runtime::{Runtime, RuntimeCall};
+use crate::spans;
 
 impl<S: Spec, Da: DaSpec> RuntimeAuthenticator<S> for Runtime<S, Da> {
     type Decodable = <Self as DispatchCall>::Decodable;
@@ -24,25 +28,59 @@ impl<S: Spec, Da: DaSpec> RuntimeAuthenticator<S> for Runtime<S, Da> {
         raw_tx: &RawTx,
         sequencer_stake_meter: &mut Self::SequencerStakeMeter,
     ) -> Result<(AuthenticatedTransactionAndRawHash<S>, Self::Decodable), AuthenticationError> {
+        let span = spans::authenticate_span();
+        let _entered = span.enter();
         let auth = Auth::try_from_slice(raw_tx.data.as_slice()).map_err(|e| {
             AuthenticationError::FatalError(FatalError::DeserializationFailed(e.to_string()))
         })?;
 
-        match auth {
+        let (tx, call) = match auth {
             Auth::Mod(tx) => ModAuth::<S, Da>::authenticate(&tx, sequencer_stake_meter),
-            // Leaving the line below as an example to support different authentication schemes:
-            // Auth::Evm(tx) => EvmAuth::<S, Da>::authenticate(&tx, sequencer_stake_meter),
+            Auth::Evm(tx) => EvmAuth::<S, Da>::authenticate(&tx, sequencer_stake_meter),
+        }?;
+        spans::record_authenticated_tx(&span, &tx.raw_tx_hash);
+        Ok((tx, call))
+    }
+}
+
+/// Authenticates a raw transaction without charging its gas, for the transaction index. Returns `None` if it fails
+/// authentication, in which case it is not applied.
+pub(crate) fn authenticate_unmetered<S: Spec, Da: DaSpec>(
+    raw_tx: &RawTx,
+) -> Option<AuthenticatedTransactionAndRawHash<S>> {
+    let mut gas_meter = UnlimitedGasMeter::<S::Gas>::default();
+    let (tx, _) = match Auth::try_from_slice(raw_tx.data.as_slice()).ok()? {
+        Auth::Mod(tx) => ModAuth::<S, Da>::authenticate(&tx, &mut gas_meter),
+        Auth::Evm(tx) => EvmAuth::<S, Da>::authenticate(&tx, &mut gas_meter),
+    }
+    .ok()?;
+    Some(tx)
+}
+
+/// Returns the target of a raw transaction for the circuit breaker, or `None` if its envelope or call can't be
+/// decoded. The transaction is not authenticated.
+pub(crate) fn call_target<S: Spec, Da: DaSpec>(raw_tx: &RawTx) -> Option<CallTarget> {
+    match Auth::try_from_slice(raw_tx.data.as_slice()).ok()? {
+        Auth::Mod(tx) => {
+            let tx = Transaction::<S>::try_from_slice(&tx).ok()?;
+            let call = <Runtime<S, Da> as DispatchCall>::decode_call(tx.runtime_msg()).ok()?;
+            Some(CallTarget::of(&call))
         }
+        // Every EVM transaction is a call of the `evm` module
+        Auth::Evm(_) => Some(CallTarget {
+            module: "evm",
+            call: None,
+        }),
     }
 }
 
 #[derive(Debug, PartialEq, Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 enum Auth {
     Mod(Vec<u8>),
-    // Leaving the line below as an example to support different authentication schemes:
-    // Evm(Vec<u8>),
+    Evm(Vec<u8>),
 }
 
+/// Authenticator for transactions signed with the rollup's native crypto.
 pub struct ModAuth<S: Spec, Da: DaSpec> {
     _phantom: PhantomData<(S, Da)>,
 }
@@ -68,3 +106,31 @@ impl<S: Spec, Da: DaSpec> Authenticator for ModAuth<S, Da> {
         Ok(RawTx { data })
     }
 }
+
+/// Authenticator for RLP-encoded Ethereum transactions signed with secp256k1.
+pub struct EvmAuth<S: Spec, Da: DaSpec> {
+    _phantom: PhantomData<(S, Da)>,
+}
+
+impl<S: Spec, Da: DaSpec> Authenticator for EvmAuth<S, Da> {
+    type Spec = S;
+    type DispatchCall = Runtime<S, Da>;
+    fn authenticate(
+        tx: &[u8],
+        stake_meter: &mut impl GasMeter<S::Gas>,
+    ) -> Result<
+        (
+            AuthenticatedTransactionAndRawHash<Self::Spec>,
+            <Self::DispatchCall as DispatchCall>::Decodable,
+        ),
+        AuthenticationError,
+    > {
+        let (tx_and_raw_hash, call) = sov_evm::authenticate::<Self::Spec>(tx, stake_meter)?;
+        Ok((tx_and_raw_hash, RuntimeCall::evm(call)))
+    }
+
+    fn encode(tx: Vec<u8>) -> Result<RawTx, anyhow::Error> {
+        let data = Auth::Evm(tx).try_to_vec()?;
+        Ok(RawTx { data })
+    }
+}
@@ -0,0 +1,173 @@
+//! The `chain_info` module records the DA block of every slot in the state, so that modules can read the
+//! current block height and time (e.g. for vesting or timeouts), and users can query the history over RPC and REST.
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "native")]
+use jsonrpsee::core::RpcResult;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "native")]
+use sov_modules_api::macros::rpc_gen;
+#[cfg(feature = "native")]
+use sov_modules_api::ApiStateAccessor;
+use sov_modules_api::{
+    CallResponse, Context, DaSpec, Module, ModuleError, ModuleId, ModuleInfo, ModuleRestApi,
+    NotInstantiable, Spec, StateCheckpoint, StateMap, StateReader, StateValue,
+    VersionedStateReadWriter, WorkingSet,
+};
+use sov_rollup_interface::da::Time;
+use sov_state::namespaces::User;
+
+/// The maximum number of slots returned by `chainInfo_getSlots`.
+pub const MAX_SLOTS_PER_QUERY: u64 = 100;
+
+/// The DA block of a slot.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct SlotInfo<Root, Hash> {
+    /// The number of the slot, starting at 1 for the first slot after genesis.
+    pub slot_number: u64,
+    /// The height of the DA block.
+    pub da_height: u64,
+    /// The hash of the DA block.
+    pub da_block_hash: Hash,
+    /// The timestamp of the DA block.
+    pub time: Time,
+    /// The state root before the slot was applied.
+    pub pre_state_root: Root,
+}
+
+/// The [`SlotInfo`] of a rollup.
+pub type SlotInfoOf<S, Da> = SlotInfo<<S as Spec>::VisibleHash, <Da as DaSpec>::SlotHash>;
+
+/// Records the DA block of every slot. It has no call messages: the slots are recorded by the runtime slot hooks.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct ChainInfo<S: Spec, Da: DaSpec> {
+    /// The address of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The recorded slots, by slot number.
+    #[state]
+    pub(crate) slots: StateMap<u64, SlotInfoOf<S, Da>>,
+
+    /// The number of the latest recorded slot.
+    #[state]
+    pub(crate) latest_slot_number: StateValue<u64>,
+}
+
+impl<S: Spec, Da: DaSpec> Module for ChainInfo<S, Da> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = NotInstantiable;
+
+    type Event = ();
+
+    fn genesis(
+        &self,
+        _config: &Self::Config,
+        working_set: &mut WorkingSet<S>,
+    ) -> Result<(), ModuleError> {
+        self.latest_slot_number.set(&0, working_set);
+        Ok(())
+    }
+
+    fn call(
+        &self,
+        msg: Self::CallMessage,
+        _context: &Context<Self::Spec>,
+        _working_set: &mut WorkingSet<S>,
+    ) -> Result<CallResponse, ModuleError> {
+        match msg {}
+    }
+}
+
+impl<S: Spec, Da: DaSpec> ChainInfo<S, Da> {
+    /// Records the DA block of the slot being opened. Called by the runtime `begin_slot_hook`.
+    pub fn begin_slot_hook(
+        &self,
+        pre_state_root: &S::VisibleHash,
+        state: &mut VersionedStateReadWriter<StateCheckpoint<S>>,
+    ) {
+        // The DA block of the slot is tracked by the `chain_state` kernel module.
+        let chain_state = sov_chain_state::ChainState::<S, Da>::default();
+        let Some(transition) = chain_state.get_in_progress_transition(state) else {
+            tracing::warn!("No slot in progress, the DA block is not recorded");
+            return;
+        };
+
+        let slot_number = self.latest_slot_number.get(state).unwrap_or_default() + 1;
+        let slot = SlotInfo {
+            slot_number,
+            da_height: chain_state.get_genesis_da_height(state).unwrap_or_default()
+                + chain_state.get_slot_height(state),
+            da_block_hash: transition.da_block_hash().clone(),
+            time: chain_state.get_time(state),
+            pre_state_root: pre_state_root.clone(),
+        };
+
+        self.slots.set(&slot_number, &slot, state);
+        self.latest_slot_number.set(&slot_number, state);
+    }
+
+    /// Returns the latest recorded slot, i.e. the current DA block during the execution of a slot.
+    pub fn latest_slot(&self, state: &mut impl StateReader<User>) -> Option<SlotInfoOf<S, Da>> {
+        let slot_number = self.latest_slot_number.get(state)?;
+        self.slots.get(&slot_number, state)
+    }
+
+    /// Returns the slot with the given number.
+    pub fn slot(
+        &self,
+        slot_number: u64,
+        state: &mut impl StateReader<User>,
+    ) -> Option<SlotInfoOf<S, Da>> {
+        self.slots.get(&slot_number, state)
+    }
+
+    /// Returns the timestamp of the current DA block.
+    pub fn current_time(&self, state: &mut impl StateReader<User>) -> Option<Time> {
+        self.latest_slot(state).map(|slot| slot.time)
+    }
+
+    /// Returns the height of the current DA block.
+    pub fn current_da_height(&self, state: &mut impl StateReader<User>) -> Option<u64> {
+        self.latest_slot(state).map(|slot| slot.da_height)
+    }
+}
+
+#[cfg(feature = "native")]
+#[rpc_gen(client, server, namespace = "chainInfo")]
+impl<S: Spec, Da: DaSpec> ChainInfo<S, Da> {
+    /// Returns the latest recorded slot.
+    #[rpc_method(name = "latestSlot")]
+    pub fn rpc_latest_slot(
+        &self,
+        state: &mut ApiStateAccessor<S>,
+    ) -> RpcResult<Option<SlotInfoOf<S, Da>>> {
+        Ok(self.latest_slot(state))
+    }
+
+    /// Returns the slot with the given number.
+    #[rpc_method(name = "getSlot")]
+    pub fn rpc_get_slot(
+        &self,
+        slot_number: u64,
+        state: &mut ApiStateAccessor<S>,
+    ) -> RpcResult<Option<SlotInfoOf<S, Da>>> {
+        Ok(self.slot(slot_number, state))
+    }
+
+    /// Returns up to `limit` slots starting at `start`, at most [`MAX_SLOTS_PER_QUERY`].
+    #[rpc_method(name = "getSlots")]
+    pub fn rpc_get_slots(
+        &self,
+        start: u64,
+        limit: u64,
+        state: &mut ApiStateAccessor<S>,
+    ) -> RpcResult<Vec<SlotInfoOf<S, Da>>> {
+        let end = start.saturating_add(limit.min(MAX_SLOTS_PER_QUERY));
+        Ok((start..end)
+            .map_while(|slot_number| self.slot(slot_number, state))
+            .collect())
+    }
+}
@@ -0,0 +1,300 @@
+//! The `circuit_breaker` module lets the admins set in genesis pause the whole runtime, a module, or a single
+//! call of a module, for incident response. While paused, matching transactions are rejected before they are
+//! authenticated.
+//!
+//! A pause target is one of:
+//! - `*`: every module.
+//! - a module, e.g. `ibc_transfer`.
+//! - a call of a module, e.g. `bank.mint`.
+//!
+//! Only the modules with call messages can be paused, and only the calls of `bank` one by one. The circuit breaker
+//! itself cannot be paused, so that it can always be unpaused.
+//!
+//! The paused transactions are dropped from their batch at its beginning, with a `TxRejected` event each, so a pause
+//! applies from the batch after the one that sent it.
+use anyhow::{anyhow, bail};
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_modules_api::batch::BatchWithId;
+use sov_modules_api::{
+    CallResponse, Context, DaSpec, Module, ModuleError, ModuleId, ModuleInfo, ModuleRestApi, Spec,
+    StateCheckpoint, StateMap, StateValue, WorkingSet,
+};
+
+use crate::authentication::call_target;
+use crate::runtime::RuntimeCall;
+
+/// The target that pauses every module.
+pub const PAUSE_ALL: &str = "*";
+
+const MODULE_NAME: &str = "circuit_breaker";
+
+/// The modules that can be paused: the modules of the runtime with call messages, but the circuit breaker.
+const PAUSABLE_MODULES: &[&str] = &[
+    "accounts",
+    "bank",
+    "ibc",
+    "ibc_transfer",
+    "sequencer_registry",
+    "prover_incentives",
+    "evm",
+];
+
+/// The calls of `bank` that can be paused, as named by [`bank_call_name`].
+const BANK_CALLS: &[&str] = &["create_token", "transfer", "burn", "mint", "freeze"];
+
+/// The module, and the call if it is known, targeted by a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallTarget {
+    /// The name of the runtime field of the module, e.g. `bank`.
+    pub module: &'static str,
+    /// The snake case name of the call, e.g. `mint`.
+    pub call: Option<&'static str>,
+}
+
+impl CallTarget {
+    /// Returns the target of a runtime call.
+    pub fn of<S: Spec, Da: DaSpec>(call: &RuntimeCall<S, Da>) -> Self {
+        let (module, call) = match call {
+            RuntimeCall::accounts(_) => ("accounts", None),
+            RuntimeCall::bank(call) => ("bank", Some(bank_call_name(call))),
+            RuntimeCall::ibc(_) => ("ibc", None),
+            RuntimeCall::ibc_transfer(_) => ("ibc_transfer", None),
+            RuntimeCall::sequencer_registry(_) => ("sequencer_registry", None),
+            RuntimeCall::prover_incentives(_) => ("prover_incentives", None),
+            RuntimeCall::evm(_) => ("evm", None),
+            RuntimeCall::chain_info(_) => ("chain_info", None),
+            RuntimeCall::tx_index(_) => ("tx_index", None),
+            RuntimeCall::circuit_breaker(_) => (MODULE_NAME, None),
+            RuntimeCall::sequencer_outcomes(_) => ("sequencer_outcomes", None),
+        };
+        Self { module, call }
+    }
+
+    /// Returns the pause targets matching this call, from the broadest.
+    fn pause_targets(&self) -> Vec<String> {
+        let mut targets = vec![PAUSE_ALL.to_string(), self.module.to_string()];
+        if let Some(call) = self.call {
+            targets.push(format!("{}.{call}", self.module));
+        }
+        targets
+    }
+}
+
+fn bank_call_name<S: Spec>(call: &sov_bank::CallMessage<S>) -> &'static str {
+    match call {
+        sov_bank::CallMessage::CreateToken { .. } => "create_token",
+        sov_bank::CallMessage::Transfer { .. } => "transfer",
+        sov_bank::CallMessage::Burn { .. } => "burn",
+        sov_bank::CallMessage::Mint { .. } => "mint",
+        sov_bank::CallMessage::Freeze { .. } => "freeze",
+    }
+}
+
+/// Genesis configuration of the [`CircuitBreaker`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "S::Address: Serialize + DeserializeOwned")]
+pub struct CircuitBreakerConfig<S: Spec> {
+    /// The addresses allowed to pause and unpause.
+    pub admins: Vec<S::Address>,
+}
+
+/// A call message of the [`CircuitBreaker`]. Only admins can send them.
+#[cfg_attr(
+    feature = "native",
+    derive(sov_modules_api::macros::CliWalletArg),
+    derive(schemars::JsonSchema)
+)]
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum CallMessage {
+    /// Rejects the transactions matching `target`.
+    Pause {
+        /// The pause target: `*`, a module or `module.call`.
+        target: String,
+    },
+    /// Lifts the pause of `target`.
+    Unpause {
+        /// The pause target, as given to `Pause`.
+        target: String,
+    },
+}
+
+/// An event of the [`CircuitBreaker`].
+#[cfg_attr(feature = "native", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum Event {
+    /// `admin` paused `target`.
+    Paused {
+        /// The pause target.
+        target: String,
+        /// The admin who paused it.
+        admin: String,
+    },
+    /// `admin` unpaused `target`.
+    Unpaused {
+        /// The pause target.
+        target: String,
+        /// The admin who unpaused it.
+        admin: String,
+    },
+    /// A transaction was rejected because `target` is paused.
+    TxRejected {
+        /// The pause target.
+        target: String,
+    },
+}
+
+/// Pauses the runtime, modules or calls on the command of the admins.
+#[derive(Clone, ModuleInfo, ModuleRestApi)]
+pub struct CircuitBreaker<S: Spec> {
+    /// The address of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The addresses allowed to pause and unpause.
+    #[state]
+    pub(crate) admins: StateValue<Vec<S::Address>>,
+
+    /// The paused targets.
+    #[state]
+    pub(crate) paused: StateMap<String, bool>,
+}
+
+impl<S: Spec> Module for CircuitBreaker<S> {
+    type Spec = S;
+
+    type Config = CircuitBreakerConfig<S>;
+
+    type CallMessage = CallMessage;
+
+    type Event = Event;
+
+    fn genesis(
+        &self,
+        config: &Self::Config,
+        working_set: &mut WorkingSet<S>,
+    ) -> Result<(), ModuleError> {
+        self.admins.set(&config.admins, working_set);
+        Ok(())
+    }
+
+    fn call(
+        &self,
+        msg: Self::CallMessage,
+        context: &Context<Self::Spec>,
+        working_set: &mut WorkingSet<S>,
+    ) -> Result<CallResponse, ModuleError> {
+        let admin = context.sender();
+        if !self
+            .admins
+            .get(working_set)
+            .unwrap_or_default()
+            .contains(admin)
+        {
+            return Err(ModuleError::ModuleError(anyhow!(
+                "{admin} is not a circuit breaker admin"
+            )));
+        }
+
+        match msg {
+            CallMessage::Pause { target } => {
+                validate_target(&target).map_err(ModuleError::ModuleError)?;
+                self.paused.set(&target, &true, working_set);
+                tracing::warn!(%target, %admin, "Circuit breaker paused");
+                self.emit_event(
+                    working_set,
+                    Event::Paused {
+                        target,
+                        admin: admin.to_string(),
+                    },
+                );
+            }
+            CallMessage::Unpause { target } => {
+                self.paused.remove(&target, working_set);
+                tracing::warn!(%target, %admin, "Circuit breaker unpaused");
+                self.emit_event(
+                    working_set,
+                    Event::Unpaused {
+                        target,
+                        admin: admin.to_string(),
+                    },
+                );
+            }
+        }
+        Ok(CallResponse::default())
+    }
+}
+
+impl<S: Spec> CircuitBreaker<S> {
+    /// Drops the transactions of `batch` whose target is paused and emits a `TxRejected` event for each. Called by
+    /// the runtime `begin_batch_hook`.
+    ///
+    /// The transactions that can't be decoded are kept, for the authenticator to reject them.
+    pub fn drop_paused_txs<Da: DaSpec>(
+        &self,
+        batch: &mut BatchWithId,
+        state_checkpoint: &mut StateCheckpoint<S>,
+    ) {
+        batch.txs.retain(|raw_tx| {
+            let Some(call) = call_target::<S, Da>(raw_tx) else {
+                return true;
+            };
+            match self.paused_target(&call, state_checkpoint) {
+                Some(target) => {
+                    tracing::warn!(%target, "Rejecting a transaction paused by the circuit breaker");
+                    self.emit_event(state_checkpoint, Event::TxRejected { target });
+                    false
+                }
+                None => true,
+            }
+        });
+    }
+
+    /// Returns the broadest paused target matching `call`, if any.
+    fn paused_target(
+        &self,
+        call: &CallTarget,
+        state_checkpoint: &mut StateCheckpoint<S>,
+    ) -> Option<String> {
+        if call.module == MODULE_NAME {
+            return None;
+        }
+        call.pause_targets().into_iter().find(|target| {
+            self.paused
+                .get(target, state_checkpoint)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Returns whether `target` is paused.
+    pub fn is_paused(&self, target: &str, working_set: &mut WorkingSet<S>) -> bool {
+        self.paused
+            .get(&target.to_string(), working_set)
+            .unwrap_or_default()
+    }
+}
+
+fn validate_target(target: &str) -> anyhow::Result<()> {
+    if target == PAUSE_ALL {
+        return Ok(());
+    }
+    let (module, call) = match target.split_once('.') {
+        Some((module, call)) => (module, Some(call)),
+        None => (target, None),
+    };
+    if module == MODULE_NAME {
+        bail!("The circuit breaker cannot be paused");
+    }
+    if !PAUSABLE_MODULES.contains(&module) {
+        bail!("Invalid pause target `{target}`, the pausable modules are {PAUSABLE_MODULES:?}");
+    }
+    match call {
+        None => Ok(()),
+        Some(call) if module == "bank" && BANK_CALLS.contains(&call) => Ok(()),
+        Some(_) if module == "bank" => {
+            bail!("Invalid pause target `{target}`, the calls of `bank` are {BANK_CALLS:?}")
+        }
+        Some(_) => bail!("Invalid pause target `{target}`, only the calls of `bank` can be paused"),
+    }
+}
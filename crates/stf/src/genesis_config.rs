@@ -1,20 +1,23 @@
-//! While the `GenesisConfig` type for `Rollup` is generated from the underlying runtime through a macro,
+//! While the `GenesisConfig` type for `Rollup` is defined along the underlying runtime,
 //! specific module configurations are obtained from files. This code is responsible for the logic
 //! that transforms module genesis data into Rollup genesis data.
 
 use std::convert::AsRef;
 use std::path::{Path, PathBuf};
 
+use anyhow::{bail, Context};
+use serde::de::DeserializeOwned;
 use sov_accounts::AccountConfig;
 use sov_bank::BankConfig;
-use sov_ibc::ExampleModuleConfig;
-use sov_ibc_transfer::TransferConfig;
+use sov_evm::EvmConfig;
 use sov_modules_api::{DaSpec, Spec};
 use sov_modules_stf_blueprint::Runtime as RuntimeTrait;
 use sov_prover_incentives::ProverIncentivesConfig;
 use sov_sequencer_registry::SequencerConfig;
 use sov_stf_runner::read_json_file;
 
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::ibc_genesis::{ConfigError, IbcConfig, IbcTransferConfig};
 /// Creates config for a rollup with some default settings, the config is used in demos and tests.
 use crate::runtime::GenesisConfig;
 use crate::runtime::Runtime;
@@ -33,17 +36,25 @@ pub struct GenesisPaths {
     pub sequencer_genesis_path: PathBuf,
     /// Prover Incentives genesis path.
     pub prover_incentives_genesis_path: PathBuf,
+    /// EVM genesis path.
+    pub evm_genesis_path: PathBuf,
+    /// Circuit breaker genesis path.
+    pub circuit_breaker_genesis_path: PathBuf,
 }
 
 impl core::fmt::Display for GenesisPaths {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "GenesisPaths {{ accounts_genesis_path: {}, bank_genesis_path: {}, sequencer_genesis_path: {}, prover_incentives_genesis_path: {} }}",
+            "GenesisPaths {{ accounts_genesis_path: {}, bank_genesis_path: {}, ibc_genesis_path: {}, ibc_transfer_genesis_path: {}, sequencer_genesis_path: {}, prover_incentives_genesis_path: {}, evm_genesis_path: {}, circuit_breaker_genesis_path: {} }}",
             self.accounts_genesis_path.display(),
             self.bank_genesis_path.display(),
+            self.ibc_genesis_path.display(),
+            self.ibc_transfer_genesis_path.display(),
             self.sequencer_genesis_path.display(),
             self.prover_incentives_genesis_path.display(),
+            self.evm_genesis_path.display(),
+            self.circuit_breaker_genesis_path.display(),
         )
     }
 }
@@ -62,6 +73,8 @@ impl GenesisPaths {
             ibc_transfer_genesis_path: dir.as_ref().join("ibc_transfer.json"),
             sequencer_genesis_path: dir.as_ref().join("sequencer_registry.json"),
             prover_incentives_genesis_path: dir.as_ref().join("prover_incentives.json"),
+            evm_genesis_path: dir.as_ref().join("evm.json"),
+            circuit_breaker_genesis_path: dir.as_ref().join("circuit_breaker.json"),
         }
     }
 }
@@ -73,18 +86,240 @@ pub fn create_genesis_config<S: Spec, Da: DaSpec>(
 ) -> anyhow::Result<<Runtime<S, Da> as RuntimeTrait<S, Da>>::GenesisConfig> {
     let accounts_config: AccountConfig<S> = read_json_file(&genesis_paths.accounts_genesis_path)?;
     let bank_config: BankConfig<S> = read_json_file(&genesis_paths.bank_genesis_path)?;
+    let ibc_config: IbcConfig = read_ibc_json_file(&genesis_paths.ibc_genesis_path)?;
+    check_ibc_config(&genesis_paths.ibc_genesis_path, ibc_config.errors())?;
+    let ibc_transfer_config: IbcTransferConfig<S> =
+        read_ibc_json_file(&genesis_paths.ibc_transfer_genesis_path)?;
+    check_ibc_config(
+        &genesis_paths.ibc_transfer_genesis_path,
+        ibc_transfer_config.errors(&ibc_config),
+    )?;
 
     let sequencer_registry_config: SequencerConfig<S, Da> =
         read_json_file(&genesis_paths.sequencer_genesis_path)?;
     let prover_incentives_config: ProverIncentivesConfig<S> =
         read_json_file(&genesis_paths.prover_incentives_genesis_path)?;
+    let evm_config: EvmConfig = read_json_file(&genesis_paths.evm_genesis_path)?;
+    let circuit_breaker_config: CircuitBreakerConfig<S> =
+        read_json_file(&genesis_paths.circuit_breaker_genesis_path)?;
 
-    Ok(GenesisConfig::new(
-        accounts_config,
-        bank_config,
-        ExampleModuleConfig {},
-        TransferConfig {},
-        sequencer_registry_config,
-        prover_incentives_config,
-    ))
+    Ok(GenesisConfig {
+        accounts: accounts_config,
+        bank: bank_config,
+        ibc: ibc_config,
+        ibc_transfer: ibc_transfer_config,
+        sequencer_registry: sequencer_registry_config,
+        prover_incentives: prover_incentives_config,
+        evm: evm_config,
+        circuit_breaker: circuit_breaker_config,
+    })
+}
+
+/// Reads an IBC genesis file. The IBC modules can start without any pre-created
+/// state, so a missing file is read as an empty config.
+///
+/// The IBC configs deny unknown fields, since those would otherwise be dropped without notice.
+pub(crate) fn read_ibc_json_file<T: DeserializeOwned + Default>(path: &Path) -> anyhow::Result<T> {
+    if !path.exists() {
+        tracing::warn!(
+            path = %path.display(),
+            "IBC genesis file not found, starting with an empty config"
+        );
+        return Ok(T::default());
+    }
+    read_json_file(path)
+        .with_context(|| format!("Invalid IBC genesis config in {}", path.display()))
+}
+
+fn check_ibc_config(path: &Path, errors: Vec<ConfigError>) -> anyhow::Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let errors: Vec<String> = errors
+        .into_iter()
+        .map(|(json_path, message)| format!("{json_path}: {message}"))
+        .collect();
+    bail!(
+        "Invalid IBC genesis config in {}: {}",
+        path.display(),
+        errors.join("; ")
+    )
+}
+
+/// The sections of a [`GenesisFile`] and the files of the directory layout they are read from.
+pub const GENESIS_SECTIONS: [(&str, &str); 9] = [
+    ("accounts", "accounts.json"),
+    ("bank", "bank.json"),
+    ("ibc", "ibc.json"),
+    ("ibc_transfer", "ibc_transfer.json"),
+    ("sequencer_registry", "sequencer_registry.json"),
+    ("prover_incentives", "prover_incentives.json"),
+    ("evm", "evm.json"),
+    ("circuit_breaker", "circuit_breaker.json"),
+    ("chain_state", "chain_state.json"),
+];
+
+/// A genesis in a single JSON or TOML file (picked from the extension), with one section per module genesis
+/// file of [`GenesisPaths::from_dir`] plus the kernel `chain_state`.
+///
+/// The sections are kept as JSON values, so converting between the two layouts does not depend on the rollup spec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenesisFile {
+    sections: serde_json::Map<String, serde_json::Value>,
+}
+
+impl GenesisFile {
+    /// Reads the genesis files of the directory layout from `dir`. A missing IBC file is read as an empty section.
+    pub fn from_dir(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let dir = dir.as_ref();
+        Self::from_paths(&GenesisPaths::from_dir(dir), &dir.join("chain_state.json"))
+    }
+
+    /// Reads the module genesis files at `genesis_paths` and the kernel genesis at `chain_state_path`.
+    /// A missing IBC file is read as an empty section.
+    pub fn from_paths(
+        genesis_paths: &GenesisPaths,
+        chain_state_path: &Path,
+    ) -> anyhow::Result<Self> {
+        let files = [
+            ("accounts", &genesis_paths.accounts_genesis_path),
+            ("bank", &genesis_paths.bank_genesis_path),
+            ("ibc", &genesis_paths.ibc_genesis_path),
+            ("ibc_transfer", &genesis_paths.ibc_transfer_genesis_path),
+            ("sequencer_registry", &genesis_paths.sequencer_genesis_path),
+            (
+                "prover_incentives",
+                &genesis_paths.prover_incentives_genesis_path,
+            ),
+            ("evm", &genesis_paths.evm_genesis_path),
+            (
+                "circuit_breaker",
+                &genesis_paths.circuit_breaker_genesis_path,
+            ),
+        ];
+        let mut sections = serde_json::Map::new();
+        for (section, path) in files {
+            let value = if !path.exists() && section.starts_with("ibc") {
+                serde_json::Value::Object(Default::default())
+            } else {
+                read_json_file(path)?
+            };
+            sections.insert(section.to_string(), value);
+        }
+        sections.insert("chain_state".to_string(), read_json_file(chain_state_path)?);
+        Ok(Self { sections })
+    }
+
+    /// Writes the sections to `dir` in the directory layout.
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        for (section, file) in GENESIS_SECTIONS {
+            let path = dir.join(file);
+            std::fs::write(
+                &path,
+                serde_json::to_string_pretty(&self.sections[section])? + "\n",
+            )
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Reads a single genesis file.
+    pub fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read genesis file {}", path.display()))?;
+        let value: serde_json::Value = if is_toml(path) {
+            toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse genesis file {}", path.display()))?
+        } else {
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse genesis file {}", path.display()))?
+        };
+
+        let serde_json::Value::Object(mut sections) = value else {
+            bail!("Genesis file {} must contain an object", path.display());
+        };
+        let unknown: Vec<&String> = sections
+            .keys()
+            .filter(|key| !GENESIS_SECTIONS.iter().any(|(section, _)| section == key))
+            .collect();
+        if !unknown.is_empty() {
+            bail!(
+                "Unknown sections {unknown:?} in genesis file {}",
+                path.display()
+            );
+        }
+        for (section, _) in GENESIS_SECTIONS {
+            if !sections.contains_key(section) {
+                if !section.starts_with("ibc") {
+                    bail!(
+                        "Missing section `{section}` in genesis file {}",
+                        path.display()
+                    );
+                }
+                sections.insert(
+                    section.to_string(),
+                    serde_json::Value::Object(Default::default()),
+                );
+            }
+        }
+        Ok(Self { sections })
+    }
+
+    /// The sections as compact JSON with sorted object keys, which is the same for both layouts and any formatting
+    /// of the files.
+    pub fn to_canonical_json(&self) -> Vec<u8> {
+        let mut value = serde_json::Value::Object(self.sections.clone());
+        sort_keys(&mut value);
+        serde_json::to_vec(&value).expect("JSON values are serializable")
+    }
+
+    /// Writes the sections to a single genesis file.
+    pub fn write(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let contents = if is_toml(path) {
+            // TOML has no null: unset optional fields are left out instead
+            let mut value = serde_json::Value::Object(self.sections.clone());
+            remove_nulls(&mut value);
+            toml::to_string_pretty(&value)?
+        } else {
+            serde_json::to_string_pretty(&self.sections)? + "\n"
+        };
+        std::fs::write(path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "toml")
+}
+
+fn remove_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            object.retain(|_, value| !value.is_null());
+            object.values_mut().for_each(remove_nulls);
+        }
+        serde_json::Value::Array(array) => array.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+fn sort_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            let mut entries: Vec<_> = std::mem::take(object).into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, mut value) in entries {
+                sort_keys(&mut value);
+                object.insert(key, value);
+            }
+        }
+        serde_json::Value::Array(array) => array.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
 }
@@ -0,0 +1,338 @@
+//! Export of the rollup state to new genesis files, for `rollup export-genesis`.
+//!
+//! The state maps of the modules cannot be enumerated, so the exported addresses and tokens are the ones of the
+//! genesis the rollup started from, plus every address and token indexed by [`crate::tx_index`]. The export fails
+//! if part of the supply of an exported token is held by other addresses than these and the modules whose balances
+//! the exported genesis re-creates: the bonds of `sequencer_registry` and `prover_incentives`, and the escrows of
+//! `ibc_transfer`. The exported files keep the format read by [`crate::genesis_config::create_genesis_config`]:
+//! - `accounts.json`: the accounts of the source genesis. Credentials registered later are not exported.
+//! - `bank.json`: the balances of the gas token and of every other token. Tokens created after genesis keep their
+//!   name but get a new ID on re-import, and their authorized minters are not exported.
+//! - `sequencer_registry.json`: the sequencer of the source genesis. Its stake is re-bonded from its balance, so a
+//!   stake above the `minimum_bond` is partly returned to the balance on re-import.
+//! - `prover_incentives.json`: the bonded provers and their bonds.
+//!
+//! The other module genesis files are copied from the source genesis. The escrows of `ibc_transfer.json` are funded
+//! again on re-import, so they are added back to the balances of their funders, and the export fails if the escrows
+//! held by `ibc_transfer` changed since genesis.
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use serde::Serialize;
+use sov_accounts::AccountConfig;
+use sov_bank::{BankConfig, TokenConfig, TokenId};
+use sov_modules_api::{ApiStateAccessor, DaSpec, ModuleInfo, Spec};
+use sov_prover_incentives::ProverIncentivesConfig;
+use sov_sequencer_registry::SequencerConfig;
+use sov_stf_runner::read_json_file;
+
+use crate::genesis_config::{read_ibc_json_file, GenesisPaths};
+use crate::ibc_genesis::IbcTransferConfig;
+use crate::runtime::Runtime;
+
+/// The module genesis configs exported from the state.
+pub struct ExportedGenesis<S: Spec, Da: DaSpec> {
+    /// The `accounts` genesis.
+    pub accounts: AccountConfig<S>,
+    /// The `bank` genesis.
+    pub bank: BankConfig<S>,
+    /// The `sequencer_registry` genesis.
+    pub sequencer_registry: SequencerConfig<S, Da>,
+    /// The `prover_incentives` genesis.
+    pub prover_incentives: ProverIncentivesConfig<S>,
+}
+
+/// Exports the state read through `state` (e.g. at an archival version), starting from the genesis at `source`.
+pub fn export_genesis<S: Spec, Da: DaSpec>(
+    source: &GenesisPaths,
+    state: &mut ApiStateAccessor<S>,
+) -> anyhow::Result<ExportedGenesis<S, Da>> {
+    let runtime = Runtime::<S, Da>::default();
+    let accounts: AccountConfig<S> = read_json_file(&source.accounts_genesis_path)?;
+    let mut bank: BankConfig<S> = read_json_file(&source.bank_genesis_path)?;
+    let sequencer_registry: SequencerConfig<S, Da> =
+        read_json_file(&source.sequencer_genesis_path)?;
+    let mut prover_incentives: ProverIncentivesConfig<S> =
+        read_json_file(&source.prover_incentives_genesis_path)?;
+    let ibc_transfer: IbcTransferConfig<S> = read_ibc_json_file(&source.ibc_transfer_genesis_path)?;
+
+    // Every address that may hold tokens or a bond, sorted so that the exported files are canonical
+    let mut addresses: Vec<S::Address> = Vec::new();
+    let genesis_addresses = accounts
+        .accounts
+        .iter()
+        .map(|account| account.address.clone())
+        .chain(
+            bank.gas_token_config
+                .address_and_balances
+                .iter()
+                .map(|(address, _)| address.clone()),
+        )
+        .chain(bank.tokens.iter().flat_map(|token| {
+            token
+                .address_and_balances
+                .iter()
+                .map(|(address, _)| address.clone())
+        }))
+        .chain([sequencer_registry.seq_rollup_address.clone()])
+        .chain(
+            prover_incentives
+                .initial_provers
+                .iter()
+                .map(|(address, _)| address.clone()),
+        );
+    for address in genesis_addresses.chain(runtime.tx_index.known_addresses(state)) {
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    addresses.sort_by_key(|address| address.to_string());
+
+    let balances = |token_id: TokenId, state: &mut ApiStateAccessor<S>| {
+        addresses
+            .iter()
+            .filter_map(|address| {
+                let balance = runtime.bank.get_balance_of(address, token_id, state)?;
+                (balance > 0).then(|| (address.clone(), balance))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let Some(sequencer_stake) = runtime
+        .sequencer_registry
+        .get_sender_balance(&sequencer_registry.seq_da_address, state)
+    else {
+        bail!(
+            "The sequencer {} of the source genesis is no longer registered",
+            sequencer_registry.seq_da_address
+        );
+    };
+
+    prover_incentives.initial_provers = addresses
+        .iter()
+        .filter_map(|address| {
+            let bond = runtime.prover_incentives.get_bond_amount(address, state);
+            (bond > 0).then(|| (address.clone(), bond))
+        })
+        .collect();
+
+    // The genesis of the sequencer registry and the prover incentives bonds the sequencer and the provers from
+    // their gas token balances, so the bonds are added back to the balances.
+    let gas_token_id = sov_bank::config_gas_token_id();
+    let mut gas_balances = balances(gas_token_id, state);
+    check_holders(
+        &runtime,
+        &bank.gas_token_config.token_name,
+        gas_token_id,
+        &gas_balances,
+        &ibc_transfer,
+        state,
+    )?;
+    let bonds = prover_incentives.initial_provers.iter().cloned().chain([(
+        sequencer_registry.seq_rollup_address.clone(),
+        sequencer_stake,
+    )]);
+    for (address, bond) in bonds {
+        add_balance(&mut gas_balances, address, bond);
+    }
+    add_escrows(&mut gas_balances, gas_token_id, &ibc_transfer);
+    bank.gas_token_config.address_and_balances = gas_balances;
+
+    // The genesis tokens are matched by name with the indexed tokens, and the tokens created later are appended
+    let mut tokens = bank.tokens.clone();
+    let genesis_token_count = tokens.len();
+    for token_id in runtime.tx_index.known_tokens(state) {
+        if token_id == gas_token_id {
+            continue;
+        }
+        let token_name = runtime
+            .bank
+            .get_token_name(&token_id, state)
+            .with_context(|| format!("Token {token_id} has no name"))?;
+        let mut address_and_balances = balances(token_id, state);
+        check_holders(
+            &runtime,
+            &token_name,
+            token_id,
+            &address_and_balances,
+            &ibc_transfer,
+            state,
+        )?;
+        add_escrows(&mut address_and_balances, token_id, &ibc_transfer);
+        match tokens[..genesis_token_count]
+            .iter_mut()
+            .find(|token| token.token_name == token_name)
+        {
+            Some(token) => token.address_and_balances = address_and_balances,
+            None => tokens.push(TokenConfig {
+                token_name,
+                address_and_balances,
+                authorized_minters: Vec::new(),
+                salt: 0,
+            }),
+        }
+    }
+    bank.tokens = tokens;
+
+    Ok(ExportedGenesis {
+        accounts,
+        bank,
+        sequencer_registry,
+        prover_incentives,
+    })
+}
+
+/// Fails if part of the supply of `token_id` is held by neither the exported `holders` nor a module whose balance the
+/// exported genesis re-creates.
+fn check_holders<S: Spec, Da: DaSpec>(
+    runtime: &Runtime<S, Da>,
+    token_name: &str,
+    token_id: TokenId,
+    holders: &[(S::Address, u64)],
+    ibc_transfer: &IbcTransferConfig<S>,
+    state: &mut ApiStateAccessor<S>,
+) -> anyhow::Result<()> {
+    let supply = runtime
+        .bank
+        .get_total_supply_of(&token_id, state)
+        .unwrap_or_default();
+    let mut held_by_modules = 0u64;
+    for (module, address) in module_accounts(runtime) {
+        let balance = runtime
+            .bank
+            .get_balance_of(&address, token_id, state)
+            .unwrap_or_default();
+        if balance == 0 {
+            continue;
+        }
+        match module {
+            // The genesis of the sequencer registry and the prover incentives bonds again from the balances
+            "sequencer_registry" | "prover_incentives" => {}
+            "ibc_transfer" => {
+                let escrowed = escrowed_amount(ibc_transfer, token_id);
+                if balance != escrowed {
+                    bail!(
+                        "The `ibc_transfer` module escrows {balance} `{token_name}` tokens ({token_id}) but \
+                         ibc_transfer.json escrows {escrowed}, so its escrows cannot be exported"
+                    );
+                }
+            }
+            _ => bail!(
+                "The `{module}` module holds {balance} `{token_name}` tokens ({token_id}), which cannot be exported"
+            ),
+        }
+        held_by_modules += balance;
+    }
+    let exported: u64 = holders.iter().map(|(_, balance)| balance).sum();
+    let unknown = supply.saturating_sub(exported.saturating_add(held_by_modules));
+    if unknown > 0 {
+        bail!(
+            "{unknown} of the {supply} `{token_name}` tokens ({token_id}) are held by addresses unknown to the \
+             transaction index, so they cannot be exported"
+        );
+    }
+    Ok(())
+}
+
+/// The accounts of the modules of the runtime, by module name.
+fn module_accounts<S: Spec, Da: DaSpec>(
+    runtime: &Runtime<S, Da>,
+) -> Vec<(&'static str, S::Address)> {
+    [
+        ("accounts", runtime.accounts.id()),
+        ("bank", runtime.bank.id()),
+        ("ibc", runtime.ibc.id()),
+        ("ibc_transfer", runtime.ibc_transfer.id()),
+        ("sequencer_registry", runtime.sequencer_registry.id()),
+        ("prover_incentives", runtime.prover_incentives.id()),
+        ("evm", runtime.evm.id()),
+        ("chain_info", runtime.chain_info.id()),
+        ("tx_index", runtime.tx_index.id()),
+        ("circuit_breaker", runtime.circuit_breaker.id()),
+        ("sequencer_outcomes", runtime.sequencer_outcomes.id()),
+    ]
+    .into_iter()
+    .map(|(module, id)| (module, id.to_address::<S::Address>()))
+    .collect()
+}
+
+/// The amount of `token_id` escrowed by `ibc_transfer.json`.
+fn escrowed_amount<S: Spec>(ibc_transfer: &IbcTransferConfig<S>, token_id: TokenId) -> u64 {
+    ibc_transfer
+        .escrows
+        .iter()
+        .filter(|escrow| escrow.token_id == token_id)
+        .map(|escrow| escrow.amount)
+        .sum()
+}
+
+/// Adds the escrows of `token_id` back to the balances of their funders, who fund them again on re-import.
+fn add_escrows<S: Spec>(
+    balances: &mut Vec<(S::Address, u64)>,
+    token_id: TokenId,
+    ibc_transfer: &IbcTransferConfig<S>,
+) {
+    for escrow in &ibc_transfer.escrows {
+        if escrow.token_id == token_id {
+            add_balance(balances, escrow.from.clone(), escrow.amount);
+        }
+    }
+}
+
+/// Adds `amount` to the balance of `address`, keeping the balances sorted.
+fn add_balance<Address: Clone + PartialEq + std::fmt::Display>(
+    balances: &mut Vec<(Address, u64)>,
+    address: Address,
+    amount: u64,
+) {
+    match balances.iter_mut().find(|(holder, _)| *holder == address) {
+        Some((_, balance)) => *balance += amount,
+        None => {
+            balances.push((address, amount));
+            balances.sort_by_key(|(address, _)| address.to_string());
+        }
+    }
+}
+
+impl<S: Spec, Da: DaSpec> ExportedGenesis<S, Da> {
+    /// Writes the exported files to `output_dir`, and copies the other module genesis files from `source`.
+    pub fn write_to_dir(&self, source: &GenesisPaths, output_dir: &Path) -> anyhow::Result<()> {
+        std::fs::create_dir_all(output_dir)
+            .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+        let output = GenesisPaths::from_dir(output_dir);
+
+        write_json(&output.accounts_genesis_path, &self.accounts)?;
+        write_json(&output.bank_genesis_path, &self.bank)?;
+        write_json(&output.sequencer_genesis_path, &self.sequencer_registry)?;
+        write_json(
+            &output.prover_incentives_genesis_path,
+            &self.prover_incentives,
+        )?;
+
+        for (from, to) in [
+            (&source.ibc_genesis_path, &output.ibc_genesis_path),
+            (
+                &source.ibc_transfer_genesis_path,
+                &output.ibc_transfer_genesis_path,
+            ),
+            (&source.evm_genesis_path, &output.evm_genesis_path),
+            (
+                &source.circuit_breaker_genesis_path,
+                &output.circuit_breaker_genesis_path,
+            ),
+        ] {
+            // The IBC genesis files are optional
+            if from.exists() {
+                std::fs::copy(from, to).with_context(|| {
+                    format!("Failed to copy {} to {}", from.display(), to.display())
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> anyhow::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)? + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,263 @@
+//! Validation of the genesis files, for `rollup genesis validate`.
+//!
+//! Every module config is parsed, and the configs are checked against each other, collecting all the errors
+//! instead of stopping at the first one. Errors point to the file and the JSON path of the offending value.
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use sov_accounts::AccountConfig;
+use sov_bank::BankConfig;
+use sov_chain_state::ChainStateConfig;
+use sov_evm::EvmConfig;
+use sov_modules_api::{DaSpec, Spec};
+use sov_prover_incentives::ProverIncentivesConfig;
+use sov_sequencer_registry::SequencerConfig;
+
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::genesis_config::GenesisPaths;
+use crate::ibc_genesis::{IbcConfig, IbcTransferConfig};
+
+/// An error in a genesis file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenesisError {
+    /// The genesis file.
+    pub file: PathBuf,
+    /// The JSON path of the offending value, e.g. `$.initial_provers[0][1]`.
+    pub path: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+impl Display for GenesisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}: {}",
+            self.file.display(),
+            self.path,
+            self.message
+        )
+    }
+}
+
+/// Validates the runtime genesis files and the kernel `chain_state` genesis file.
+///
+/// If `genesis_height` is given, it is the `runner.genesis_height` of the rollup config, which the
+/// `genesis_da_height` of the chain state must match. Returns every error found.
+pub fn validate_genesis<S: Spec, Da: DaSpec>(
+    genesis_paths: &GenesisPaths,
+    chain_state_path: &Path,
+    genesis_height: Option<u64>,
+) -> Vec<GenesisError> {
+    let mut validator = Validator::default();
+
+    let accounts: Option<AccountConfig<S>> = validator.parse(&genesis_paths.accounts_genesis_path);
+    let bank: Option<BankConfig<S>> = validator.parse(&genesis_paths.bank_genesis_path);
+    // The IBC modules start with an empty config if their file is missing
+    let ibc: Option<IbcConfig> = if genesis_paths.ibc_genesis_path.exists() {
+        validator.parse(&genesis_paths.ibc_genesis_path)
+    } else {
+        Some(IbcConfig::default())
+    };
+    let ibc_transfer: Option<IbcTransferConfig<S>> =
+        if genesis_paths.ibc_transfer_genesis_path.exists() {
+            validator.parse(&genesis_paths.ibc_transfer_genesis_path)
+        } else {
+            Some(IbcTransferConfig::default())
+        };
+    let sequencer: Option<SequencerConfig<S, Da>> =
+        validator.parse(&genesis_paths.sequencer_genesis_path);
+    let prover_incentives: Option<ProverIncentivesConfig<S>> =
+        validator.parse(&genesis_paths.prover_incentives_genesis_path);
+    validator.parse::<EvmConfig>(&genesis_paths.evm_genesis_path);
+    validator.parse::<CircuitBreakerConfig<S>>(&genesis_paths.circuit_breaker_genesis_path);
+    let chain_state: Option<ChainStateConfig<S>> = validator.parse(chain_state_path);
+
+    if let Some(ibc) = &ibc {
+        for (path, message) in ibc.errors() {
+            validator.error(&genesis_paths.ibc_genesis_path, path, message);
+        }
+        if let Some(ibc_transfer) = &ibc_transfer {
+            for (path, message) in ibc_transfer.errors(ibc) {
+                validator.error(&genesis_paths.ibc_transfer_genesis_path, path, message);
+            }
+        }
+    }
+
+    if let Some(accounts) = &accounts {
+        validator.check_accounts(&genesis_paths.accounts_genesis_path, accounts);
+    }
+    if let Some(bank) = &bank {
+        validator.check_minters(&genesis_paths.bank_genesis_path, bank);
+
+        // An address can bond as the sequencer and as provers, its balance must cover all of its bonds
+        let mut bonds: HashMap<&S::Address, u64> = HashMap::new();
+        if let Some(sequencer) = &sequencer {
+            *bonds.entry(&sequencer.seq_rollup_address).or_default() = sequencer.minimum_bond;
+        }
+        if let Some(prover_incentives) = &prover_incentives {
+            for (prover, bond) in &prover_incentives.initial_provers {
+                let total = bonds.entry(prover).or_default();
+                *total = total.saturating_add(*bond);
+            }
+        }
+
+        if let Some(sequencer) = &sequencer {
+            let address = &sequencer.seq_rollup_address;
+            let balance = gas_token_balance(bank, address);
+            let bonded = bonds[address];
+            if balance < bonded {
+                validator.error(
+                    &genesis_paths.sequencer_genesis_path,
+                    "$.seq_rollup_address",
+                    format!(
+                        "The sequencer {address} has a gas token balance of {balance} in {}, below its bonds of {bonded} (the `minimum_bond` of {} and its prover bonds)",
+                        genesis_paths.bank_genesis_path.display(),
+                        sequencer.minimum_bond
+                    ),
+                );
+            }
+        }
+
+        if let Some(prover_incentives) = &prover_incentives {
+            for (i, (prover, bond)) in prover_incentives.initial_provers.iter().enumerate() {
+                if *bond < prover_incentives.minimum_bond {
+                    validator.error(
+                        &genesis_paths.prover_incentives_genesis_path,
+                        format!("$.initial_provers[{i}][1]"),
+                        format!(
+                            "The bond {bond} of the prover {prover} is below the `minimum_bond` of {}",
+                            prover_incentives.minimum_bond
+                        ),
+                    );
+                }
+                let balance = gas_token_balance(bank, prover);
+                let bonded = bonds[prover];
+                if balance < bonded {
+                    validator.error(
+                        &genesis_paths.prover_incentives_genesis_path,
+                        format!("$.initial_provers[{i}][0]"),
+                        format!(
+                            "The prover {prover} has a gas token balance of {balance} in {}, below its bonds of {bonded}",
+                            genesis_paths.bank_genesis_path.display()
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    if let (Some(chain_state), Some(genesis_height)) = (&chain_state, genesis_height) {
+        if chain_state.genesis_da_height != genesis_height {
+            validator.error(
+                chain_state_path,
+                "$.genesis_da_height",
+                format!(
+                    "The genesis DA height {} does not match the `runner.genesis_height` {genesis_height} of the rollup config",
+                    chain_state.genesis_da_height
+                ),
+            );
+        }
+    }
+
+    validator.errors
+}
+
+/// Returns the gas token balance of `address` in the bank genesis.
+fn gas_token_balance<S: Spec>(bank: &BankConfig<S>, address: &S::Address) -> u64 {
+    bank.gas_token_config
+        .address_and_balances
+        .iter()
+        .filter(|(holder, _)| holder == address)
+        .fold(0u64, |total, (_, balance)| total.saturating_add(*balance))
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<GenesisError>,
+}
+
+impl Validator {
+    fn error(&mut self, file: &Path, path: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(GenesisError {
+            file: file.to_path_buf(),
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Reads and parses a genesis file, recording the JSON path of any parsing error.
+    fn parse<T: DeserializeOwned>(&mut self, file: &Path) -> Option<T> {
+        let contents = match std::fs::read_to_string(file) {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.error(file, "$", format!("Failed to read the file: {e}"));
+                return None;
+            }
+        };
+
+        let deserializer = &mut serde_json::Deserializer::from_str(&contents);
+        match serde_path_to_error::deserialize(deserializer) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                let path = match e.path().to_string().as_str() {
+                    "." => "$".to_string(),
+                    path => format!("$.{path}"),
+                };
+                self.error(file, path, e.into_inner().to_string());
+                None
+            }
+        }
+    }
+
+    fn check_accounts<S: Spec>(&mut self, file: &Path, accounts: &AccountConfig<S>) {
+        let mut credentials = HashSet::new();
+        let mut addresses = HashSet::new();
+        for (i, account) in accounts.accounts.iter().enumerate() {
+            if !credentials.insert(&account.credential_id) {
+                self.error(
+                    file,
+                    format!("$.accounts[{i}].credential_id"),
+                    format!("Duplicate credential ID {:?}", account.credential_id),
+                );
+            }
+            if !addresses.insert(&account.address) {
+                self.error(
+                    file,
+                    format!("$.accounts[{i}].address"),
+                    format!("Duplicate address {}", account.address),
+                );
+            }
+        }
+    }
+
+    /// Checks that the authorized minters of every token are listed once.
+    /// Their addresses were already checked when the file was parsed.
+    fn check_minters<S: Spec>(&mut self, file: &Path, bank: &BankConfig<S>) {
+        let tokens = std::iter::once((
+            "$.gas_token_config".to_string(),
+            &bank.gas_token_config.authorized_minters,
+        ))
+        .chain(
+            bank.tokens
+                .iter()
+                .enumerate()
+                .map(|(i, token)| (format!("$.tokens[{i}]"), &token.authorized_minters)),
+        );
+
+        for (token_path, minters) in tokens {
+            let mut seen = HashSet::new();
+            for (i, minter) in minters.iter().enumerate() {
+                if !seen.insert(minter) {
+                    self.error(
+                        file,
+                        format!("{token_path}.authorized_minters[{i}]"),
+                        format!("Duplicate authorized minter {minter}"),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -5,18 +5,64 @@
 //! - At the beginning and end of each slot (DA layer block)
 use sov_modules_api::batch::BatchWithId;
 use sov_modules_api::hooks::{ApplyBatchHooks, FinalizeHook, SlotHooks, TxHooks};
-use sov_modules_api::{Spec, StateCheckpoint, StateReaderAndWriter, WorkingSet};
+use sov_modules_api::transaction::AuthenticatedTransactionData;
+use sov_modules_api::{Context, Spec, StateCheckpoint, StateReaderAndWriter, WorkingSet};
 use sov_modules_stf_blueprint::BatchSequencerOutcome;
 use sov_rollup_interface::da::DaSpec;
 use sov_sequencer_registry::SequencerRegistry;
 use sov_state::namespaces::Accessory;
-use tracing::info;
 
 use super::runtime::Runtime;
+use crate::authentication::authenticate_unmetered;
+use crate::spans;
+use crate::tx_index::{bank_created_tokens, bank_mints, bank_transfers, PendingTx};
 
 impl<S: Spec, Da: DaSpec> TxHooks for Runtime<S, Da> {
     type Spec = S;
     type TxState = WorkingSet<S>;
+
+    fn pre_dispatch_tx_hook(
+        &self,
+        _tx: &AuthenticatedTransactionData<S>,
+        _working_set: &mut WorkingSet<S>,
+    ) -> anyhow::Result<()> {
+        spans::enter_tx();
+        Ok(())
+    }
+
+    fn post_dispatch_tx_hook(
+        &self,
+        tx: &AuthenticatedTransactionData<S>,
+        ctx: &Context<S>,
+        working_set: &mut WorkingSet<S>,
+    ) -> anyhow::Result<()> {
+        // Queue the transaction for the accessory index, which is built in `finalize_hook`
+        let tx_hash = self
+            .tx_index
+            .applied_tx_hash(tx, &mut working_set.accessory_state());
+        match tx_hash {
+            Some(tx_hash) => {
+                let pending = PendingTx {
+                    slot_number: self
+                        .chain_info
+                        .latest_slot(working_set)
+                        .map(|slot| slot.slot_number)
+                        .unwrap_or_default(),
+                    tx_hash,
+                    sender: ctx.sender().clone(),
+                    transfers: bank_transfers(working_set),
+                    created_tokens: bank_created_tokens(working_set),
+                    mints: bank_mints(working_set),
+                };
+                self.tx_index.queue_tx(pending, working_set);
+            }
+            None => {
+                tracing::warn!("The hash of the applied transaction is unknown, it is not indexed")
+            }
+        }
+        spans::exit_tx();
+        Ok(())
+    }
 }
 
 impl<S: Spec, Da: DaSpec> ApplyBatchHooks<Da> for Runtime<S, Da> {
@@ -29,9 +75,28 @@ impl<S: Spec, Da: DaSpec> ApplyBatchHooks<Da> for Runtime<S, Da> {
         sender: &Da::Address,
         state_checkpoint: &mut StateCheckpoint<S>,
     ) -> anyhow::Result<()> {
-        // Before executing each batch, check that the sender is registered as a sequencer
-        self.sequencer_registry
+        spans::enter_batch(&batch.id, sender);
+        // Before executing each batch, check that the sender is registered as a sequencer. A rejected batch is
+        // skipped without `end_batch_hook`, which would close its span
+        if let Err(e) = self
+            .sequencer_registry
             .begin_batch_hook(batch, sender, state_checkpoint)
+        {
+            spans::exit_batch();
+            return Err(e);
+        }
+        // Drop the transactions paused by the circuit breaker before they are authenticated
+        self.circuit_breaker
+            .drop_paused_txs::<Da>(batch, state_checkpoint);
+        // The transaction hooks don't get the hash of the transaction, which the index finds among the batch
+        let txs = batch
+            .txs
+            .iter()
+            .filter_map(authenticate_unmetered::<S, Da>)
+            .collect();
+        self.tx_index
+            .begin_batch(txs, &mut state_checkpoint.accessory_state());
+        Ok(())
     }
 
     fn end_batch_hook(
@@ -44,25 +109,34 @@ impl<S: Spec, Da: DaSpec> ApplyBatchHooks<Da> for Runtime<S, Da> {
         // convert the `SequencerOutcome` structures manually.
         match result {
             BatchSequencerOutcome::Rewarded(amount) => {
-                info!(%sender, ?amount, "Rewarding sequencer");
+                let amount: u64 = amount.into();
                 <SequencerRegistry<S, Da> as ApplyBatchHooks<Da>>::end_batch_hook(
                     &self.sequencer_registry,
-                    sov_sequencer_registry::SequencerOutcome::Rewarded(amount.into()),
+                    sov_sequencer_registry::SequencerOutcome::Rewarded(amount),
                     sender,
                     state_checkpoint,
                 );
+                self.sequencer_outcomes
+                    .sequencer_rewarded(sender, amount, state_checkpoint);
             }
             BatchSequencerOutcome::Ignored => {}
             BatchSequencerOutcome::Slashed(reason) => {
-                info!(%sender, ?reason, "Slashing sequencer");
+                // The registry takes the whole stake of a slashed sequencer, so read it first
+                let stake = self
+                    .sequencer_registry
+                    .get_sender_balance(sender, state_checkpoint)
+                    .unwrap_or_default();
                 <SequencerRegistry<S, Da> as ApplyBatchHooks<Da>>::end_batch_hook(
                     &self.sequencer_registry,
                     sov_sequencer_registry::SequencerOutcome::Slashed,
                     sender,
                     state_checkpoint,
                 );
+                self.sequencer_outcomes
+                    .sequencer_slashed(sender, &reason, stake, state_checkpoint);
             }
         }
+        spans::exit_batch();
     }
 }
 
@@ -71,14 +145,26 @@ impl<S: Spec, Da: DaSpec> SlotHooks for Runtime<S, Da> {
 
     fn begin_slot_hook(
         &self,
-        _pre_state_root: <Self::Spec as Spec>::VisibleHash,
-        _versioned_state_checkpoint: &mut sov_modules_api::VersionedStateReadWriter<
+        pre_state_root: <Self::Spec as Spec>::VisibleHash,
+        versioned_state_checkpoint: &mut sov_modules_api::VersionedStateReadWriter<
             StateCheckpoint<Self::Spec>,
         >,
     ) {
+        // Record the DA block of the slot before any module reads the current height or time
+        self.chain_info
+            .begin_slot_hook(&pre_state_root, versioned_state_checkpoint);
+        if let Some(slot) = self.chain_info.latest_slot(versioned_state_checkpoint) {
+            spans::enter_slot(slot.slot_number, slot.da_height);
+        }
+        // The EVM opens a new Ethereum block at the beginning of each slot
+        self.evm
+            .begin_slot_hook(pre_state_root, versioned_state_checkpoint);
     }
 
-    fn end_slot_hook(&self, _state_checkpoint: &mut StateCheckpoint<S>) {}
+    fn end_slot_hook(&self, state_checkpoint: &mut StateCheckpoint<S>) {
+        self.evm.end_slot_hook(state_checkpoint);
+        spans::exit_slot();
+    }
 }
 
 impl<S: Spec, Da: DaSpec> FinalizeHook for Runtime<S, Da> {
@@ -86,8 +172,12 @@ impl<S: Spec, Da: DaSpec> FinalizeHook for Runtime<S, Da> {
 
     fn finalize_hook(
         &self,
-        #[allow(unused_variables)] root_hash: S::VisibleHash,
-        #[allow(unused_variables)] accessory_state: &mut impl StateReaderAndWriter<Accessory>,
+        root_hash: S::VisibleHash,
+        accessory_state: &mut impl StateReaderAndWriter<Accessory>,
     ) {
+        // The EVM stores the sealed Ethereum blocks and receipts in the accessory state
+        self.evm.finalize_hook(root_hash, accessory_state);
+        // Index the transactions of the slot by address and token
+        self.tx_index.index_pending_txs(accessory_state);
     }
 }
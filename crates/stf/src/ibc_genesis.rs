@@ -0,0 +1,364 @@
+//! The IBC state of the genesis: light clients, connections, channels and escrow balances, read from `ibc.json` and
+//! `ibc_transfer.json`.
+//!
+//! The `ibc` and `ibc_transfer` modules have no genesis state of their own, so the runtime genesis writes this state
+//! after theirs, through the IBC context of the `ibc` module as a handshake would. The escrows are funded from the
+//! `bank` genesis balances.
+//!
+//! The identifiers of the clients, connections and channels must be numbered from 0 in the order of the files, e.g.
+//! `connection-0`, `connection-1`, so that the IBC counters continue after them.
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use anyhow::{anyhow, Context as _};
+use ibc_core::channel::types::channel::ChannelEnd;
+use ibc_core::channel::types::packet::Sequence;
+use ibc_core::client::context::ClientExecutionContext;
+use ibc_core::client::types::Height;
+use ibc_core::connection::types::ConnectionEnd;
+use ibc_core::host::types::identifiers::{ChannelId, ClientId, ConnectionId, PortId};
+use ibc_core::host::types::path::{
+    ChannelEndPath, ClientConnectionPath, ClientConsensusStatePath, ClientStatePath,
+    ConnectionPath, SeqAckPath, SeqRecvPath, SeqSendPath,
+};
+use ibc_core::host::ExecutionContext;
+use ibc_primitives::proto::Any;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sov_bank::{Bank, Coins, TokenId};
+use sov_ibc::clients::{AnyClientState, AnyConsensusState};
+use sov_ibc::context::IbcContext;
+use sov_ibc::Ibc;
+use sov_ibc_transfer::IbcTransfer;
+use sov_modules_api::{ModuleInfo, Spec, WorkingSet};
+
+/// The port of the `ibc_transfer` module, which holds the escrows.
+pub const TRANSFER_PORT: &str = "transfer";
+
+/// A protobuf `Any`, with its value in hex.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncodedAny {
+    /// The type URL, e.g. `/ibc.lightclients.tendermint.v1.ClientState`.
+    pub type_url: String,
+    /// The protobuf encoding of the value, in hex.
+    pub value: String,
+}
+
+impl EncodedAny {
+    fn decode(&self) -> anyhow::Result<Any> {
+        Ok(Any {
+            type_url: self.type_url.clone(),
+            value: hex::decode(self.value.trim_start_matches("0x"))
+                .context("The value is not valid hex")?,
+        })
+    }
+}
+
+/// A consensus state of a genesis light client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConsensusStateGenesis {
+    /// The height of the counterparty chain.
+    pub height: Height,
+    /// The encoded consensus state.
+    pub consensus_state: EncodedAny,
+}
+
+/// A light client created at genesis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientGenesis {
+    /// The identifier of the client, e.g. `07-tendermint-0`.
+    pub client_id: ClientId,
+    /// The encoded client state.
+    pub client_state: EncodedAny,
+    /// The consensus states known to the client.
+    pub consensus_states: Vec<ConsensusStateGenesis>,
+}
+
+/// A connection opened at genesis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionGenesis {
+    /// The identifier of the connection, e.g. `connection-0`.
+    pub connection_id: ConnectionId,
+    /// The connection, whose client is one of the genesis clients.
+    pub connection_end: ConnectionEnd,
+}
+
+/// A channel opened at genesis. Its packet sequences start at 1.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelGenesis {
+    /// The port of the channel, e.g. `transfer`.
+    pub port_id: PortId,
+    /// The identifier of the channel, e.g. `channel-0`.
+    pub channel_id: ChannelId,
+    /// The channel, whose connection is one of the genesis connections.
+    pub channel_end: ChannelEnd,
+}
+
+/// The contents of `ibc.json`. A missing file is an empty config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IbcConfig {
+    /// The light clients.
+    #[serde(default)]
+    pub clients: Vec<ClientGenesis>,
+    /// The connections.
+    #[serde(default)]
+    pub connections: Vec<ConnectionGenesis>,
+    /// The channels.
+    #[serde(default)]
+    pub channels: Vec<ChannelGenesis>,
+}
+
+/// Tokens escrowed at genesis for a channel of the [`TRANSFER_PORT`], as if they had been sent over it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    deny_unknown_fields,
+    bound = "S::Address: Serialize + DeserializeOwned"
+)]
+pub struct EscrowGenesis<S: Spec> {
+    /// The channel the tokens were sent over.
+    pub channel_id: ChannelId,
+    /// The escrowed token.
+    pub token_id: TokenId,
+    /// The escrowed amount.
+    pub amount: u64,
+    /// The account funding the escrow from its `bank` genesis balance.
+    pub from: S::Address,
+}
+
+/// The contents of `ibc_transfer.json`. A missing file is an empty config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    deny_unknown_fields,
+    bound = "S::Address: Serialize + DeserializeOwned"
+)]
+pub struct IbcTransferConfig<S: Spec> {
+    /// The escrow balances.
+    #[serde(default)]
+    pub escrows: Vec<EscrowGenesis<S>>,
+}
+
+impl<S: Spec> Default for IbcTransferConfig<S> {
+    fn default() -> Self {
+        Self { escrows: vec![] }
+    }
+}
+
+/// An error in an IBC genesis file: the JSON path of the offending value, and what is wrong with it.
+pub type ConfigError = (String, String);
+
+impl IbcConfig {
+    /// Checks that the identifiers are numbered in order, and that the connections and channels refer to the
+    /// clients and connections before them.
+    pub fn errors(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        let mut check_number = |path: String, id: &str, expected: usize| {
+            if sequence_number(id) != Some(expected as u64) {
+                errors.push((path, format!("`{id}` must be numbered {expected}")));
+            }
+        };
+        for (i, client) in self.clients.iter().enumerate() {
+            check_number(
+                format!("$.clients[{i}].client_id"),
+                client.client_id.as_str(),
+                i,
+            );
+        }
+        for (i, connection) in self.connections.iter().enumerate() {
+            check_number(
+                format!("$.connections[{i}].connection_id"),
+                connection.connection_id.as_str(),
+                i,
+            );
+        }
+        for (i, channel) in self.channels.iter().enumerate() {
+            check_number(
+                format!("$.channels[{i}].channel_id"),
+                channel.channel_id.as_str(),
+                i,
+            );
+        }
+
+        let clients: HashSet<_> = self
+            .clients
+            .iter()
+            .map(|client| &client.client_id)
+            .collect();
+        for (i, connection) in self.connections.iter().enumerate() {
+            let client_id = connection.connection_end.client_id();
+            if !clients.contains(client_id) {
+                errors.push((
+                    format!("$.connections[{i}].connection_end.client_id"),
+                    format!("Unknown client `{client_id}`"),
+                ));
+            }
+        }
+
+        let connections: HashSet<_> = self
+            .connections
+            .iter()
+            .map(|connection| &connection.connection_id)
+            .collect();
+        for (i, channel) in self.channels.iter().enumerate() {
+            for (hop, connection_id) in channel.channel_end.connection_hops().iter().enumerate() {
+                if !connections.contains(connection_id) {
+                    errors.push((
+                        format!("$.channels[{i}].channel_end.connection_hops[{hop}]"),
+                        format!("Unknown connection `{connection_id}`"),
+                    ));
+                }
+            }
+        }
+        errors
+    }
+}
+
+impl<S: Spec> IbcTransferConfig<S> {
+    /// Checks that the escrows are for channels of the [`TRANSFER_PORT`] in `ibc`, and are not empty.
+    pub fn errors(&self, ibc: &IbcConfig) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
+        for (i, escrow) in self.escrows.iter().enumerate() {
+            let known = ibc.channels.iter().any(|channel| {
+                channel.port_id.as_str() == TRANSFER_PORT && channel.channel_id == escrow.channel_id
+            });
+            if !known {
+                errors.push((
+                    format!("$.escrows[{i}].channel_id"),
+                    format!(
+                        "Unknown channel `{}` of the `{TRANSFER_PORT}` port",
+                        escrow.channel_id
+                    ),
+                ));
+            }
+            if escrow.amount == 0 {
+                errors.push((
+                    format!("$.escrows[{i}].amount"),
+                    "The escrowed amount must not be 0".to_string(),
+                ));
+            }
+        }
+        errors
+    }
+}
+
+/// The number at the end of an IBC identifier, e.g. 3 for `connection-3`.
+fn sequence_number(id: &str) -> Option<u64> {
+    id.rsplit_once('-')?.1.parse().ok()
+}
+
+/// Funds the escrows of `config`, moving their tokens from their funders to the `ibc_transfer` module. Called by the
+/// runtime genesis.
+pub fn fund_escrows<S: Spec>(
+    bank: &Bank<S>,
+    ibc_transfer: &IbcTransfer<S>,
+    config: &IbcTransferConfig<S>,
+    working_set: &mut WorkingSet<S>,
+) -> anyhow::Result<()> {
+    let escrow_address = ibc_transfer.id().to_address::<S::Address>();
+    for escrow in &config.escrows {
+        let coins = Coins {
+            amount: escrow.amount,
+            token_id: escrow.token_id,
+        };
+        bank.transfer_from(&escrow.from, &escrow_address, coins, working_set)
+            .with_context(|| {
+                format!(
+                    "Failed to escrow {} of {} for `{}`",
+                    escrow.amount, escrow.token_id, escrow.channel_id
+                )
+            })?;
+    }
+    Ok(())
+}
+
+/// Stores the clients, connections and channels of `config`, and advances the IBC counters past them. Called by the
+/// runtime genesis.
+pub fn create_ibc_state<S: Spec>(
+    ibc: &Ibc<S>,
+    config: &IbcConfig,
+    working_set: &mut WorkingSet<S>,
+) -> anyhow::Result<()> {
+    let mut context = IbcContext::new(ibc, Rc::new(RefCell::new(working_set)));
+    for client in &config.clients {
+        let client_id = &client.client_id;
+        let client_state = client
+            .client_state
+            .decode()
+            .and_then(|any| AnyClientState::try_from(any).map_err(|e| anyhow!("{e}")))
+            .with_context(|| format!("Invalid client state of `{client_id}`"))?;
+        context
+            .store_client_state(ClientStatePath::new(client_id.clone()), client_state)
+            .map_err(|e| anyhow!("Failed to store the client `{client_id}`: {e}"))?;
+        for consensus in &client.consensus_states {
+            let height = consensus.height;
+            let consensus_state = consensus
+                .consensus_state
+                .decode()
+                .and_then(|any| AnyConsensusState::try_from(any).map_err(|e| anyhow!("{e}")))
+                .with_context(|| {
+                    format!("Invalid consensus state of `{client_id}` at height {height}")
+                })?;
+            let path = ClientConsensusStatePath::new(
+                client_id.clone(),
+                height.revision_number(),
+                height.revision_height(),
+            );
+            context
+                .store_consensus_state(path, consensus_state)
+                .map_err(|e| anyhow!("Failed to store a consensus state of `{client_id}`: {e}"))?;
+        }
+        context
+            .increase_client_counter()
+            .map_err(|e| anyhow!("{e}"))?;
+    }
+
+    for connection in &config.connections {
+        let connection_id = &connection.connection_id;
+        let client_id = connection.connection_end.client_id().clone();
+        context
+            .store_connection(
+                &ConnectionPath::new(connection_id),
+                connection.connection_end.clone(),
+            )
+            .and_then(|()| {
+                context.store_connection_to_client(
+                    &ClientConnectionPath::new(client_id),
+                    connection_id.clone(),
+                )
+            })
+            .map_err(|e| anyhow!("Failed to store the connection `{connection_id}`: {e}"))?;
+        context
+            .increase_connection_counter()
+            .map_err(|e| anyhow!("{e}"))?;
+    }
+
+    for channel in &config.channels {
+        let (port_id, channel_id) = (&channel.port_id, &channel.channel_id);
+        let first = Sequence::from(1);
+        context
+            .store_channel(
+                &ChannelEndPath::new(port_id, channel_id),
+                channel.channel_end.clone(),
+            )
+            .and_then(|()| {
+                context.store_next_sequence_send(&SeqSendPath::new(port_id, channel_id), first)
+            })
+            .and_then(|()| {
+                context.store_next_sequence_recv(&SeqRecvPath::new(port_id, channel_id), first)
+            })
+            .and_then(|()| {
+                context.store_next_sequence_ack(&SeqAckPath::new(port_id, channel_id), first)
+            })
+            .map_err(|e| anyhow!("Failed to store the channel `{port_id}/{channel_id}`: {e}"))?;
+        context
+            .increase_channel_counter()
+            .map_err(|e| anyhow!("{e}"))?;
+    }
+    Ok(())
+}
@@ -1,10 +1,20 @@
 //! The rollup State Transition Function.
 
 pub mod authentication;
+pub mod chain_info;
+pub mod circuit_breaker;
 #[cfg(feature = "native")]
 pub mod genesis_config;
+#[cfg(feature = "native")]
+pub mod genesis_export;
+#[cfg(feature = "native")]
+pub mod genesis_validation;
 pub mod hooks;
+pub mod ibc_genesis;
 pub mod runtime;
+pub mod sequencer_outcomes;
+pub mod spans;
+pub mod tx_index;
 
 pub use runtime::*;
 use sov_modules_stf_blueprint::StfBlueprint;
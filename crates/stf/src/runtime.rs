@@ -2,24 +2,32 @@
 //! This module implements the core logic of the rollup.
 //! To add new functionality to your rollup:
 //!   1. Add a new module dependency to your `Cargo.toml` file
-//!   2. Add the module to the `Runtime` below
+//!   2. Add the module to the `Runtime` below, and its config to the `GenesisConfig` and the `Genesis` implementation
 //!   3. Update `genesis.json` with any additional data required by your new module
 
 use sov_capabilities::StandardProvenRollupCapabilities as StandardCapabilities;
 use sov_modules_api::capabilities::HasCapabilities;
 #[cfg(feature = "native")]
 use sov_modules_api::macros::{expose_rpc, CliWallet};
-use sov_modules_api::{DispatchCall, Event, Genesis, MessageCodec, Spec};
+use sov_modules_api::{
+    DispatchCall, Event, Genesis, MessageCodec, Module, ModuleError, Spec, WorkingSet,
+};
 use sov_rollup_interface::da::DaSpec;
 use sov_sequencer_registry::SequencerStakeMeter;
 
+#[cfg(feature = "native")]
+pub use crate::chain_info::ChainInfoRpcServer;
 #[cfg(feature = "native")]
 use crate::genesis_config::GenesisPaths;
 #[cfg(feature = "native")]
+pub use crate::tx_index::TxIndexRpcServer;
+#[cfg(feature = "native")]
 pub use sov_accounts::AccountsRpcServer;
 #[cfg(feature = "native")]
 pub use sov_bank::BankRpcServer;
 #[cfg(feature = "native")]
+pub use sov_evm::EvmRpcServer;
+#[cfg(feature = "native")]
 pub use sov_ibc::IbcRpcServer;
 #[cfg(feature = "native")]
 pub use sov_ibc_transfer::IbcTransferRpcServer;
@@ -46,8 +54,8 @@ pub use sov_sequencer_registry::SequencerRegistryRpcServer;
 ///
 /// 1. Initialization:
 ///     When a rollup is deployed for the first time, it needs to set its genesis state.
-///     The `#[derive(Genesis)` macro will generate `Runtime::genesis(config)` method which returns
-///     `Storage` with the initialized state.
+///     The `Genesis` implementation below runs the genesis of every module from the [`GenesisConfig`], and
+///     creates the IBC state of `ibc.json` and `ibc_transfer.json` along the genesis of the IBC modules.
 ///
 /// 2. Calls:
 ///     The `Module` interface defines a `call` method which accepts a module-defined type and triggers the specific `module logic.`
@@ -58,7 +66,7 @@ pub use sov_sequencer_registry::SequencerRegistryRpcServer;
 /// `Runtime::decode_call` accepts a serialized call message and returns a type that implements the `DispatchCall` trait.
 ///  The `DispatchCall` implementation (derived by a macro) forwards the message to the appropriate module and executes its `call` method.
 #[cfg_attr(feature = "native", derive(CliWallet), expose_rpc)]
-#[derive(Default, Genesis, DispatchCall, Event, MessageCodec, RuntimeRestApi)]
+#[derive(Default, DispatchCall, Event, MessageCodec, RuntimeRestApi)]
 #[serialization(
     borsh::BorshDeserialize,
     borsh::BorshSerialize,
@@ -78,6 +86,83 @@ pub struct Runtime<S: Spec, Da: DaSpec> {
     pub sequencer_registry: sov_sequencer_registry::SequencerRegistry<S, Da>,
     /// The Prover Incentives module.
     pub prover_incentives: sov_prover_incentives::ProverIncentives<S, Da>,
+    /// The EVM module executes RLP-encoded Ethereum transactions, authenticated by [`crate::authentication::EvmAuth`]
+    #[cfg_attr(feature = "native", cli_skip)]
+    #[rest_api(skip)]
+    pub evm: sov_evm::Evm<S>,
+    /// The chain info module records the DA block of every slot, see [`crate::chain_info`]
+    #[cfg_attr(feature = "native", cli_skip)]
+    pub chain_info: crate::chain_info::ChainInfo<S, Da>,
+    /// The transaction index module indexes transactions and transfers in the accessory state, see [`crate::tx_index`]
+    #[cfg_attr(feature = "native", cli_skip)]
+    #[rest_api(skip)]
+    pub tx_index: crate::tx_index::TxIndex<S>,
+    /// The circuit breaker module lets the admins pause the runtime, a module or a call, see [`crate::circuit_breaker`]
+    pub circuit_breaker: crate::circuit_breaker::CircuitBreaker<S>,
+    /// The sequencer outcomes module reports the reward or slashing of the sequencer of every batch, see [`crate::sequencer_outcomes`]
+    #[cfg_attr(feature = "native", cli_skip)]
+    #[rest_api(skip)]
+    pub sequencer_outcomes: crate::sequencer_outcomes::SequencerOutcomes<S, Da>,
+}
+
+/// The genesis configuration of the [`Runtime`], with a field per module.
+pub struct GenesisConfig<S: Spec, Da: DaSpec> {
+    /// The `accounts` genesis.
+    pub accounts: sov_accounts::AccountConfig<S>,
+    /// The `bank` genesis.
+    pub bank: sov_bank::BankConfig<S>,
+    /// The `ibc` genesis: the light clients, connections and channels of `ibc.json`.
+    pub ibc: crate::ibc_genesis::IbcConfig,
+    /// The `ibc_transfer` genesis: the escrows of `ibc_transfer.json`.
+    pub ibc_transfer: crate::ibc_genesis::IbcTransferConfig<S>,
+    /// The `sequencer_registry` genesis.
+    pub sequencer_registry: sov_sequencer_registry::SequencerConfig<S, Da>,
+    /// The `prover_incentives` genesis.
+    pub prover_incentives: sov_prover_incentives::ProverIncentivesConfig<S>,
+    /// The `evm` genesis.
+    pub evm: sov_evm::EvmConfig,
+    /// The `circuit_breaker` genesis.
+    pub circuit_breaker: crate::circuit_breaker::CircuitBreakerConfig<S>,
+}
+
+impl<S: Spec, Da: DaSpec> Genesis for Runtime<S, Da> {
+    type Spec = S;
+    type Config = GenesisConfig<S, Da>;
+
+    fn genesis(
+        &self,
+        config: &Self::Config,
+        working_set: &mut WorkingSet<S>,
+    ) -> Result<(), ModuleError> {
+        self.accounts.genesis(&config.accounts, working_set)?;
+        self.bank.genesis(&config.bank, working_set)?;
+        // The IBC modules have no genesis state of their own, so the IBC state of the genesis files is created
+        // after theirs. The escrows are funded from the `bank` genesis balances
+        self.ibc
+            .genesis(&sov_ibc::ExampleModuleConfig {}, working_set)?;
+        crate::ibc_genesis::create_ibc_state(&self.ibc, &config.ibc, working_set)
+            .map_err(ModuleError::ModuleError)?;
+        self.ibc_transfer
+            .genesis(&sov_ibc_transfer::TransferConfig {}, working_set)?;
+        crate::ibc_genesis::fund_escrows(
+            &self.bank,
+            &self.ibc_transfer,
+            &config.ibc_transfer,
+            working_set,
+        )
+        .map_err(ModuleError::ModuleError)?;
+        self.sequencer_registry
+            .genesis(&config.sequencer_registry, working_set)?;
+        self.prover_incentives
+            .genesis(&config.prover_incentives, working_set)?;
+        self.evm.genesis(&config.evm, working_set)?;
+        self.chain_info.genesis(&(), working_set)?;
+        self.tx_index.genesis(&(), working_set)?;
+        self.circuit_breaker
+            .genesis(&config.circuit_breaker, working_set)?;
+        self.sequencer_outcomes.genesis(&(), working_set)?;
+        Ok(())
+    }
 }
 
 impl<S, Da> sov_modules_stf_blueprint::Runtime<S, Da> for Runtime<S, Da>
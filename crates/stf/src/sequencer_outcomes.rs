@@ -0,0 +1,121 @@
+//! The `sequencer_outcomes` module reports the reward or the slashing of the sequencer of every batch as typed
+//! runtime events. The rollup node counts them per sequencer from the ledger (see `sov_rollup_starter::metrics`),
+//! since the STF also runs in the prover and in the sequencer simulations.
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use sov_modules_api::{
+    CallResponse, Context, DaSpec, Module, ModuleError, ModuleId, ModuleInfo, NotInstantiable,
+    Spec, StateCheckpoint, WorkingSet,
+};
+use sov_modules_stf_blueprint::SlashingReason;
+
+/// An event of the [`SequencerOutcomes`].
+#[cfg_attr(feature = "native", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub enum Event {
+    /// The sequencer of a batch was rewarded.
+    SequencerRewarded {
+        /// The DA address of the sequencer.
+        sequencer: String,
+        /// The reward.
+        amount: u64,
+    },
+    /// The sequencer of a batch was slashed.
+    SequencerSlashed {
+        /// The DA address of the sequencer.
+        sequencer: String,
+        /// Why the sequencer was slashed, named by [`slashing_reason_name`].
+        reason: String,
+        /// The stake of the sequencer when it was slashed.
+        amount: u64,
+    },
+}
+
+/// Returns the name of a slashing `reason`, from a fixed set so that it can label the metrics.
+pub fn slashing_reason_name(reason: &SlashingReason) -> &'static str {
+    match reason {
+        SlashingReason::InvalidBatchEncoding => "invalid_batch_encoding",
+        SlashingReason::StatelessVerificationFailed => "stateless_verification_failed",
+        SlashingReason::InvalidTransactionEncoding => "invalid_transaction_encoding",
+    }
+}
+
+/// Reports the outcome of every batch for its sequencer. It has no call messages: the outcomes are reported by the
+/// runtime `end_batch_hook`.
+#[derive(Clone, ModuleInfo)]
+pub struct SequencerOutcomes<S: Spec, Da: DaSpec> {
+    /// The address of the module.
+    #[id]
+    pub id: ModuleId,
+
+    #[phantom]
+    phantom: core::marker::PhantomData<(S, Da)>,
+}
+
+impl<S: Spec, Da: DaSpec> Module for SequencerOutcomes<S, Da> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = NotInstantiable;
+
+    type Event = Event;
+
+    fn genesis(
+        &self,
+        _config: &Self::Config,
+        _working_set: &mut WorkingSet<S>,
+    ) -> Result<(), ModuleError> {
+        Ok(())
+    }
+
+    fn call(
+        &self,
+        msg: Self::CallMessage,
+        _context: &Context<Self::Spec>,
+        _working_set: &mut WorkingSet<S>,
+    ) -> Result<CallResponse, ModuleError> {
+        match msg {}
+    }
+}
+
+impl<S: Spec, Da: DaSpec> SequencerOutcomes<S, Da> {
+    /// Reports that `sequencer` was rewarded `amount`.
+    pub fn sequencer_rewarded(
+        &self,
+        sequencer: &Da::Address,
+        amount: u64,
+        state_checkpoint: &mut StateCheckpoint<S>,
+    ) {
+        tracing::info!(%sequencer, amount, "Rewarding sequencer");
+
+        self.emit_event(
+            state_checkpoint,
+            Event::SequencerRewarded {
+                sequencer: sequencer.to_string(),
+                amount,
+            },
+        );
+    }
+
+    /// Reports that `sequencer` was slashed its stake of `amount` for `reason`.
+    pub fn sequencer_slashed(
+        &self,
+        sequencer: &Da::Address,
+        reason: &SlashingReason,
+        amount: u64,
+        state_checkpoint: &mut StateCheckpoint<S>,
+    ) {
+        let reason = slashing_reason_name(reason);
+        tracing::info!(%sequencer, reason, amount, "Slashing sequencer");
+
+        self.emit_event(
+            state_checkpoint,
+            Event::SequencerSlashed {
+                sequencer: sequencer.to_string(),
+                reason: reason.to_string(),
+                amount,
+            },
+        );
+    }
+}
@@ -0,0 +1,99 @@
+//! Tracing spans over the processing of a slot, opened and closed by the runtime hooks.
+//!
+//! The STF calls the hooks of a slot one after the other on the same thread, so the spans are kept in
+//! thread-locals between the hooks: a `slot` span from `begin_slot_hook` to `end_slot_hook`, a `batch` span from
+//! `begin_batch_hook` to `end_batch_hook`, and a `tx` span from `pre_dispatch_tx_hook` to `post_dispatch_tx_hook`.
+//! The `authenticate` span covers the runtime authenticator. Every span is tagged with the `slot` number, and the
+//! transaction spans with the `tx_hash`, so that one transaction can be followed across components.
+use std::cell::{Cell, RefCell};
+
+use tracing::span::EnteredSpan;
+use tracing::{field, Span};
+
+std::thread_local! {
+    static SLOT_NUMBER: Cell<u64> = const { Cell::new(0) };
+    static TX_HASH: RefCell<Option<String>> = const { RefCell::new(None) };
+    static SLOT_SPAN: RefCell<Option<EnteredSpan>> = const { RefCell::new(None) };
+    static BATCH_SPAN: RefCell<Option<EnteredSpan>> = const { RefCell::new(None) };
+    static TX_SPAN: RefCell<Option<EnteredSpan>> = const { RefCell::new(None) };
+}
+
+/// The number of the slot being applied on this thread.
+pub fn current_slot() -> u64 {
+    SLOT_NUMBER.with(Cell::get)
+}
+
+/// Opens the `slot` span of the slot `slot_number`, at DA height `da_height`.
+pub fn enter_slot(slot_number: u64, da_height: u64) {
+    exit_slot();
+    SLOT_NUMBER.with(|slot| slot.set(slot_number));
+    let span = tracing::info_span!("slot", slot = slot_number, da_height).entered();
+    SLOT_SPAN.with(|slot_span| *slot_span.borrow_mut() = Some(span));
+}
+
+/// Closes the `slot` span, and the batch and transaction spans left open in it.
+pub fn exit_slot() {
+    exit_batch();
+    SLOT_SPAN.with(|slot_span| slot_span.borrow_mut().take());
+}
+
+/// Opens the `batch` span of the batch `batch_id` of `sequencer`.
+pub fn enter_batch(batch_id: &[u8], sequencer: &dyn std::fmt::Display) {
+    exit_batch();
+    let span = tracing::info_span!(
+        "batch",
+        slot = current_slot(),
+        batch_id = field::Empty,
+        %sequencer
+    );
+    if !span.is_disabled() {
+        span.record("batch_id", hex(batch_id));
+    }
+    BATCH_SPAN.with(|batch_span| *batch_span.borrow_mut() = Some(span.entered()));
+}
+
+/// Closes the `batch` span, and the transaction span left open in it.
+pub fn exit_batch() {
+    exit_tx();
+    BATCH_SPAN.with(|batch_span| batch_span.borrow_mut().take());
+}
+
+/// The `authenticate` span of a raw transaction. Its `tx_hash` is recorded by [`record_authenticated_tx`].
+pub fn authenticate_span() -> Span {
+    tracing::info_span!(
+        "authenticate",
+        slot = current_slot(),
+        tx_hash = field::Empty
+    )
+}
+
+/// Records the hash of the authenticated transaction on the `authenticate` span, for the `tx` span to come.
+pub fn record_authenticated_tx(span: &Span, tx_hash: &dyn std::fmt::Display) {
+    // Formatting the hash is skipped if nothing collects the spans, e.g. in the zkVM
+    let tx_hash = (!span.is_disabled()).then(|| tx_hash.to_string());
+    if let Some(tx_hash) = &tx_hash {
+        span.record("tx_hash", tx_hash.as_str());
+    }
+    TX_HASH.with(|current| *current.borrow_mut() = tx_hash);
+}
+
+/// Opens the `tx` span of the transaction authenticated last on this thread.
+pub fn enter_tx() {
+    exit_tx();
+    let span = tracing::info_span!("tx", slot = current_slot(), tx_hash = field::Empty);
+    TX_HASH.with(|tx_hash| {
+        if let Some(tx_hash) = tx_hash.borrow().as_deref() {
+            span.record("tx_hash", tx_hash);
+        }
+    });
+    TX_SPAN.with(|tx_span| *tx_span.borrow_mut() = Some(span.entered()));
+}
+
+/// Closes the `tx` span.
+pub fn exit_tx() {
+    TX_SPAN.with(|tx_span| tx_span.borrow_mut().take());
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
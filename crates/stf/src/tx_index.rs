@@ -0,0 +1,443 @@
+//! The `tx_index` module indexes the transactions of the rollup by address, and the bank transfers by token,
+//! in the accessory state. The index is not part of the state root and is only served over RPC.
+//!
+//! The runtime `begin_batch_hook` records the hashes of the transactions of the batch, and `post_dispatch_tx_hook`
+//! queues every applied transaction, with its hash and the transfers and mints it emitted. At the end of the slot,
+//! `finalize_hook` moves the queued transactions into the indexes.
+//! Transactions are identified by their slot and their hash, which finds them in the ledger (`ledger_getTxByHash`),
+//! see [`TxRef`]. Their position can't be used: the ledger also lists the reverted transactions, which are not queued.
+//!
+//! The index also lists every address and token it has seen, including the recipients of created and minted tokens,
+//! so that the state can be exported to a new genesis (see [`crate::genesis_export`]).
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "native")]
+use jsonrpsee::core::RpcResult;
+use serde::{Deserialize, Serialize};
+use sov_bank::TokenId;
+#[cfg(feature = "native")]
+use sov_modules_api::macros::rpc_gen;
+use sov_modules_api::transaction::{
+    AuthenticatedTransactionAndRawHash, AuthenticatedTransactionData,
+};
+#[cfg(feature = "native")]
+use sov_modules_api::ApiStateAccessor;
+use sov_modules_api::{
+    AccessoryStateMap, AccessoryStateValue, AccessoryStateVec, CallResponse, Context, Module,
+    ModuleError, ModuleId, ModuleInfo, NotInstantiable, Spec, StateReader, StateReaderAndWriter,
+    TxHash, WorkingSet,
+};
+use sov_state::namespaces::Accessory;
+
+/// The maximum number of items returned by a page of the index.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// A transaction of the ledger.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct TxRef {
+    /// The slot of the transaction, as recorded by [`crate::chain_info`].
+    pub slot_number: u64,
+    /// The hash of the transaction.
+    pub tx_hash: TxHash,
+}
+
+/// A bank transfer.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct TokenTransfer<Address> {
+    /// The sender of the tokens.
+    pub from: Address,
+    /// The recipient of the tokens.
+    pub to: Address,
+    /// The transferred token.
+    pub token_id: TokenId,
+    /// The transferred amount.
+    pub amount: u64,
+}
+
+/// Tokens credited to an address without a transfer: the initial balance of a created token, or a mint.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct TokenMint<Address> {
+    /// The recipient of the tokens.
+    pub to: Address,
+    /// The minted token.
+    pub token_id: TokenId,
+    /// The minted amount.
+    pub amount: u64,
+}
+
+/// An indexed bank transfer.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct Transfer<Address> {
+    /// The transaction that made the transfer.
+    pub tx: TxRef,
+    /// The transfer.
+    pub transfer: TokenTransfer<Address>,
+}
+
+/// A transaction queued for indexing.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+pub struct PendingTx<Address> {
+    /// The slot of the transaction.
+    pub slot_number: u64,
+    /// The hash of the transaction.
+    pub tx_hash: TxHash,
+    /// The sender of the transaction.
+    pub sender: Address,
+    /// The transfers made by the transaction.
+    pub transfers: Vec<TokenTransfer<Address>>,
+    /// The tokens created by the transaction.
+    pub created_tokens: Vec<TokenId>,
+    /// The tokens minted by the transaction, including the initial balances of the created tokens.
+    pub mints: Vec<TokenMint<Address>>,
+}
+
+/// A page of an index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// The items of the page, oldest first.
+    pub items: Vec<T>,
+    /// The number of items in the index.
+    pub total: u64,
+}
+
+/// Indexes the transactions by address and the transfers by token.
+#[derive(Clone, ModuleInfo)]
+pub struct TxIndex<S: Spec> {
+    /// The address of the module.
+    #[id]
+    pub id: ModuleId,
+
+    /// The hashes of the authenticated transactions of the current batch, in order.
+    #[state]
+    pub(crate) batch_tx_hashes: AccessoryStateVec<TxHash>,
+
+    /// The authenticated transactions of the current batch, matching `batch_tx_hashes`.
+    #[state]
+    pub(crate) batch_txs: AccessoryStateVec<AuthenticatedTransactionData<S>>,
+
+    /// The position in `batch_txs` after the last applied transaction.
+    #[state]
+    pub(crate) batch_cursor: AccessoryStateValue<u64>,
+
+    /// The transactions applied in the current slot, waiting to be indexed.
+    #[state]
+    pub(crate) pending_txs: AccessoryStateVec<PendingTx<S::Address>>,
+
+    /// The number of indexed transactions of each address.
+    #[state]
+    pub(crate) tx_counts: AccessoryStateMap<S::Address, u64>,
+
+    /// The transactions sent or received by each address, by position.
+    #[state]
+    pub(crate) txs_by_address: AccessoryStateMap<(S::Address, u64), TxRef>,
+
+    /// The number of indexed transfers of each token.
+    #[state]
+    pub(crate) transfer_counts: AccessoryStateMap<TokenId, u64>,
+
+    /// The transfers of each token, by position.
+    #[state]
+    pub(crate) transfers_by_token: AccessoryStateMap<(TokenId, u64), Transfer<S::Address>>,
+
+    /// Every indexed address, in the order they were first seen.
+    #[state]
+    pub(crate) addresses: AccessoryStateVec<S::Address>,
+
+    /// Every created or transferred token, in the order they were first seen.
+    #[state]
+    pub(crate) tokens: AccessoryStateVec<TokenId>,
+
+    /// The tokens listed in `tokens`.
+    #[state]
+    pub(crate) known_tokens: AccessoryStateMap<TokenId, ()>,
+}
+
+impl<S: Spec> Module for TxIndex<S> {
+    type Spec = S;
+
+    type Config = ();
+
+    type CallMessage = NotInstantiable;
+
+    type Event = ();
+
+    fn genesis(
+        &self,
+        _config: &Self::Config,
+        _working_set: &mut WorkingSet<S>,
+    ) -> Result<(), ModuleError> {
+        Ok(())
+    }
+
+    fn call(
+        &self,
+        msg: Self::CallMessage,
+        _context: &Context<Self::Spec>,
+        _working_set: &mut WorkingSet<S>,
+    ) -> Result<CallResponse, ModuleError> {
+        match msg {}
+    }
+}
+
+impl<S: Spec> TxIndex<S> {
+    /// Records the authenticated transactions of a batch, in order. Called by the runtime `begin_batch_hook`.
+    pub fn begin_batch(
+        &self,
+        txs: Vec<AuthenticatedTransactionAndRawHash<S>>,
+        accessory_state: &mut impl StateReaderAndWriter<Accessory>,
+    ) {
+        self.batch_tx_hashes.clear(accessory_state);
+        self.batch_txs.clear(accessory_state);
+        for tx in txs {
+            self.batch_tx_hashes.push(&tx.raw_tx_hash, accessory_state);
+            self.batch_txs.push(&tx.authenticated_tx, accessory_state);
+        }
+        self.batch_cursor.set(&0, accessory_state);
+    }
+
+    /// Returns the hash of the applied transaction `tx`: the first transaction of the batch after the previously
+    /// applied one that matches it. The transactions skipped before it were reverted. Called by the runtime
+    /// `post_dispatch_tx_hook`.
+    pub fn applied_tx_hash(
+        &self,
+        tx: &AuthenticatedTransactionData<S>,
+        accessory_state: &mut impl StateReaderAndWriter<Accessory>,
+    ) -> Option<TxHash> {
+        let cursor = self.batch_cursor.get(accessory_state).unwrap_or_default();
+        let len = self.batch_txs.len(accessory_state) as u64;
+        let position = (cursor..len).find(|&position| {
+            self.batch_txs
+                .get(position as usize, accessory_state)
+                .as_ref()
+                == Some(tx)
+        })?;
+        self.batch_cursor.set(&(position + 1), accessory_state);
+        self.batch_tx_hashes.get(position as usize, accessory_state)
+    }
+
+    /// Queues an applied transaction. Called by the runtime `post_dispatch_tx_hook`.
+    pub fn queue_tx(&self, tx: PendingTx<S::Address>, working_set: &mut WorkingSet<S>) {
+        self.pending_txs
+            .push(&tx, &mut working_set.accessory_state());
+    }
+
+    /// Indexes the transactions queued during the slot. Called by the runtime `finalize_hook`.
+    pub fn index_pending_txs(&self, accessory_state: &mut impl StateReaderAndWriter<Accessory>) {
+        let pending_txs: Vec<_> = self.pending_txs.iter(accessory_state).collect();
+        for pending in pending_txs {
+            let tx = TxRef {
+                slot_number: pending.slot_number,
+                tx_hash: pending.tx_hash,
+            };
+
+            // Index the transaction once for its sender, every party of its transfers and every minted address
+            let mut addresses = vec![pending.sender.clone()];
+            let parties = pending
+                .transfers
+                .iter()
+                .flat_map(|transfer| [&transfer.from, &transfer.to])
+                .chain(pending.mints.iter().map(|mint| &mint.to));
+            for address in parties {
+                if !addresses.contains(address) {
+                    addresses.push(address.clone());
+                }
+            }
+            for address in &addresses {
+                self.index_tx(address, &tx, accessory_state);
+            }
+
+            let minted_tokens = pending.mints.iter().map(|mint| &mint.token_id);
+            for token_id in pending.created_tokens.iter().chain(minted_tokens) {
+                self.register_token(token_id, accessory_state);
+            }
+            for transfer in pending.transfers {
+                let token_id = transfer.token_id;
+                self.register_token(&token_id, accessory_state);
+                let position = self
+                    .transfer_counts
+                    .get(&token_id, accessory_state)
+                    .unwrap_or_default();
+                self.transfers_by_token.set(
+                    &(token_id, position),
+                    &Transfer {
+                        tx: tx.clone(),
+                        transfer,
+                    },
+                    accessory_state,
+                );
+                self.transfer_counts
+                    .set(&token_id, &(position + 1), accessory_state);
+            }
+        }
+        self.pending_txs.clear(accessory_state);
+    }
+
+    /// Returns the transactions sent or received by `address`, starting at the `offset`-th one.
+    pub fn txs_by_address(
+        &self,
+        address: &S::Address,
+        offset: u64,
+        limit: u64,
+        state: &mut impl StateReader<Accessory>,
+    ) -> Page<TxRef> {
+        let total = self.tx_counts.get(address, state).unwrap_or_default();
+        let items = page_range(offset, limit, total)
+            .filter_map(|position| self.txs_by_address.get(&(address.clone(), position), state))
+            .collect();
+        Page { items, total }
+    }
+
+    /// Returns the transfers of `token_id`, starting at the `offset`-th one.
+    pub fn transfers_by_token(
+        &self,
+        token_id: &TokenId,
+        offset: u64,
+        limit: u64,
+        state: &mut impl StateReader<Accessory>,
+    ) -> Page<Transfer<S::Address>> {
+        let total = self
+            .transfer_counts
+            .get(token_id, state)
+            .unwrap_or_default();
+        let items = page_range(offset, limit, total)
+            .filter_map(|position| self.transfers_by_token.get(&(*token_id, position), state))
+            .collect();
+        Page { items, total }
+    }
+
+    /// Returns every indexed address.
+    pub fn known_addresses(&self, state: &mut impl StateReader<Accessory>) -> Vec<S::Address> {
+        self.addresses.iter(state).collect()
+    }
+
+    /// Returns every created or transferred token.
+    pub fn known_tokens(&self, state: &mut impl StateReader<Accessory>) -> Vec<TokenId> {
+        self.tokens.iter(state).collect()
+    }
+
+    fn index_tx(
+        &self,
+        address: &S::Address,
+        tx: &TxRef,
+        accessory_state: &mut impl StateReaderAndWriter<Accessory>,
+    ) {
+        let position = self
+            .tx_counts
+            .get(address, accessory_state)
+            .unwrap_or_default();
+        if position == 0 {
+            self.addresses.push(address, accessory_state);
+        }
+        self.txs_by_address
+            .set(&(address.clone(), position), tx, accessory_state);
+        self.tx_counts
+            .set(address, &(position + 1), accessory_state);
+    }
+
+    fn register_token(
+        &self,
+        token_id: &TokenId,
+        accessory_state: &mut impl StateReaderAndWriter<Accessory>,
+    ) {
+        if self.known_tokens.get(token_id, accessory_state).is_none() {
+            self.known_tokens.set(token_id, &(), accessory_state);
+            self.tokens.push(token_id, accessory_state);
+        }
+    }
+}
+
+/// Returns the bank transfers emitted so far in `working_set`.
+pub fn bank_transfers<S: Spec>(working_set: &WorkingSet<S>) -> Vec<TokenTransfer<S::Address>> {
+    working_set
+        .events()
+        .iter()
+        .filter_map(
+            |event| match event.downcast::<sov_bank::event::Event<S>>()? {
+                sov_bank::event::Event::TokenTransferred { from, to, coins } => {
+                    Some(TokenTransfer {
+                        from: from.clone(),
+                        to: to.clone(),
+                        token_id: coins.token_id,
+                        amount: coins.amount,
+                    })
+                }
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// Returns the tokens created so far in `working_set`.
+pub fn bank_created_tokens<S: Spec>(working_set: &WorkingSet<S>) -> Vec<TokenId> {
+    working_set
+        .events()
+        .iter()
+        .filter_map(
+            |event| match event.downcast::<sov_bank::event::Event<S>>()? {
+                sov_bank::event::Event::TokenCreated { coins, .. } => Some(coins.token_id),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// Returns the tokens minted so far in `working_set`, including the initial balances of the created tokens.
+pub fn bank_mints<S: Spec>(working_set: &WorkingSet<S>) -> Vec<TokenMint<S::Address>> {
+    working_set
+        .events()
+        .iter()
+        .filter_map(
+            |event| match event.downcast::<sov_bank::event::Event<S>>()? {
+                sov_bank::event::Event::TokenCreated { minter, coins, .. } => Some(TokenMint {
+                    to: minter.clone(),
+                    token_id: coins.token_id,
+                    amount: coins.amount,
+                }),
+                sov_bank::event::Event::TokenMinted {
+                    mint_to_identity,
+                    coins,
+                    ..
+                } => Some(TokenMint {
+                    to: mint_to_identity.clone(),
+                    token_id: coins.token_id,
+                    amount: coins.amount,
+                }),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// The positions of the page of `limit` items (at most [`MAX_PAGE_SIZE`]) starting at `offset`, in an index of
+/// `total` items.
+pub fn page_range(offset: u64, limit: u64, total: u64) -> std::ops::Range<u64> {
+    let start = offset.min(total);
+    start..start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(total)
+}
+
+#[cfg(feature = "native")]
+#[rpc_gen(client, server, namespace = "index")]
+impl<S: Spec> TxIndex<S> {
+    /// Returns a page of the transactions sent or received by `address`, oldest first.
+    #[rpc_method(name = "txsByAddress")]
+    pub fn rpc_txs_by_address(
+        &self,
+        address: S::Address,
+        offset: u64,
+        limit: u64,
+        state: &mut ApiStateAccessor<S>,
+    ) -> RpcResult<Page<TxRef>> {
+        Ok(self.txs_by_address(&address, offset, limit, state))
+    }
+
+    /// Returns a page of the transfers of `token_id`, oldest first.
+    #[rpc_method(name = "transfersByToken")]
+    pub fn rpc_transfers_by_token(
+        &self,
+        token_id: TokenId,
+        offset: u64,
+        limit: u64,
+        state: &mut ApiStateAccessor<S>,
+    ) -> RpcResult<Page<Transfer<S::Address>>> {
+        Ok(self.transfers_by_token(&token_id, offset, limit, state))
+    }
+}
@@ -7,16 +7,23 @@ if [ $? -ne 0 ]; then
     exit 1
 fi
 
-echo 'Running: '\''make clean-db'\'''
-make clean-db
+echo 'Running: '\''make init FORCE=1'\'''
+make init FORCE=1
 if [ $? -ne 0 ]; then
     echo "Expected exit code 0, got $?"
     exit 1
 fi
 
-echo 'Running: '\''cargo run --bin rollup'\'''
+echo 'Running: '\''make reset'\'''
+make reset
+if [ $? -ne 0 ]; then
+    echo "Expected exit code 0, got $?"
+    exit 1
+fi
+
+echo 'Running: '\''cargo run --bin rollup -- run'\'''
 output=$(mktemp)
-cargo run --bin rollup &> $output &
+cargo run --bin rollup -- run &> $output &
 background_process_pid=$!
 echo "Waiting for process with PID: $background_process_pid"
 until grep -q -i RPC $output